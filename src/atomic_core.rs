@@ -5,10 +5,11 @@
 // Version: 3.3 Gold/Platinum (Stabilized, EN)
 // =================================================================
 
-use std::f64::consts::{PI, TAU};
+use crate::fixed;
+use std::f64::consts::PI;
 
 /// Irrational constants for aperiodic drift (Fractal Resilience).
-const PHI: f64 = 1.6180339887498948;
+const PHI: f64 = 1.618_033_988_749_895;
 const EQUILIBRIUM: f64 = PHI / PI;
 const SEPTIMAL_SHIFT: f64 = 1.777;
 
@@ -16,6 +17,7 @@ const SEPTIMAL_SHIFT: f64 = 1.777;
 const MIN_VALENCE: f64 = 0.001;
 
 #[repr(C)]
+#[derive(Clone, Debug)]
 pub struct AtomicCore {
     /// Proton count — stability threshold (core mass).
     pub proton_count: u32,
@@ -134,17 +136,126 @@ impl AtomicCore {
     }
 }
 
+// =================================================================
+// FIXED-POINT TWIN — Q32.32, integer-only (src/fixed.rs)
+// =================================================================
+
+const PHI_Q: i64 = fixed::PHI;
+const EQUILIBRIUM_Q: i64 = fixed::from_f64(EQUILIBRIUM);
+const SEPTIMAL_SHIFT_Q: i64 = fixed::from_f64(SEPTIMAL_SHIFT);
+const MIN_VALENCE_Q: i64 = fixed::from_f64(MIN_VALENCE);
+
+/// AtomicCore in Q32.32: same state machine, same constants, no floats.
+/// Arguments and results are Q32.32 (`fixed::from_f64` / `to_f64` at the
+/// edges); trajectories track the f64 core to ~10^-9 per step, so
+/// thresholds flip at the same impacts except within rounding of an edge.
+/// Energies saturate at the Q32.32 bound (2^31) where the f64 core keeps
+/// growing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedAtomicCore {
+    pub proton_count: u32,
+    pub electron_cloud: i64,
+    pub valence_energy: i64,
+    pub scars_energy: i64,
+    pub mutation_phase: u8,
+    pub is_critical: bool,
+}
+
+impl FixedAtomicCore {
+    pub fn new(stability: u32) -> Self {
+        Self {
+            proton_count: stability.max(1),
+            electron_cloud: fixed::HALF,
+            valence_energy: fixed::ONE,
+            scars_energy: 0,
+            mutation_phase: 0,
+            is_critical: false,
+        }
+    }
+
+    pub fn sharpen_angles(&mut self, impact: i64) {
+        let abs_impact = impact.saturating_abs();
+
+        let mutation_multiplier = fixed::ONE + self.mutation_phase as i64 * fixed::from_f64(0.1);
+        let resistance = fixed::saturating_mul(
+            fixed::saturating_mul(fixed::from_int(self.proton_count.min(i32::MAX as u32) as i32), PHI_Q),
+            mutation_multiplier,
+        )
+        .max(fixed::ONE);
+
+        let angle = fixed::atan(fixed::saturating_div(abs_impact, resistance));
+
+        self.electron_cloud = fixed::cos(self.electron_cloud.saturating_add(angle)).abs().min(fixed::ONE);
+
+        self.valence_energy = self
+            .valence_energy
+            .saturating_add(fixed::saturating_mul(angle, fixed::from_f64(0.1)));
+
+        if abs_impact > resistance {
+            self.scars_energy = self.scars_energy.saturating_add(angle / 2);
+        }
+    }
+
+    pub fn find_the_middle(&mut self, input_entropy: i64) -> i64 {
+        self.valence_energy = self.valence_energy.max(MIN_VALENCE_Q);
+
+        let ratio = fixed::saturating_div(input_entropy, self.valence_energy);
+        let drift = ratio.saturating_sub(EQUILIBRIUM_Q);
+
+        if drift.saturating_abs() > fixed::from_f64(0.08) {
+            self.is_critical = true;
+
+            let step = drift.signum() * fixed::from_f64(0.02);
+            self.valence_energy = fixed::saturating_mul(self.valence_energy, fixed::ONE - step);
+
+            if drift.saturating_abs() > fixed::from_f64(0.15) {
+                self.trigger_mutation();
+            }
+        } else {
+            self.valence_energy =
+                fixed::saturating_mul(self.valence_energy, fixed::from_f64(0.95)) + fixed::from_f64(0.05);
+            self.is_critical = false;
+        }
+
+        self.valence_energy = self.valence_energy.max(MIN_VALENCE_Q);
+
+        drift
+    }
+
+    pub fn threat_probability(&self, input_entropy: i64) -> i64 {
+        if self.valence_energy <= 0 {
+            return fixed::ONE;
+        }
+
+        let drift = fixed::saturating_div(input_entropy, self.valence_energy).saturating_sub(EQUILIBRIUM_Q);
+        let s = fixed::sin(fixed::saturating_mul(fixed::saturating_mul(drift, fixed::PI), SEPTIMAL_SHIFT_Q));
+        fixed::saturating_mul(s, s)
+    }
+
+    fn trigger_mutation(&mut self) {
+        self.mutation_phase = (self.mutation_phase + 1) % 7;
+        self.scars_energy = fixed::saturating_mul(self.scars_energy, fixed::from_f64(0.9));
+        self.valence_energy = self.valence_energy.saturating_add(fixed::from_f64(0.2));
+    }
+}
+
 // =================================================================
 // C-FFI BINDINGS — Bridges for Python / TigerCore
 // =================================================================
+// Every binding except `core_create` dereferences a pointer handed in
+// from C, so they are `unsafe fn`: the caller vouches for the pointer.
 
 #[no_mangle]
 pub extern "C" fn core_create(stability: u32) -> *mut AtomicCore {
     Box::into_raw(Box::new(AtomicCore::new(stability)))
 }
 
+/// # Safety
+///
+/// `core_ptr` must be null or a pointer returned by [`core_create`] that
+/// has not been passed to [`core_destroy`] yet.
 #[no_mangle]
-pub extern "C" fn core_process_impact(
+pub unsafe extern "C" fn core_process_impact(
     core_ptr: *mut AtomicCore,
     impact: f64,
     entropy: f64,
@@ -159,24 +270,36 @@ pub extern "C" fn core_process_impact(
     core.threat_probability(entropy)
 }
 
+/// # Safety
+///
+/// `core_ptr` must be null or a pointer returned by [`core_create`] that
+/// has not been passed to [`core_destroy`] yet.
 #[no_mangle]
-pub extern "C" fn core_is_critical(core_ptr: *const AtomicCore) -> bool {
+pub unsafe extern "C" fn core_is_critical(core_ptr: *const AtomicCore) -> bool {
     if core_ptr.is_null() {
         return false;
     }
     unsafe { (*core_ptr).is_critical }
 }
 
+/// # Safety
+///
+/// `core_ptr` must be null or a pointer returned by [`core_create`] that
+/// has not been passed to [`core_destroy`] yet.
 #[no_mangle]
-pub extern "C" fn core_get_scars_energy(core_ptr: *const AtomicCore) -> f64 {
+pub unsafe extern "C" fn core_get_scars_energy(core_ptr: *const AtomicCore) -> f64 {
     if core_ptr.is_null() {
         return 0.0;
     }
     unsafe { (*core_ptr).scars_energy }
 }
 
+/// # Safety
+///
+/// `core_ptr` must be null or a pointer returned by [`core_create`] that
+/// has not been passed to [`core_destroy`] yet.
 #[no_mangle]
-pub extern "C" fn core_get_mutation_phase(core_ptr: *const AtomicCore) -> u8 {
+pub unsafe extern "C" fn core_get_mutation_phase(core_ptr: *const AtomicCore) -> u8 {
     if core_ptr.is_null() {
         return 0;
     }
@@ -184,16 +307,25 @@ pub extern "C" fn core_get_mutation_phase(core_ptr: *const AtomicCore) -> u8 {
 }
 
 /// NEW: Electron cloud getter (signal legitimacy / masking field)
+///
+/// # Safety
+///
+/// `core_ptr` must be null or a pointer returned by [`core_create`] that
+/// has not been passed to [`core_destroy`] yet.
 #[no_mangle]
-pub extern "C" fn core_get_electron_cloud(core_ptr: *const AtomicCore) -> f64 {
+pub unsafe extern "C" fn core_get_electron_cloud(core_ptr: *const AtomicCore) -> f64 {
     if core_ptr.is_null() {
         return 0.0;
     }
     unsafe { (*core_ptr).electron_cloud }
 }
 
+/// # Safety
+///
+/// `core_ptr` must be null or a pointer returned by [`core_create`]; it is
+/// freed here and must not be used afterwards.
 #[no_mangle]
-pub extern "C" fn core_destroy(core_ptr: *mut AtomicCore) {
+pub unsafe extern "C" fn core_destroy(core_ptr: *mut AtomicCore) {
     if !core_ptr.is_null() {
         unsafe {
             let _ = Box::from_raw(core_ptr);
@@ -0,0 +1,349 @@
+// src/audit.rs
+
+//! Statistical audit of the folding manifold
+//! -----------------------------------------
+//! A compact value always lies in [0, 1) — that is the `mod 1` at the end
+//! of every fold, not evidence of diffusion. The audit measures what the
+//! range check cannot, over the 32 fractional bits of the Q32.32 output:
+//!
+//! * **avalanche** — flipping one input bit (within the slot's declared
+//!   width) should flip each output bit with probability ½ (strict
+//!   avalanche criterion); both the mean and the worst input bit count;
+//! * **chi-square** — the top 8 output bits over random vectors, against
+//!   the uniform distribution (256 buckets, 255 d.o.f., p = 0.001);
+//! * **collisions** — vectors one unit apart in one attribute must not
+//!   land on the same output (exact) or within 2^-16 of it (near);
+//! * **nonce sensitivity** — the same input under the next epoch's salts,
+//!   and under related salts (bit j flipped in the nonce and in each
+//!   64-bit word of the key, so every compactifier sees a change whatever
+//!   part of the salts it reads), should again flip half the output bits.
+//!
+//! Inputs are drawn from a seeded xorshift64, so a report is reproducible
+//! from `(seed, samples)`. src/bin/manifold_audit.rs prints it as JSON.
+
+use crate::compactifier::{Compactifier, CompactifierKind};
+use crate::salt::{EpochSalts, SaltManager};
+use crate::schema::{Attrs, ATTR_COUNT, ATTR_SLOTS};
+use crate::string_state::StringState;
+
+/// Output bits audited (the Q32.32 fraction).
+pub const OUTPUT_BITS: u32 = 32;
+
+/// Allowed |mean flip rate − ½| for avalanche and nonce tests.
+pub const MEAN_BIAS_LIMIT: f64 = 0.01;
+
+/// Allowed |flip rate − ½| of the worst single input bit.
+pub const WORST_BIAS_LIMIT: f64 = 0.05;
+
+/// Allowed |flip rate − ½| of the worst single salt bit (fewer draws per bit).
+pub const WORST_SALT_BIAS_LIMIT: f64 = 0.1;
+
+/// Chi-square buckets (top 8 output bits).
+pub const CHI_BUCKETS: usize = 256;
+
+/// Upper 0.1 % point of χ² with 255 degrees of freedom.
+pub const CHI_CRITICAL: f64 = 330.52;
+
+/// Random vectors per sample in the chi-square test.
+pub const CHI_DRAWS_PER_SAMPLE: usize = 8;
+
+/// Distance (in 2^-32 units) below which two outputs nearly collide.
+pub const NEAR_DISTANCE: u32 = 1 << 16;
+
+/// Allowed near-collision rate; an ideal fold gives 2^-15 ≈ 3·10^-5.
+pub const NEAR_RATE_LIMIT: f64 = 1e-3;
+
+/// Allowed exact-collision rate; an ideal fold gives 2^-32.
+pub const EXACT_RATE_LIMIT: f64 = 1e-4;
+
+/// Bit positions probed by the related-salt test (one 64-bit word).
+pub const SALT_BITS: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AuditConfig {
+    /// Base vectors per test.
+    pub samples: usize,
+    pub seed: u64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self { samples: 2000, seed: 0x7163_E5D1_A11D_17E4 }
+    }
+}
+
+/// One test's verdict with its metrics.
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub pass: bool,
+    /// Human-readable pass condition.
+    pub limit: String,
+    pub metrics: Vec<(&'static str, f64)>,
+}
+
+impl Check {
+    pub fn metric(&self, name: &str) -> Option<f64> {
+        self.metrics.iter().find(|(n, _)| *n == name).map(|&(_, v)| v)
+    }
+
+    pub fn to_json(&self) -> String {
+        let metrics: Vec<String> = self.metrics.iter().map(|(n, v)| format!("\"{}\":{}", n, json_num(*v))).collect();
+        format!(
+            "{{\"name\":\"{}\",\"pass\":{},\"limit\":\"{}\",\"metrics\":{{{}}}}}",
+            self.name,
+            self.pass,
+            self.limit,
+            metrics.join(",")
+        )
+    }
+}
+
+/// All checks of one fold.
+#[derive(Clone, Debug)]
+pub struct SubjectReport {
+    pub subject: String,
+    pub checks: Vec<Check>,
+}
+
+impl SubjectReport {
+    pub fn pass(&self) -> bool {
+        self.checks.iter().all(|c| c.pass)
+    }
+
+    pub fn check(&self, name: &str) -> Option<&Check> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+
+    pub fn to_json(&self) -> String {
+        let checks: Vec<String> = self.checks.iter().map(Check::to_json).collect();
+        format!(
+            "{{\"subject\":\"{}\",\"pass\":{},\"checks\":[{}]}}",
+            self.subject,
+            self.pass(),
+            checks.join(",")
+        )
+    }
+}
+
+/// Full report: every subject, overall verdict.
+pub fn report_json(config: &AuditConfig, subjects: &[SubjectReport]) -> String {
+    let body: Vec<String> = subjects.iter().map(SubjectReport::to_json).collect();
+    format!(
+        "{{\"seed\":{},\"samples\":{},\"pass\":{},\"subjects\":[{}]}}",
+        config.seed,
+        config.samples,
+        subjects.iter().all(SubjectReport::pass),
+        body.join(",")
+    )
+}
+
+fn json_num(v: f64) -> String {
+    if v.is_finite() {
+        format!("{:.6}", v)
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Seeded xorshift64.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A vector with every slot uniform over its declared width.
+    fn attrs(&mut self) -> Attrs {
+        let mut a = [0i64; ATTR_COUNT];
+        for (v, slot) in a.iter_mut().zip(ATTR_SLOTS.iter()) {
+            if slot.bits > 0 {
+                *v = (self.next() >> (64 - slot.bits)) as i64;
+            }
+        }
+        a
+    }
+}
+
+fn flips(x: u32, y: u32) -> u32 {
+    (x ^ y).count_ones()
+}
+
+fn bias(flipped: u64, trials: u64) -> f64 {
+    if trials == 0 {
+        return 0.0;
+    }
+    flipped as f64 / (trials as f64 * OUTPUT_BITS as f64) - 0.5
+}
+
+/// Input-side tests over any `Attrs → Q32.32` fold.
+pub fn input_checks(config: &AuditConfig, fold: &mut dyn FnMut(&Attrs) -> i64) -> Vec<Check> {
+    let mut out = |a: &Attrs| fold(a) as u32;
+    vec![
+        avalanche(config, &mut out),
+        chi_square(config, &mut out),
+        collisions(config, &mut out),
+    ]
+}
+
+fn avalanche(config: &AuditConfig, out: &mut dyn FnMut(&Attrs) -> u32) -> Check {
+    let mut rng = Rng::new(config.seed);
+    let positions: Vec<(usize, u32)> = ATTR_SLOTS
+        .iter()
+        .enumerate()
+        .flat_map(|(i, slot)| (0..slot.bits).map(move |b| (i, b)))
+        .collect();
+    let mut per_bit = vec![0u64; positions.len()];
+    for _ in 0..config.samples {
+        let a = rng.attrs();
+        let base = out(&a);
+        for (k, &(i, b)) in positions.iter().enumerate() {
+            let mut v = a;
+            v[i] ^= 1 << b;
+            per_bit[k] += flips(base, out(&v)) as u64;
+        }
+    }
+    let trials = config.samples as u64;
+    let mean = bias(per_bit.iter().sum(), trials * positions.len() as u64);
+    let worst = per_bit.iter().map(|&f| bias(f, trials).abs()).fold(0.0, f64::max);
+    Check {
+        name: "avalanche",
+        pass: mean.abs() <= MEAN_BIAS_LIMIT && worst <= WORST_BIAS_LIMIT,
+        limit: format!("|mean-0.5| <= {}, worst input bit |p-0.5| <= {}", MEAN_BIAS_LIMIT, WORST_BIAS_LIMIT),
+        metrics: vec![
+            ("flip_rate", 0.5 + mean),
+            ("worst_bit_bias", worst),
+            ("input_bits", positions.len() as f64),
+        ],
+    }
+}
+
+fn chi_square(config: &AuditConfig, out: &mut dyn FnMut(&Attrs) -> u32) -> Check {
+    let mut rng = Rng::new(config.seed ^ 0xC41);
+    let draws = config.samples * CHI_DRAWS_PER_SAMPLE;
+    let mut buckets = [0u64; CHI_BUCKETS];
+    for _ in 0..draws {
+        buckets[(out(&rng.attrs()) >> 24) as usize] += 1;
+    }
+    let expected = draws as f64 / CHI_BUCKETS as f64;
+    let chi2: f64 = buckets.iter().map(|&n| (n as f64 - expected).powi(2) / expected).sum();
+    Check {
+        name: "chi_square",
+        pass: chi2 <= CHI_CRITICAL,
+        limit: format!("chi2 <= {} (df {}, p 0.001)", CHI_CRITICAL, CHI_BUCKETS - 1),
+        metrics: vec![("chi2", chi2), ("draws", draws as f64)],
+    }
+}
+
+fn collisions(config: &AuditConfig, out: &mut dyn FnMut(&Attrs) -> u32) -> Check {
+    let mut rng = Rng::new(config.seed ^ 0xC011);
+    let (mut pairs, mut exact, mut near) = (0u64, 0u64, 0u64);
+    for _ in 0..config.samples {
+        let a = rng.attrs();
+        let base = out(&a);
+        for (i, slot) in ATTR_SLOTS.iter().enumerate() {
+            if slot.bits == 0 {
+                continue;
+            }
+            let max = (1i64 << slot.bits) - 1;
+            for delta in [-1i64, 1] {
+                let mut v = a;
+                v[i] += delta;
+                if !(0..=max).contains(&v[i]) {
+                    continue;
+                }
+                let y = out(&v);
+                let distance = base.wrapping_sub(y).min(y.wrapping_sub(base));
+                pairs += 1;
+                exact += (distance == 0) as u64;
+                near += (distance < NEAR_DISTANCE) as u64;
+            }
+        }
+    }
+    let rate = |n: u64| if pairs == 0 { 0.0 } else { n as f64 / pairs as f64 };
+    Check {
+        name: "collisions",
+        pass: rate(exact) <= EXACT_RATE_LIMIT && rate(near) <= NEAR_RATE_LIMIT,
+        limit: format!("exact rate <= {}, near rate (< 2^-16) <= {}", EXACT_RATE_LIMIT, NEAR_RATE_LIMIT),
+        metrics: vec![("pairs", pairs as f64), ("exact_rate", rate(exact)), ("near_rate", rate(near))],
+    }
+}
+
+/// Related salts: bit `j` flipped in the nonce and in every key word.
+fn flip_salt_bit(salts: &EpochSalts, j: usize) -> EpochSalts {
+    let mut s = *salts;
+    s.nonce ^= 1 << j;
+    for word in s.key.chunks_exact_mut(8) {
+        word[j / 8] ^= 1 << (j % 8);
+    }
+    s
+}
+
+/// Salt-side tests of one compactifier.
+pub fn nonce_checks(config: &AuditConfig, compactifier: &dyn Compactifier, manager: &SaltManager) -> Vec<Check> {
+    let mut rng = Rng::new(config.seed ^ 0x0A0CE);
+    let out = |a: &Attrs, s: &EpochSalts| compactifier.compactify(a, s) as u32;
+
+    let (mut epoch_flips, mut unchanged) = (0u64, 0u64);
+    let mut per_bit = vec![0u64; SALT_BITS];
+    for n in 0..config.samples {
+        let a = rng.attrs();
+        let salts = manager.derive(n as u64);
+        let next = manager.derive(n as u64 + 1);
+        let base = out(&a, &salts);
+        let f = flips(base, out(&a, &next));
+        epoch_flips += f as u64;
+        unchanged += (f == 0) as u64;
+        for (j, count) in per_bit.iter_mut().enumerate() {
+            *count += flips(base, out(&a, &flip_salt_bit(&salts, j))) as u64;
+        }
+    }
+    let trials = config.samples as u64;
+    let epoch_bias = bias(epoch_flips, trials);
+    let related_bias = bias(per_bit.iter().sum(), trials * SALT_BITS as u64);
+    let worst = per_bit.iter().map(|&f| bias(f, trials).abs()).fold(0.0, f64::max);
+    vec![
+        Check {
+            name: "epoch_sensitivity",
+            pass: epoch_bias.abs() <= MEAN_BIAS_LIMIT,
+            limit: format!("|mean-0.5| <= {}", MEAN_BIAS_LIMIT),
+            metrics: vec![
+                ("flip_rate", 0.5 + epoch_bias),
+                ("unchanged_rate", unchanged as f64 / trials.max(1) as f64),
+            ],
+        },
+        Check {
+            name: "related_salt",
+            pass: related_bias.abs() <= MEAN_BIAS_LIMIT && worst <= WORST_SALT_BIAS_LIMIT,
+            limit: format!("|mean-0.5| <= {}, worst salt bit |p-0.5| <= {}", MEAN_BIAS_LIMIT, WORST_SALT_BIAS_LIMIT),
+            metrics: vec![("flip_rate", 0.5 + related_bias), ("worst_bit_bias", worst)],
+        },
+    ]
+}
+
+/// Every check of one compactifier under salts from `master_key`.
+pub fn audit_compactifier(config: &AuditConfig, kind: CompactifierKind, master_key: &[u8]) -> SubjectReport {
+    let manager = SaltManager::new(master_key);
+    let compactifier = kind.build();
+    let salts = manager.derive(0);
+    let mut fold = |a: &Attrs| compactifier.compactify(a, &salts);
+    let mut checks = input_checks(config, &mut fold);
+    checks.extend(nonce_checks(config, compactifier.as_ref(), &manager));
+    SubjectReport { subject: kind.as_str().to_string(), checks }
+}
+
+/// Input-side checks through the live `StringState::compactify` path
+/// (wall-clock epochs; a rotation mid-pair skews at most a few pairs).
+pub fn audit_string_state(config: &AuditConfig, state: &mut StringState) -> SubjectReport {
+    let subject = format!("string_state/{}", state.compactifier().kind().as_str());
+    let mut fold = |a: &Attrs| state.compactify(a);
+    SubjectReport { subject, checks: input_checks(config, &mut fold) }
+}
@@ -0,0 +1,80 @@
+// =================================================================
+// Project: TigerΔ (Tiger Delta)
+// Module: manifold_audit.rs
+// Description: Statistical audit of StringState and every Compactifier
+//              (avalanche, chi-square, collisions, nonce sensitivity).
+// =================================================================
+//
+// manifold_audit [--samples N] [--seed S] [--out report.json] [--strict]
+//
+// JSON-звіт — у stdout або у --out; короткий підсумок — у stderr.
+// StringState береться як у main.rs (TIGER_MASTER_KEY_FILE,
+// TIGER_COMPACTIFIER). З --strict код виходу 1, якщо хоч одна
+// перевірка не пройшла.
+
+use anyhow::{anyhow, bail, Context};
+use std::fs;
+use std::path::PathBuf;
+use tiger_delta_ai_safety::audit::{self, AuditConfig};
+use tiger_delta_ai_safety::compactifier::CompactifierKind;
+use tiger_delta_ai_safety::salt::SaltManager;
+use tiger_delta_ai_safety::string_state::StringState;
+
+/// Master key of the per-compactifier subjects: fixed, so reports compare.
+const AUDIT_KEY: &[u8] = b"tiger-delta/manifold-audit/v1";
+
+struct Args {
+    config: AuditConfig,
+    out: Option<PathBuf>,
+    strict: bool,
+}
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut args = Args { config: AuditConfig::default(), out: None, strict: false };
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+        match arg.as_str() {
+            "--samples" => args.config.samples = value()?.parse().context("--samples")?,
+            "--seed" => args.config.seed = value()?.parse().context("--seed")?,
+            "--out" => args.out = Some(PathBuf::from(value()?)),
+            "--strict" => args.strict = true,
+            _ => bail!("usage: manifold_audit [--samples N] [--seed S] [--out FILE] [--strict]"),
+        }
+    }
+    if args.config.samples == 0 {
+        bail!("--samples must be positive");
+    }
+    Ok(args)
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = parse_args()?;
+
+    let mut state = StringState::with_salts(SaltManager::from_env()?)
+        .with_compactifier(CompactifierKind::from_env()?.build());
+    let mut subjects = vec![audit::audit_string_state(&args.config, &mut state)];
+    for kind in CompactifierKind::ALL {
+        subjects.push(audit::audit_compactifier(&args.config, kind, AUDIT_KEY));
+    }
+
+    for subject in &subjects {
+        let failed: Vec<&str> = subject.checks.iter().filter(|c| !c.pass).map(|c| c.name).collect();
+        if failed.is_empty() {
+            eprintln!("✅ {:<22} pass", subject.subject);
+        } else {
+            eprintln!("❌ {:<22} fail: {}", subject.subject, failed.join(", "));
+        }
+    }
+
+    let report = audit::report_json(&args.config, &subjects);
+    match &args.out {
+        Some(path) => fs::write(path, &report).with_context(|| format!("cannot write {}", path.display()))?,
+        None => println!("{}", report),
+    }
+
+    if args.strict && !subjects.iter().all(|s| s.pass()) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
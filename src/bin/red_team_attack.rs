@@ -10,18 +10,19 @@
 
 use std::f64::consts::PI;
 use std::time::Instant;
+use tiger_delta_ai_safety::schema::{normalize, used_slots, Attrs, ATTR_COUNT, ATTR_SLOTS};
 
 /// High-precision Golden Ratio for maximum irrational resonance
-const PHI: f64 = 1.6180339887498948;
+const PHI: f64 = 1.618_033_988_749_895;
 
 /// Generates a malicious packet feature vector.
 /// Simulates patterns designed to synchronize with the folding manifold logic.
-fn generate_malicious_vector(seed: u64) -> [f64; 10] {
-    let mut vec = [0.0; 10];
-    for i in 0..10 {
+fn generate_malicious_vector(seed: u64) -> Attrs {
+    let mut vec = [0i64; ATTR_COUNT];
+    for (k, slot) in used_slots().enumerate() {
         // Attack pattern: complex cosine wave with phase shift and irrational drift
-        let angle = (seed.wrapping_add(i as u64) as f64 * 0.1337) + PI;
-        vec[i] = angle.cos().abs();
+        let angle = (seed.wrapping_add(k as u64) as f64 * 0.1337) + PI;
+        vec[slot] = ATTR_SLOTS[slot].normalization.invert(angle.cos().abs());
     }
     vec
 }
@@ -43,7 +44,7 @@ fn main() {
         let packet = generate_malicious_vector(i as u64);
 
         // Simulated Kernel Folding Manifold logic
-        let folding = packet.iter().fold(0.0, |acc, &val| {
+        let folding = normalize(&packet).iter().fold(0.0, |acc, &val| {
             (acc + (val * PI).sin()).abs() * PHI
         }) % 1.0;
 
@@ -2,7 +2,7 @@ use std::thread;
 use std::time::Instant;
 use std::f64::consts::PI;
 
-const PHI: f64 = 1.6180339887498948482;
+const PHI: f64 = 1.618_033_988_749_895;
 const ITERATIONS: u64 = 10_000_000;
 
 #[inline(always)]
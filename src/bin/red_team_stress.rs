@@ -1,13 +1,14 @@
 use std::f64::consts::PI;
 use std::time::Instant;
+use tiger_delta_ai_safety::schema::{normalize, used_slots, Attrs, ATTR_COUNT, ATTR_SLOTS};
 
-const PHI: f64 = 1.6180339887498948;
+const PHI: f64 = 1.618_033_988_749_895;
 
-fn generate_malicious_vector(seed: u64) -> [f64; 10] {
-    let mut vec = [0.0; 10];
-    for i in 0..10 {
-        let angle = (seed.wrapping_add(i as u64) as f64 * 0.1337) + PI;
-        vec[i] = angle.cos().abs();
+fn generate_malicious_vector(seed: u64) -> Attrs {
+    let mut vec = [0i64; ATTR_COUNT];
+    for (k, slot) in used_slots().enumerate() {
+        let angle = (seed.wrapping_add(k as u64) as f64 * 0.1337) + PI;
+        vec[slot] = ATTR_SLOTS[slot].normalization.invert(angle.cos().abs());
     }
     vec
 }
@@ -23,7 +24,7 @@ fn main() {
 
     for i in 1..=iterations {
         let packet = generate_malicious_vector(i as u64);
-        let folding = packet.iter().fold(0.0, |acc, &val| {
+        let folding = normalize(&packet).iter().fold(0.0, |acc, &val| {
             (acc + (val * PI).sin()).abs() * PHI
         }) % 1.0;
         state = (state + folding) / 2.0;
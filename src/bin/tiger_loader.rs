@@ -1,63 +1,154 @@
-use aya::programs::{Xdp, XdpFlags};
-use aya::maps::{Array, PerCpuArray}; // Додано PerCpuArray
-use aya::{include_bytes_aligned, Bpf};
-use std::{env, thread, time::{Duration, Instant}};
-use rand::{Rng, thread_rng};
+mod aggregate;
+mod cidr;
+mod config;
+mod daemon;
+mod egress;
+mod instance;
+mod netlink;
+mod pin;
+mod policy;
+mod stats;
+mod topk;
+mod xsk;
+
+use aya::maps::Map;
+use aya::Bpf;
+use anyhow::{bail, Context};
+use std::{env, path::PathBuf, thread, time::Duration};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use signal_hook::consts::{SIGINT, SIGTERM, SIGUSR1};
+
+use crate::config::LoaderConfig;
+use crate::instance::Instance;
+use tiger_delta_ai_safety::salt::SaltManager;
+use tiger_delta_ai_safety::string_state::StringState;
+
+const USAGE: &str = "Usage: tiger_loader --config <FILE>\n       tiger_loader [--daemon] [--egress] <INTERFACE> [CIDR_LIST]";
+
+/// Takes ownership of a map so several components can hold theirs at once.
+pub(crate) fn take_map(bpf: &mut Bpf, name: &str) -> anyhow::Result<Map> {
+    bpf.take_map(name)
+        .with_context(|| format!("map '{}' not found in object", name))
+}
+
+/// `--config FILE`, or the single-interface command line.
+fn parse_args() -> anyhow::Result<LoaderConfig> {
+    // --daemon: ядро + когнітивний шар (Brain на кожне джерело) в одному процесі
+    // --egress: TC-спостерігач вихідного трафіку (Brain на кожне призначення)
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(pos) = args.iter().position(|a| a == "--config") {
+        let path = args.get(pos + 1).context(USAGE)?;
+        return LoaderConfig::load(&PathBuf::from(path));
+    }
+
+    let daemon_mode = args.iter().any(|a| a == "--daemon");
+    let egress_mode = args.iter().any(|a| a == "--egress");
+    let mut positional = args.iter().filter(|a| !a.starts_with("--")).cloned();
+    let Some(iface) = positional.next() else {
+        bail!(USAGE);
+    };
+    LoaderConfig::from_args(&iface, positional.next(), daemon_mode, egress_mode)
+}
 
 fn main() -> Result<(), anyhow::Error> {
-    let iface = env::args().nth(1).expect("Usage: tiger_loader <INTERFACE>");
-
-    let mut bpf = Bpf::load(include_bytes_aligned!(
-        "../../src/kernel/tiger_delta_xdp.o"
-    ))?;
-
-    let program: &mut Xdp = bpf.program_mut("tiger_delta_xdp")?.try_into()?;
-    program.load()?;
-    program.attach(&iface, XdpFlags::SKB_MODE)?;
-
-    let mut config_map: Array<_, u64> = Array::try_from(bpf.map("config_map")?)?;
-    let mut policy_map: Array<_, u32> = Array::try_from(bpf.map("policy_map")?)?;
-    
-    // Отримуємо Per-CPU мапу
-    let resonance_map: PerCpuArray<_, u64> = PerCpuArray::try_from(bpf.map("resonance_state")?)?;
-
-    let mut rng = thread_rng();
-    let mut last_rotation = Instant::now();
-
-    println!("🐅 TigerΔ v1.0 Ulenspiegel: Per-CPU High-Performance Mode");
-
-    loop {
-        // --- Salt Rotation (кожні 30 сек) ---
-        if last_rotation.elapsed() >= Duration::from_secs(30) {
-            config_map.set(0, rng.gen::<u64>(), 0)?;
-            config_map.set(1, rng.gen::<u64>(), 0)?;
-            last_rotation = Instant::now();
-            println!("🔄 Dynamic Manifold Shifted");
+    let config = parse_args()?;
+
+    // Солі виводяться з майстер-ключа та епохи (HKDF), тож перезапущений
+    // лоадер і вузли флоту з тим самим ключем публікують ті самі значення
+    // StringState володіє менеджером солей: ядро і Brain бачать одну епоху
+    let mut string_state = StringState::with_salts(
+        SaltManager::from_env()
+            .with_context(|| format!("cannot read {}", tiger_delta_ai_safety::salt::MASTER_KEY_ENV))?,
+    )
+    .with_compactifier(config.compactifier.build())
+    .with_normalizer(config.normalize.clone());
+    if let Some(path) = &config.state_file {
+        if string_state
+            .load_snapshot(path)
+            .with_context(|| format!("cannot restore state from {}", path.display()))?
+        {
+            println!("💾 State restored from {}", path.display());
         }
+    }
 
-        // --- Агрегація ентропії з усіх ядер ---
-        let mut global_entropy = 0u64;
-        let mut cpu_count = 0;
+    let mut instances = Vec::new();
+    for set in config.map_sets() {
+        instances.push(Instance::load(&set, &config, string_state.salts().current())?);
+    }
+
+    // SIGINT/SIGTERM — чисте від'єднання; SIGUSR1 — вихід із залишенням
+    // програми в ядрі (передача новому лоадеру при оновленні)
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let handover = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(SIGINT, Arc::clone(&shutdown))?;
+    signal_hook::flag::register(SIGTERM, Arc::clone(&shutdown))?;
+    signal_hook::flag::register(SIGUSR1, Arc::clone(&handover))?;
+
+    println!(
+        "🐅 TigerΔ v1.0 Ulenspiegel: Per-CPU High-Performance Mode ({} interface(s), {} map set(s), {} compactifier, normalize {})",
+        config.ifaces.len(),
+        instances.len(),
+        config.compactifier.as_str(),
+        config.normalize.spec()
+    );
 
-        // resonance_map.get(&0, 0) повертає PerCpuValues<u64>
-        if let Ok(values) = resonance_map.get(&0, 0) {
-            for cpu_val in values.iter() {
-                global_entropy = global_entropy.wrapping_add(*cpu_val);
-                cpu_count += 1;
+    // Епоха, яку бачить ядро; публікуємо щоразу, коли поточна від неї
+    // відрізняється, хто б її не повернув
+    let mut published = string_state.salts().current().epoch;
+    while !shutdown.load(Ordering::Relaxed) && !handover.load(Ordering::Relaxed) {
+        // --- Salt Rotation (30 с у спокої, до 5 с під тиском) ---
+        string_state.salts_mut().refresh();
+        let salts = string_state.salts().current();
+        if salts.epoch != published {
+            for instance in instances.iter_mut() {
+                instance.publish_salts(salts)?;
             }
+            published = salts.epoch;
+            println!(
+                "🔄 Dynamic Manifold Shifted | epoch={} period={}s",
+                salts.epoch,
+                string_state.salts().period().as_secs()
+            );
         }
 
-        let avg_entropy = if cpu_count > 0 { global_entropy / cpu_count as u64 } else { 0 };
-        let is_attack = avg_entropy > 0x8000_0000_0000_0000;
-        
-        policy_map.set(0, if is_attack { 1 } else { 0 }, 0)?;
+        let mut pressure: f64 = 0.0;
+        let mut fragments = Vec::new();
+        for instance in instances.iter_mut() {
+            instance.check_health();
+            let tick = instance.tick(&mut string_state)?;
+            pressure = pressure.max(tick.pressure);
+            fragments.push(tick.json);
+        }
+        // Найгарячіший інтерфейс задає темп ротації для всіх; пік, який
+        // Brain зафіксував на пакетах, тримається до наступної ротації
+        string_state.salts_mut().report_pressure(pressure);
 
-        println!(
-            "Entropy (Global Avg): {:016X} | Status: {}", 
-            avg_entropy, 
-            if is_attack { "🔥 BLOCKING" } else { "🟢 STABLE" }
+        // --- Machine-readable report ---
+        let report = format!(
+            "{{\"timestamp\":{},\"salt_epoch\":{},\"instances\":[{}]}}\n",
+            stats::unix_time(),
+            string_state.salts().current().epoch,
+            fragments.join(","),
         );
+        if let Err(e) = stats::write_report(&config.stats_file, &report) {
+            eprintln!("⚠️ Stats report {} not written: {:#}", config.stats_file.display(), e);
+        }
 
         thread::sleep(Duration::from_millis(500));
     }
+
+    // Навчена нормалізація переживає перезапуск і передачу
+    if let Some(path) = &config.state_file {
+        if let Err(e) = string_state.save_snapshot(path) {
+            eprintln!("⚠️ State snapshot {} not written: {:#}", path.display(), e);
+        }
+    }
+
+    let handover = handover.load(Ordering::Relaxed);
+    for instance in instances {
+        instance.shutdown(handover)?;
+    }
+
+    Ok(())
 }
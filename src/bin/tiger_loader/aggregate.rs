@@ -0,0 +1,202 @@
+// src/bin/tiger_loader/aggregate.rs
+//
+// Per-CPU resonance aggregation.
+// Стан `resonance_state` живе у верхній половині u64, тому наївна сума
+// `wrapping_add` переповнюється саме тоді, коли значення високі.
+// Тут: безпечне середнє (u128), медіана, максимум, розкид між CPU та
+// ковзні вікна (секунди/хвилини) з визначенням тренду.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const U64_RANGE: f64 = 18_446_744_073_709_551_616.0; // 2^64
+
+/// Resonance as a fraction of the u64 range, in [0, 1).
+pub fn to_level(value: u64) -> f64 {
+    value as f64 / U64_RANGE
+}
+
+/// One reading of the per-CPU map.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuSnapshot {
+    pub cpus: usize,
+    pub mean: u64,
+    pub median: u64,
+    pub max: u64,
+    /// Standard deviation between CPUs, in level units.
+    pub spread: f64,
+}
+
+impl CpuSnapshot {
+    pub fn from_values(values: &[u64]) -> Self {
+        let cpus = values.len();
+        if cpus == 0 {
+            return Self::default();
+        }
+
+        let sum: u128 = values.iter().map(|&v| v as u128).sum();
+        let mean = (sum / cpus as u128) as u64;
+
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        let median = if cpus % 2 == 1 {
+            sorted[cpus / 2]
+        } else {
+            ((sorted[cpus / 2 - 1] as u128 + sorted[cpus / 2] as u128) / 2) as u64
+        };
+
+        let mean_level = to_level(mean);
+        let variance = values
+            .iter()
+            .map(|&v| (to_level(v) - mean_level).powi(2))
+            .sum::<f64>()
+            / cpus as f64;
+
+        Self {
+            cpus,
+            mean,
+            median,
+            max: sorted[cpus - 1],
+            spread: variance.sqrt(),
+        }
+    }
+}
+
+/// Time-bounded window of level samples.
+pub struct RollingWindow {
+    span: Duration,
+    samples: VecDeque<(Instant, f64)>,
+}
+
+impl RollingWindow {
+    pub fn new(span: Duration) -> Self {
+        Self { span, samples: VecDeque::new() }
+    }
+
+    pub fn push(&mut self, now: Instant, level: f64) {
+        self.samples.push_back((now, level));
+        while let Some(&(t, _)) = self.samples.front() {
+            if now.duration_since(t) > self.span {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().map(|&(_, v)| v).sum::<f64>() / self.samples.len() as f64
+    }
+
+    pub fn max(&self) -> f64 {
+        self.samples.iter().map(|&(_, v)| v).fold(0.0, f64::max)
+    }
+
+    /// Least-squares slope in level units per minute.
+    pub fn slope_per_min(&self) -> f64 {
+        let n = self.samples.len();
+        let first = match self.samples.front() {
+            Some(&(t, _)) if n >= 2 => t,
+            _ => return 0.0,
+        };
+
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .map(|&(t, v)| (t.duration_since(first).as_secs_f64() / 60.0, v))
+            .collect();
+        let mx = points.iter().map(|p| p.0).sum::<f64>() / n as f64;
+        let my = points.iter().map(|p| p.1).sum::<f64>() / n as f64;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mx).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mx) * (p.1 - my)).sum();
+
+        if sxx > 0.0 { sxy / sxx } else { 0.0 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Flat,
+    Falling,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AggregateReport {
+    pub snapshot: CpuSnapshot,
+    pub short_mean: f64,
+    pub long_mean: f64,
+    pub long_max: f64,
+    pub slope_per_min: f64,
+    pub trend: Trend,
+    /// Level handed to the drop policy.
+    pub decision_level: f64,
+}
+
+impl AggregateReport {
+    pub fn to_json(self) -> String {
+        let s = &self.snapshot;
+        format!(
+            "{{\"cpus\":{},\"mean\":\"{:016X}\",\"median\":\"{:016X}\",\"max\":\"{:016X}\",\"spread\":{:.6},\
+             \"short_mean\":{:.6},\"long_mean\":{:.6},\"long_max\":{:.6},\"slope_per_min\":{:.6},\"trend\":\"{:?}\",\"decision_level\":{:.6}}}",
+            s.cpus, s.mean, s.median, s.max, s.spread,
+            self.short_mean, self.long_mean, self.long_max, self.slope_per_min, self.trend, self.decision_level,
+        )
+    }
+}
+
+/// Short (seconds) and long (minutes) windows over the per-CPU median.
+pub struct Aggregator {
+    short: RollingWindow,
+    long: RollingWindow,
+    /// Short/long mean gap that counts as a trend.
+    pub trend_margin: f64,
+}
+
+impl Aggregator {
+    pub fn new(short: Duration, long: Duration) -> Self {
+        Self {
+            short: RollingWindow::new(short),
+            long: RollingWindow::new(long),
+            trend_margin: 0.02,
+        }
+    }
+
+    pub fn update(&mut self, now: Instant, values: &[u64]) -> AggregateReport {
+        let snapshot = CpuSnapshot::from_values(values);
+
+        // Медіана: один «гарячий» CPU (напр. RSS-перекіс) не вмикає скидання сам
+        let level = to_level(snapshot.median);
+        self.short.push(now, level);
+        self.long.push(now, level);
+
+        let short_mean = self.short.mean();
+        let long_mean = self.long.mean();
+        let trend = if short_mean > long_mean + self.trend_margin {
+            Trend::Rising
+        } else if short_mean < long_mean - self.trend_margin {
+            Trend::Falling
+        } else {
+            Trend::Flat
+        };
+
+        // Ескалація реагує одразу, спад — через згладжене вікно
+        let decision_level = match trend {
+            Trend::Rising => level.max(short_mean),
+            _ => short_mean,
+        };
+
+        AggregateReport {
+            snapshot,
+            short_mean,
+            long_mean,
+            long_max: self.long.max(),
+            slope_per_min: self.long.slope_per_min(),
+            trend,
+            decision_level,
+        }
+    }
+}
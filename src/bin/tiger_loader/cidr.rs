@@ -0,0 +1,266 @@
+// src/bin/tiger_loader/cidr.rs
+//
+// CIDR allow/deny lists for the XDP core.
+// Файл конфігурації — по одному префіксу на рядок:
+//
+//     # management
+//     allow 10.20.0.0/16
+//     deny  203.0.113.0/24
+//     deny  2001:db8:bad::/48
+//
+// Адреса без префікса трактується як хост (/32 або /128).
+// Лоадер перечитує файл при зміні mtime і синхронізує LPM-мапи:
+// нові записи додаються, видалені — прибираються, лічильники
+// незмінених записів зберігаються. Мапи закріплені й переживають
+// перезапуск, тож на старті їхній вміст звіряється з файлом: префікси,
+// видалені, поки лоадера не було, теж прибираються.
+
+use anyhow::{anyhow, bail, Context};
+use aya::maps::lpm_trie::{Key, LpmTrie};
+use aya::maps::MapData;
+use aya::Bpf;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::take_map;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ListKind {
+    Allow,
+    Deny,
+}
+
+impl fmt::Display for ListKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListKind::Allow => write!(f, "allow"),
+            ListKind::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+/// One list entry, with host bits already masked off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CidrEntry {
+    pub kind: ListKind,
+    pub net: IpAddr,
+    pub prefix: u8,
+}
+
+impl fmt::Display for CidrEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}/{}", self.kind, self.net, self.prefix)
+    }
+}
+
+impl CidrEntry {
+    /// Parses `allow|deny <addr>[/prefix]`.
+    pub fn parse(line: &str) -> anyhow::Result<Self> {
+        let mut parts = line.split_whitespace();
+        let kind = match parts.next() {
+            Some("allow") => ListKind::Allow,
+            Some("deny") => ListKind::Deny,
+            Some(other) => bail!("unknown list '{}'", other),
+            None => bail!("empty entry"),
+        };
+        let cidr = parts.next().ok_or_else(|| anyhow!("missing prefix"))?;
+        if parts.next().is_some() {
+            bail!("trailing data after '{}'", cidr);
+        }
+
+        let (addr, prefix) = match cidr.split_once('/') {
+            Some((a, p)) => (a, Some(p)),
+            None => (cidr, None),
+        };
+        let addr: IpAddr = addr.parse().with_context(|| format!("bad address '{}'", addr))?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => p.parse::<u8>().with_context(|| format!("bad prefix '{}'", p))?,
+            None => max,
+        };
+        if prefix > max {
+            bail!("prefix /{} too long for {}", prefix, addr);
+        }
+
+        Ok(Self { kind, net: mask(addr, prefix), prefix })
+    }
+}
+
+fn mask(addr: IpAddr, prefix: u8) -> IpAddr {
+    match addr {
+        IpAddr::V4(a) => {
+            let bits = u32::from(a);
+            let m = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+            IpAddr::V4(Ipv4Addr::from(bits & m))
+        }
+        IpAddr::V6(a) => {
+            let bits = u128::from(a);
+            let m = if prefix == 0 { 0 } else { u128::MAX << (128 - prefix) };
+            IpAddr::V6(Ipv6Addr::from(bits & m))
+        }
+    }
+}
+
+/// Reads a list file, skipping blank lines and `#` comments.
+pub fn parse_file(path: &Path) -> anyhow::Result<BTreeSet<CidrEntry>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("cannot read {}", path.display()))?;
+
+    let mut entries = BTreeSet::new();
+    for (n, raw) in text.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let entry = CidrEntry::parse(line)
+            .with_context(|| format!("{}:{}", path.display(), n + 1))?;
+        entries.insert(entry);
+    }
+    Ok(entries)
+}
+
+/// Owns the four LPM maps and keeps them in sync with the list file.
+pub struct CidrLists {
+    allow_v4: LpmTrie<MapData, u32, u64>,
+    deny_v4: LpmTrie<MapData, u32, u64>,
+    allow_v6: LpmTrie<MapData, [u8; 16], u64>,
+    deny_v6: LpmTrie<MapData, [u8; 16], u64>,
+    path: PathBuf,
+    mtime: Option<SystemTime>,
+    active: BTreeSet<CidrEntry>,
+}
+
+impl CidrLists {
+    pub fn new(bpf: &mut Bpf, path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let mut lists = Self::open(bpf, path.into())?;
+        lists.reload_if_changed()?;
+        Ok(lists)
+    }
+
+    /// No list configured: empties whatever a previous run left in the
+    /// pinned maps. Returns the number of entries removed.
+    pub fn clear(bpf: &mut Bpf) -> anyhow::Result<usize> {
+        let mut lists = Self::open(bpf, PathBuf::new())?;
+        let stale: Vec<CidrEntry> = lists.active.iter().copied().collect();
+        for entry in &stale {
+            lists.remove(entry)?;
+            println!("➖ CIDR {} (no list configured)", entry);
+        }
+        Ok(stale.len())
+    }
+
+    /// Takes the maps; whatever they already hold counts as active, so
+    /// the first sync removes entries missing from the file.
+    fn open(bpf: &mut Bpf, path: PathBuf) -> anyhow::Result<Self> {
+        let mut lists = Self {
+            allow_v4: LpmTrie::try_from(take_map(bpf, "allow_v4")?)?,
+            deny_v4: LpmTrie::try_from(take_map(bpf, "deny_v4")?)?,
+            allow_v6: LpmTrie::try_from(take_map(bpf, "allow_v6")?)?,
+            deny_v6: LpmTrie::try_from(take_map(bpf, "deny_v6")?)?,
+            path,
+            mtime: None,
+            active: BTreeSet::new(),
+        };
+        lists.active = lists.pinned()?;
+        Ok(lists)
+    }
+
+    /// Entries currently in the maps.
+    fn pinned(&self) -> anyhow::Result<BTreeSet<CidrEntry>> {
+        let mut entries = BTreeSet::new();
+        for (kind, trie) in [(ListKind::Allow, &self.allow_v4), (ListKind::Deny, &self.deny_v4)] {
+            for key in trie.keys() {
+                let key = key?;
+                let net = IpAddr::V4(Ipv4Addr::from(key.data().to_ne_bytes()));
+                entries.insert(CidrEntry { kind, net, prefix: key.prefix_len() as u8 });
+            }
+        }
+        for (kind, trie) in [(ListKind::Allow, &self.allow_v6), (ListKind::Deny, &self.deny_v6)] {
+            for key in trie.keys() {
+                let key = key?;
+                let net = IpAddr::V6(Ipv6Addr::from(key.data()));
+                entries.insert(CidrEntry { kind, net, prefix: key.prefix_len() as u8 });
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Re-reads the file when its mtime moved and applies the difference.
+    /// `active` follows the maps entry by entry and the mtime is taken only
+    /// once the whole difference is applied, so a failure part-way (bad
+    /// line, full trie) is retried on the next call instead of leaving the
+    /// maps half-synced. Returns true when the maps were touched.
+    pub fn reload_if_changed(&mut self) -> anyhow::Result<bool> {
+        let mtime = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if mtime.is_some() && mtime == self.mtime {
+            return Ok(false);
+        }
+
+        let wanted = parse_file(&self.path)?;
+        let removed: Vec<CidrEntry> = self.active.difference(&wanted).copied().collect();
+        let added: Vec<CidrEntry> = wanted.difference(&self.active).copied().collect();
+
+        for entry in &removed {
+            self.remove(entry)?;
+            self.active.remove(entry);
+            println!("➖ CIDR {}", entry);
+        }
+        for entry in &added {
+            self.insert(entry)?;
+            self.active.insert(*entry);
+            println!("➕ CIDR {}", entry);
+        }
+
+        self.mtime = mtime;
+        Ok(!removed.is_empty() || !added.is_empty())
+    }
+
+    fn insert(&mut self, entry: &CidrEntry) -> anyhow::Result<()> {
+        match (entry.kind, entry.net) {
+            (ListKind::Allow, IpAddr::V4(a)) => self.allow_v4.insert(&v4_key(a, entry.prefix), 0, 0)?,
+            (ListKind::Deny, IpAddr::V4(a)) => self.deny_v4.insert(&v4_key(a, entry.prefix), 0, 0)?,
+            (ListKind::Allow, IpAddr::V6(a)) => self.allow_v6.insert(&v6_key(a, entry.prefix), 0, 0)?,
+            (ListKind::Deny, IpAddr::V6(a)) => self.deny_v6.insert(&v6_key(a, entry.prefix), 0, 0)?,
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, entry: &CidrEntry) -> anyhow::Result<()> {
+        match (entry.kind, entry.net) {
+            (ListKind::Allow, IpAddr::V4(a)) => self.allow_v4.remove(&v4_key(a, entry.prefix))?,
+            (ListKind::Deny, IpAddr::V4(a)) => self.deny_v4.remove(&v4_key(a, entry.prefix))?,
+            (ListKind::Allow, IpAddr::V6(a)) => self.allow_v6.remove(&v6_key(a, entry.prefix))?,
+            (ListKind::Deny, IpAddr::V6(a)) => self.deny_v6.remove(&v6_key(a, entry.prefix))?,
+        }
+        Ok(())
+    }
+
+    /// Hit counters for every active entry, as maintained by the XDP core.
+    pub fn hits(&self) -> Vec<(CidrEntry, u64)> {
+        self.active
+            .iter()
+            .map(|entry| {
+                let hits = match (entry.kind, entry.net) {
+                    (ListKind::Allow, IpAddr::V4(a)) => self.allow_v4.get(&v4_key(a, entry.prefix), 0),
+                    (ListKind::Deny, IpAddr::V4(a)) => self.deny_v4.get(&v4_key(a, entry.prefix), 0),
+                    (ListKind::Allow, IpAddr::V6(a)) => self.allow_v6.get(&v6_key(a, entry.prefix), 0),
+                    (ListKind::Deny, IpAddr::V6(a)) => self.deny_v6.get(&v6_key(a, entry.prefix), 0),
+                };
+                (*entry, hits.unwrap_or(0))
+            })
+            .collect()
+    }
+}
+
+/// LPM keys hold the address in network byte order.
+fn v4_key(addr: Ipv4Addr, prefix: u8) -> Key<u32> {
+    Key::new(prefix as u32, u32::from_ne_bytes(addr.octets()))
+}
+
+fn v6_key(addr: Ipv6Addr, prefix: u8) -> Key<[u8; 16]> {
+    Key::new(prefix as u32, addr.octets())
+}
@@ -0,0 +1,358 @@
+// src/bin/tiger_loader/config.rs
+//
+// Multi-interface loader config.
+// Формат — секції з парами `ключ = значення`, `#` — коментар:
+//
+//     [global]
+//     stats_file  = /run/tiger_delta/stats.json
+//     pin_root    = /sys/fs/bpf/tiger_delta
+//     daemon      = true
+//     egress      = false
+//     sample_rate = 64
+//     xsk_queues  = 0
+//     compactifier = sine   # sine | siphash | blake3 | poly
+//     normalize   = quantile, src_port=minmax   # schema | minmax | zscore | log | quantile
+//     state_file  = /var/lib/tiger_delta/state
+//     simul       = montecarlo   # scalar | montecarlo
+//
+//     [iface eth0]
+//     attach    = native      # skb | native | offload
+//     policy    = enforce     # monitor | enforce
+//     maps      = separate    # separate | shared
+//     ramp_low  = 0.5
+//     ramp_high = 0.875
+//     cidr      = /etc/tiger_delta/eth0.cidr
+//
+// Інтерфейси з `maps = shared` обслуговує один об'єкт з одним набором
+// карт (<pin_root>/shared): спільні солі, резонанс, політика і списки.
+// Тому політика, рампа і CIDR у них мають збігатися — інакше помилка.
+// Старий виклик `tiger_loader [--daemon] [--egress] <IFACE> [CIDR]`
+// перетворюється на конфіг з одним інтерфейсом (див. from_args).
+
+use anyhow::{anyhow, bail, Context};
+use aya::programs::XdpFlags;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::pin;
+use crate::policy::PolicyMode;
+use tiger_delta_ai_safety::compactifier::{CompactifierKind, COMPACTIFIER_ENV};
+use tiger_delta_ai_safety::normalize::{Normalizer, NORMALIZE_ENV};
+use tiger_delta_ai_safety::simul::{SimulMode, SIMUL_ENV};
+use tiger_delta_ai_safety::string_state::STATE_FILE_ENV;
+
+/// Default location of the machine-readable stats report.
+pub const DEFAULT_STATS_FILE: &str = "/run/tiger_delta/stats.json";
+
+/// Default 1-in-N packet sampling in daemon mode.
+pub const DEFAULT_SAMPLE_RATE: u32 = 64;
+
+/// Pin sub-directory of the shared map set.
+pub const SHARED_GROUP: &str = "shared";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttachMode {
+    Skb,
+    Native,
+    Offload,
+}
+
+impl AttachMode {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "skb" | "generic" => Some(AttachMode::Skb),
+            "native" | "drv" => Some(AttachMode::Native),
+            "offload" | "hw" => Some(AttachMode::Offload),
+            _ => None,
+        }
+    }
+
+    pub fn flags(self) -> XdpFlags {
+        match self {
+            AttachMode::Skb => XdpFlags::SKB_MODE,
+            AttachMode::Native => XdpFlags::DRV_MODE,
+            AttachMode::Offload => XdpFlags::HW_MODE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapSharing {
+    Separate,
+    Shared,
+}
+
+#[derive(Clone, Debug)]
+pub struct IfaceConfig {
+    pub name: String,
+    pub attach: AttachMode,
+    pub mode: PolicyMode,
+    pub maps: MapSharing,
+    pub ramp_low: f64,
+    pub ramp_high: f64,
+    pub cidr: Option<PathBuf>,
+}
+
+impl IfaceConfig {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attach: AttachMode::Skb,
+            mode: PolicyMode::Enforce,
+            maps: MapSharing::Separate,
+            ramp_low: 0.5,
+            ramp_high: 0.875,
+            cidr: None,
+        }
+    }
+
+    /// Settings that live in the maps and so must agree within a shared set.
+    fn map_settings(&self) -> (PolicyMode, u64, u64, Option<&PathBuf>) {
+        (self.mode, self.ramp_low.to_bits(), self.ramp_high.to_bits(), self.cidr.as_ref())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LoaderConfig {
+    pub stats_file: PathBuf,
+    pub pin_root: PathBuf,
+    pub daemon: bool,
+    pub egress: bool,
+    pub sample_rate: u32,
+    /// AF_XDP inspector queues per interface (0 = off).
+    pub xsk_queues: u32,
+    /// Userspace fold of the attribute vector (StringState).
+    pub compactifier: CompactifierKind,
+    /// Per-feature normalization ahead of the fold.
+    pub normalize: Normalizer,
+    /// Snapshot of the learned state, restored at start, written at exit.
+    pub state_file: Option<PathBuf>,
+    /// Digital twin of the daemon's per-source brains.
+    pub simul: SimulMode,
+    pub ifaces: Vec<IfaceConfig>,
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_num<T: std::str::FromStr>(key: &str, value: &str) -> anyhow::Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("'{}' is not a valid value for {}", value, key))
+}
+
+impl LoaderConfig {
+    /// Global defaults, with the environment overrides the single-interface
+    /// loader has always honoured.
+    fn defaults() -> Self {
+        Self {
+            stats_file: PathBuf::from(
+                env::var("TIGER_STATS_FILE").unwrap_or_else(|_| DEFAULT_STATS_FILE.to_string()),
+            ),
+            pin_root: PathBuf::from(
+                env::var("TIGER_PIN_ROOT").unwrap_or_else(|_| pin::DEFAULT_PIN_ROOT.to_string()),
+            ),
+            daemon: false,
+            egress: false,
+            sample_rate: env::var("TIGER_SAMPLE_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SAMPLE_RATE),
+            xsk_queues: env::var("TIGER_XSK_QUEUES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            compactifier: env::var(COMPACTIFIER_ENV)
+                .ok()
+                .and_then(|v| CompactifierKind::parse(&v))
+                .unwrap_or_default(),
+            normalize: env::var(NORMALIZE_ENV)
+                .ok()
+                .and_then(|v| Normalizer::parse(&v))
+                .unwrap_or_default(),
+            state_file: env::var(STATE_FILE_ENV).ok().map(PathBuf::from),
+            simul: env::var(SIMUL_ENV)
+                .ok()
+                .and_then(|v| SimulMode::parse(&v))
+                .unwrap_or_default(),
+            ifaces: Vec::new(),
+        }
+    }
+
+    /// Legacy command line: one interface, settings from the environment.
+    pub fn from_args(iface: &str, cidr: Option<String>, daemon: bool, egress: bool) -> anyhow::Result<Self> {
+        let mut config = Self::defaults();
+        config.daemon = daemon;
+        config.egress = egress;
+
+        let mut iface = IfaceConfig::new(iface);
+        if let Ok(value) = env::var("TIGER_POLICY") {
+            iface.mode = PolicyMode::from_env_value(&value)
+                .with_context(|| format!("TIGER_POLICY must be 'monitor' or 'enforce', got '{}'", value))?;
+        }
+        iface.cidr = cidr.map(PathBuf::from);
+        config.ifaces.push(iface);
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("in {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut config = Self::defaults();
+        let mut section: Option<String> = None;
+
+        for (n, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let at = || format!("line {}", n + 1);
+
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let header = header.trim();
+                if header == "global" {
+                    section = None;
+                } else if let Some(name) = header.strip_prefix("iface ") {
+                    let name = name.trim();
+                    if config.ifaces.iter().any(|i| i.name == name) {
+                        bail!("{}: interface '{}' configured twice", at(), name);
+                    }
+                    config.ifaces.push(IfaceConfig::new(name));
+                    section = Some(name.to_string());
+                } else {
+                    bail!("{}: unknown section [{}]", at(), header);
+                }
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .ok_or_else(|| anyhow!("{}: expected 'key = value'", at()))?;
+
+            match section {
+                None => config.set_global(key, value).with_context(at)?,
+                Some(_) => {
+                    let iface = config.ifaces.last_mut().expect("section implies an interface");
+                    set_iface(iface, key, value).with_context(at)?;
+                }
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn set_global(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        match key {
+            "stats_file" => self.stats_file = PathBuf::from(value),
+            "pin_root" => self.pin_root = PathBuf::from(value),
+            "daemon" => self.daemon = parse_bool(value).ok_or_else(|| anyhow!("daemon: expected true/false"))?,
+            "egress" => self.egress = parse_bool(value).ok_or_else(|| anyhow!("egress: expected true/false"))?,
+            "sample_rate" => self.sample_rate = parse_num(key, value)?,
+            "xsk_queues" => self.xsk_queues = parse_num(key, value)?,
+            "compactifier" => {
+                self.compactifier = CompactifierKind::parse(value)
+                    .ok_or_else(|| anyhow!("compactifier must be sine, siphash, blake3 or poly, got '{}'", value))?
+            }
+            "normalize" => {
+                self.normalize = Normalizer::parse(value).ok_or_else(|| {
+                    anyhow!("normalize: expected 'strategy[, slot=strategy ...]' with schema, minmax, zscore, log or quantile, got '{}'", value)
+                })?
+            }
+            "state_file" => self.state_file = Some(PathBuf::from(value)),
+            "simul" => {
+                self.simul = SimulMode::parse(value)
+                    .ok_or_else(|| anyhow!("simul must be scalar or montecarlo, got '{}'", value))?
+            }
+            _ => bail!("unknown global key '{}'", key),
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.ifaces.is_empty() {
+            bail!("no [iface <name>] section");
+        }
+        if self.daemon && self.sample_rate == 0 {
+            // 0 вимикає семплювання в ядрі: демон не отримав би жодного пакета
+            bail!("daemon = true needs sample_rate >= 1 (0 turns kernel sampling off)");
+        }
+        for iface in &self.ifaces {
+            if !(0.0..1.0).contains(&iface.ramp_low) || iface.ramp_high <= iface.ramp_low || iface.ramp_high > 1.0 {
+                bail!("{}: need 0 <= ramp_low < ramp_high <= 1", iface.name);
+            }
+        }
+
+        let mut shared = self.ifaces.iter().filter(|i| i.maps == MapSharing::Shared);
+        if let Some(first) = shared.next() {
+            for other in shared {
+                if other.map_settings() != first.map_settings() {
+                    bail!(
+                        "{} and {} share maps but differ in policy, ramp or cidr",
+                        first.name,
+                        other.name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Interfaces grouped by map set: all shared ones first (if any),
+    /// then one group per separate interface.
+    pub fn map_sets(&self) -> Vec<Vec<IfaceConfig>> {
+        let shared: Vec<IfaceConfig> = self
+            .ifaces
+            .iter()
+            .filter(|i| i.maps == MapSharing::Shared)
+            .cloned()
+            .collect();
+        let mut sets = Vec::new();
+        if !shared.is_empty() {
+            sets.push(shared);
+        }
+        sets.extend(
+            self.ifaces
+                .iter()
+                .filter(|i| i.maps == MapSharing::Separate)
+                .map(|i| vec![i.clone()]),
+        );
+        sets
+    }
+}
+
+fn set_iface(iface: &mut IfaceConfig, key: &str, value: &str) -> anyhow::Result<()> {
+    match key {
+        "attach" => {
+            iface.attach = AttachMode::parse(value)
+                .ok_or_else(|| anyhow!("attach must be skb, native or offload, got '{}'", value))?
+        }
+        "policy" => {
+            iface.mode = PolicyMode::from_env_value(value)
+                .ok_or_else(|| anyhow!("policy must be 'monitor' or 'enforce', got '{}'", value))?
+        }
+        "maps" => {
+            iface.maps = match value {
+                "separate" => MapSharing::Separate,
+                "shared" => MapSharing::Shared,
+                _ => bail!("maps must be 'separate' or 'shared', got '{}'", value),
+            }
+        }
+        "ramp_low" => iface.ramp_low = parse_num(key, value)?,
+        "ramp_high" => iface.ramp_high = parse_num(key, value)?,
+        "cidr" => iface.cidr = Some(PathBuf::from(value)),
+        _ => bail!("unknown interface key '{}'", key),
+    }
+    Ok(())
+}
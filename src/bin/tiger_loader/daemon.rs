@@ -0,0 +1,415 @@
+// src/bin/tiger_loader/daemon.rs
+//
+// Daemon mode: XDP fast path + cognitive slow path в одному процесі.
+// Ядро копіює кожен N-й пакет у ring buffer `samples`; тут кожне джерело
+// отримує власний Brain (ті самі ядра, що й у main.rs), а підтверджені
+// атакуючі потрапляють у `source_policy` і скидаються вже на швидкості лінії.
+// З інспектором (xsk.rs) джерела в смузі невизначеності додатково
+// віддають кілька пакетів цілком — вердикт по payload, а не по заголовках.
+
+use aya::maps::{HashMap as BpfHashMap, MapData, RingBuf};
+use aya::Bpf;
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::time::{Duration, Instant};
+use tiger_delta_ai_safety::brain::{Action, Brain};
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::jitter::Rhythm;
+use tiger_delta_ai_safety::lumis::LumisClock;
+use tiger_delta_ai_safety::simul::SimulMode;
+use tiger_delta_ai_safety::spectrum::synchronized_pulses;
+use tiger_delta_ai_safety::schema::{attr, Attrs, RawSample, ATTR_COUNT};
+use tiger_delta_ai_safety::string_state::StringState;
+use tiger_delta_ai_safety::xdp_emulator::{parse_frame_payload, PacketMeta, DROP_ALWAYS};
+
+use crate::take_map;
+use crate::topk::HeavyWindow;
+use crate::xsk::Inspector;
+
+/// Lower edge of the uncertain band: threat in [INSPECT_LOW, threshold)
+/// sends the source to the AF_XDP inspector.
+pub const INSPECT_LOW: f64 = 0.5;
+
+/// Maps a kernel sample onto the attribute vector the brain expects.
+/// Семпл несе лише заголовки: слоти payload (перший байт, вага байтів,
+/// вага заголовка) лишаються нулями, як у пакета без payload, тож і
+/// ентропія байтів у Brain нульова. tcp_flags і VLAN слотів не мають.
+fn sample_attrs(m: &PacketMeta) -> Attrs {
+    let len = u16::from_be(m.len) as i64;
+    let mut a = [0i64; ATTR_COUNT];
+    a[attr::SRC_PORT] = u16::from_be(m.sport) as i64;
+    a[attr::LENGTH] = len;
+    a[attr::LENGTH_MOD_7] = len % 7;
+    a[attr::SRC_IP_TEXT_LEN] = source_label(m.saddr, m.is_v6).len() as i64;
+    a
+}
+
+/// Maps a top-K entry onto the attribute vector: rank, rate and share of
+/// the window stand in for the per-packet features. Byte slots stay
+/// zero — a volume signal carries no byte entropy.
+fn heavy_attrs(rank: usize, saddr: u32, is_v6: bool, packets: u32, window: &HeavyWindow) -> Attrs {
+    let pps = (packets as f64 / window.secs.max(1e-3)) as i64;
+    let share_permille = (packets as u64 * 1000 / window.packets.max(1)) as i64;
+    let mut a = [0i64; ATTR_COUNT];
+    a[attr::SRC_PORT] = rank as i64;
+    a[attr::LENGTH] = pps;
+    a[attr::LENGTH_MOD_7] = pps % 7;
+    a[attr::HEAD_WEIGHT] = share_permille;
+    a[attr::SRC_IP_TEXT_LEN] = source_label(saddr, is_v6).len() as i64;
+    a
+}
+
+/// Printable source key: IPv4 as dotted quad, folded IPv6 as hex.
+pub fn source_label(saddr: u32, is_v6: bool) -> String {
+    if is_v6 {
+        format!("v6#{:08x}", saddr)
+    } else {
+        Ipv4Addr::from(saddr.to_ne_bytes()).to_string()
+    }
+}
+
+struct SourceEntry {
+    brain: Brain,
+    is_v6: bool,
+    strikes: u32,
+    last_seen: Instant,
+}
+
+/// What one poll did, for the console and the stats report.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DaemonTick {
+    pub samples: u64,
+    pub attacks: u64,
+    pub new_blocks: u64,
+    /// Full frames judged by the AF_XDP inspector.
+    pub inspected: u64,
+    pub peak_threat: f64,
+}
+
+pub struct Daemon {
+    samples: RingBuf<MapData>,
+    source_policy: BpfHashMap<MapData, u32, u32>,
+    sources: HashMap<u32, SourceEntry>,
+    blocked: HashMap<u32, Instant>,
+    inspector: Option<Inspector>,
+    /// Upper bound on tracked sources (memory guard).
+    pub max_sources: usize,
+    /// Consecutive attack verdicts before a source is blocked.
+    pub strikes_to_block: u32,
+    /// Kernel 1-in-N sampling: one ring sample counts as N arrivals.
+    pub sample_rate: u32,
+    /// Digital twin of new per-source brains (src/simul.rs).
+    pub simul: SimulMode,
+    pub block_ttl: Duration,
+    pub idle_ttl: Duration,
+}
+
+impl Daemon {
+    pub fn new(bpf: &mut Bpf) -> anyhow::Result<Self> {
+        Ok(Self {
+            samples: RingBuf::try_from(take_map(bpf, "samples")?)?,
+            source_policy: BpfHashMap::try_from(take_map(bpf, "source_policy")?)?,
+            sources: HashMap::new(),
+            blocked: HashMap::new(),
+            inspector: None,
+            max_sources: 65_536,
+            strikes_to_block: 3,
+            sample_rate: 1,
+            simul: SimulMode::Scalar,
+            block_ttl: Duration::from_secs(60),
+            idle_ttl: Duration::from_secs(300),
+        })
+    }
+
+    /// Enables the AF_XDP path for sources in the uncertain band.
+    pub fn with_inspector(mut self, inspector: Inspector) -> Self {
+        self.inspector = Some(inspector);
+        self
+    }
+
+    /// `off_zone` names the equilibrium zones the source left (src/lagrange.rs).
+    fn block(&mut self, saddr: u32, is_v6: bool, threat_p: f64, off_zone: &str, now: Instant) -> anyhow::Result<()> {
+        self.source_policy.insert(saddr, DROP_ALWAYS, 0)?;
+        self.blocked.insert(saddr, now + self.block_ttl);
+        println!(
+            "⛔ BLOCK {} for {}s (p={:.2}, off-zone=[{}])",
+            source_label(saddr, is_v6),
+            self.block_ttl.as_secs(),
+            threat_p,
+            off_zone
+        );
+        Ok(())
+    }
+
+    /// Runs one header sample or top-K entry of `saddr` through its brain
+    /// (scored, not learned); strikes lead to
+    /// a block, the uncertain band to the inspector. `sampled_at` is the
+    /// kernel timestamp and length of a ring sample (it stands for
+    /// `sample_rate` arrivals); other observations reuse the source's
+    /// current timing.
+    #[allow(clippy::too_many_arguments)]
+    fn judge(
+        &mut self,
+        saddr: u32,
+        is_v6: bool,
+        attrs: &[i64],
+        sampled_at: Option<(u64, u64)>,
+        state: &mut StringState,
+        now: Instant,
+        tick: &mut DaemonTick,
+    ) -> anyhow::Result<()> {
+        if self.blocked.contains_key(&saddr) {
+            return Ok(());
+        }
+        if !self.sources.contains_key(&saddr) && self.sources.len() >= self.max_sources {
+            return Ok(());
+        }
+
+        let entry = self.sources.entry(saddr).or_insert_with(|| SourceEntry {
+            // Lumis на монотонному годиннику: сон і загасання за часом,
+            // а не за кількістю вибірок джерела
+            brain: Brain::with_lumis_clock(LumisClock::Monotonic).with_simul_mode(self.simul),
+            is_v6,
+            strikes: 0,
+            last_seen: now,
+        });
+        entry.last_seen = now;
+
+        let arrival = match sampled_at {
+            Some((ts_ns, bytes)) => entry.brain.timing.record_sampled(ts_ns, self.sample_rate.max(1), bytes),
+            None => entry.brain.timing.arrival(),
+        };
+        // Заголовки й агрегати оцінюються, але не вчать спільну нормалізацію
+        let verdict = entry.brain.process_synthetic(attrs, arrival, state);
+        tick.peak_threat = tick.peak_threat.max(verdict.threat_p);
+
+        match verdict.action {
+            Action::Attack | Action::Preempt => {
+                tick.attacks += 1;
+                entry.strikes += 1;
+            }
+            Action::Pass => entry.strikes = 0,
+        }
+
+        if entry.strikes >= self.strikes_to_block {
+            entry.strikes = 0;
+            let is_v6 = entry.is_v6;
+            let off_zone = entry.brain.zones.report().outside_names();
+            self.block(saddr, is_v6, verdict.threat_p, &off_zone, now)?;
+            tick.new_blocks += 1;
+        } else if verdict.action == Action::Pass
+            && (INSPECT_LOW..verdict.threshold).contains(&verdict.threat_p)
+        {
+            if let Some(inspector) = self.inspector.as_mut() {
+                if inspector.mark(saddr)? {
+                    println!("🔬 INSPECT {} (p={:.2})", source_label(saddr, entry.is_v6), verdict.threat_p);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Drains the sample ring through the per-source brains.
+    pub fn poll(&mut self, state: &mut StringState) -> anyhow::Result<DaemonTick> {
+        let mut tick = DaemonTick::default();
+        let now = Instant::now();
+
+        while let Some(item) = self.samples.next() {
+            if item.len() < std::mem::size_of::<RawSample>() {
+                continue;
+            }
+            // SAFETY: довжину перевірено, RawSample — repr(C) з полів POD
+            let sample: RawSample = unsafe { std::ptr::read_unaligned(item.as_ptr() as *const RawSample) };
+            // Звільняємо слот кільця до виклику judge (він позичає self)
+            drop(item);
+            let meta = PacketMeta::from(sample.meta);
+            tick.samples += 1;
+
+            // ts_ns — bpf_ktime_get_ns(): ті самі 4 мс кошики, що й у fold
+            let attrs = sample_attrs(&meta);
+            let sampled_at = Some((sample.ts_ns, u16::from_be(meta.len) as u64));
+            self.judge(meta.saddr, meta.is_v6, &attrs, sampled_at, state, now, &mut tick)?;
+        }
+
+        self.poll_inspector(state, now, &mut tick)?;
+        Ok(tick)
+    }
+
+    /// Feeds the window's top talkers into their per-source brains, so
+    /// volume alone can earn strikes even between samples.
+    pub fn observe_heavy(&mut self, window: &HeavyWindow, state: &mut StringState) -> anyhow::Result<DaemonTick> {
+        let mut tick = DaemonTick::default();
+        let now = Instant::now();
+        for (rank, &(saddr, packets)) in window.top.iter().enumerate() {
+            let is_v6 = self.sources.get(&saddr).map(|e| e.is_v6).unwrap_or(false);
+            let attrs = heavy_attrs(rank, saddr, is_v6, packets, window);
+            self.judge(saddr, is_v6, &attrs, None, state, now, &mut tick)?;
+        }
+        Ok(tick)
+    }
+
+    /// Judges redirected frames on their full payload. An attack verdict
+    /// blocks the source at once; a confident pass ends the inspection.
+    fn poll_inspector(&mut self, state: &mut StringState, now: Instant, tick: &mut DaemonTick) -> anyhow::Result<()> {
+        let Some(inspector) = self.inspector.as_mut() else {
+            return Ok(());
+        };
+
+        let mut frames = Vec::new();
+        inspector.drain(|frame| frames.push(frame.to_vec()));
+
+        for frame in frames {
+            let Some(parsed) = parse_frame_payload(&frame) else {
+                continue;
+            };
+            let meta = parsed.meta;
+            if self.blocked.contains_key(&meta.saddr) {
+                continue;
+            }
+            let Some(entry) = self.sources.get_mut(&meta.saddr) else {
+                // Бюджет пережив забуте джерело — ознака застарілого запису
+                if let Some(inspector) = self.inspector.as_mut() {
+                    inspector.clear(meta.saddr);
+                }
+                continue;
+            };
+            entry.last_seen = now;
+            tick.inspected += 1;
+
+            let attrs = payload_attrs(
+                u16::from_be(meta.sport),
+                parsed.src.to_string().len(),
+                parsed.payload,
+            );
+            // Кадри інспектора — копії вже порахованих пакетів: час не додаємо
+            let arrival = entry.brain.timing.arrival();
+            let verdict = entry.brain.process_with_arrival(&attrs, arrival, state);
+            tick.peak_threat = tick.peak_threat.max(verdict.threat_p);
+            let is_v6 = entry.is_v6;
+            let off_zone = entry.brain.zones.report().outside_names();
+
+            match verdict.action {
+                Action::Attack | Action::Preempt => {
+                    tick.attacks += 1;
+                    entry.strikes = 0;
+                    self.block(meta.saddr, is_v6, verdict.threat_p, &off_zone, now)?;
+                    tick.new_blocks += 1;
+                }
+                Action::Pass if verdict.threat_p < INSPECT_LOW => {}
+                Action::Pass => continue,
+            }
+            if let Some(inspector) = self.inspector.as_mut() {
+                inspector.clear(meta.saddr);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn inspector_queues(&self) -> usize {
+        self.inspector.as_ref().map(|i| i.queues()).unwrap_or(0)
+    }
+
+    /// Lifts expired blocks, forgets idle sources and ticks the idle
+    /// timer of the rest.
+    pub fn expire(&mut self) -> anyhow::Result<()> {
+        let now = Instant::now();
+
+        let expired: Vec<u32> = self
+            .blocked
+            .iter()
+            .filter(|(_, &until)| until <= now)
+            .map(|(&saddr, _)| saddr)
+            .collect();
+        for saddr in expired {
+            self.blocked.remove(&saddr);
+            // LRU могла вже витіснити запис — це не помилка
+            let _ = self.source_policy.remove(&saddr);
+            println!("✅ UNBLOCK {}", source_label(saddr, self.sources.get(&saddr).map(|e| e.is_v6).unwrap_or(false)));
+        }
+
+        let idle_ttl = self.idle_ttl;
+        let blocked = &self.blocked;
+        self.sources
+            .retain(|saddr, e| blocked.contains_key(saddr) || now.duration_since(e.last_seen) < idle_ttl);
+        for entry in self.sources.values_mut() {
+            entry.brain.idle();
+        }
+        Ok(())
+    }
+
+    pub fn tracked_sources(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn blocked_sources(&self) -> usize {
+        self.blocked.len()
+    }
+
+    /// Inter-arrival rhythm of the tracked sources for the dashboard:
+    /// counts per rhythm and the `limit` most anomalous flagged sources.
+    pub fn rhythm_json(&self, limit: usize) -> String {
+        let (mut regular, mut chaotic) = (0u64, 0u64);
+        let mut flagged = Vec::new();
+        for (&saddr, entry) in &self.sources {
+            let hrv = entry.brain.timing.hrv();
+            match hrv.rhythm() {
+                Rhythm::Regular => regular += 1,
+                Rhythm::Chaotic => chaotic += 1,
+                Rhythm::Natural | Rhythm::Unknown => continue,
+            }
+            flagged.push((saddr, entry.is_v6, hrv));
+        }
+        flagged.sort_by(|a, b| b.2.score().total_cmp(&a.2.score()));
+        let top = flagged
+            .iter()
+            .take(limit)
+            .map(|(saddr, is_v6, hrv)| {
+                format!("{{\"source\":\"{}\",\"hrv\":{}}}", source_label(*saddr, *is_v6), hrv.to_json())
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{\"regular\":{},\"chaotic\":{},\"flagged\":[{}]}}", regular, chaotic, top)
+    }
+
+    /// Beaconing sources (strongest `limit`) and groups of sources pulsing
+    /// in step, for the dashboard.
+    pub fn beacon_json(&self, limit: usize) -> String {
+        let beats: Vec<(u32, _)> = self
+            .sources
+            .iter()
+            .map(|(&saddr, e)| (saddr, e.brain.timing.periodicity()))
+            .filter(|(_, p)| p.is_beacon())
+            .collect();
+
+        let mut strongest = beats.clone();
+        strongest.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+        let label = |saddr: u32| source_label(saddr, self.sources.get(&saddr).map(|e| e.is_v6).unwrap_or(false));
+        let beacons = strongest
+            .iter()
+            .take(limit)
+            .map(|(saddr, p)| format!("{{\"source\":\"{}\",\"spectrum\":{}}}", label(*saddr), p.to_json()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let pulses = synchronized_pulses(&beats, 3)
+            .iter()
+            .map(|g| {
+                let members = g
+                    .sources
+                    .iter()
+                    .take(limit)
+                    .map(|&s| format!("\"{}\"", label(s)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!(
+                    "{{\"dominant_hz\":{:.4},\"coherence\":{:.3},\"size\":{},\"sources\":[{}]}}",
+                    g.dominant_hz,
+                    g.coherence,
+                    g.sources.len(),
+                    members
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{\"beacons\":[{}],\"pulses\":[{}]}}", beacons, pulses)
+    }
+}
@@ -0,0 +1,196 @@
+// src/bin/tiger_loader/egress.rs
+//
+// Egress observer: TC-програма (src/kernel/tiger_delta_tc.c) згортає
+// вихідні пакети тим самим многовидом і солями, що й XDP, і тримає стан
+// на кожне призначення. Тут кожне призначення отримує власний Brain:
+// скомпрометований хост, що «маякує» або виносить дані, видно в тій
+// самій резонансній рамці, що й вхідну атаку. Лише спостереження —
+// вихідний трафік не скидається.
+
+use aya::maps::{HashMap as BpfHashMap, MapData};
+use aya::programs::{tc, SchedClassifier, TcAttachType};
+use aya::{include_bytes_aligned, Bpf, BpfLoader, Pod};
+use anyhow::Context;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tiger_delta_ai_safety::brain::{Action, Brain};
+use tiger_delta_ai_safety::lumis::LumisClock;
+use tiger_delta_ai_safety::schema::Attrs;
+use tiger_delta_ai_safety::string_state::StringState;
+
+use crate::daemon::source_label;
+use crate::pin::PinLayout;
+use crate::take_map;
+
+/// Mirror of `struct egress_dst`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EgressDst {
+    pub resonance: u64,
+    pub packets: u64,
+    pub bytes: u64,
+    pub first_ns: u64,
+    pub last_ns: u64,
+    pub gap_ewma_ns: u64,
+    pub dport: u16,
+    pub protocol: u8,
+    pub is_v6: u8,
+    pub pad: u32,
+}
+
+// SAFETY: repr(C), лише цілі поля, явний padding
+unsafe impl Pod for EgressDst {}
+
+/// Maps the growth of one destination since the last poll onto the
+/// 10-slot vector the brain expects.
+fn egress_attrs(cur: &EgressDst, packets: u64, bytes: u64) -> Attrs {
+    let weight = (cur.resonance >> 48) as i64;
+    let avg_len = (bytes / packets.max(1)) as i64;
+    [
+        u16::from_be(cur.dport) as i64,
+        avg_len,
+        cur.protocol as i64,
+        weight,
+        weight % 111,
+        (packets % 7) as i64,
+        (cur.gap_ewma_ns / 1_000_000) as i64,
+        if cur.is_v6 != 0 { 16 } else { 4 },
+        packets as i64,
+        (bytes >> 10) as i64,
+    ]
+}
+
+struct DestEntry {
+    brain: Brain,
+    last: EgressDst,
+    last_seen: Instant,
+    alerted_at: Option<Instant>,
+}
+
+/// What one poll did, for the console and the stats report.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EgressTick {
+    pub active: u64,
+    pub alerts: u64,
+    pub peak_threat: f64,
+}
+
+pub struct EgressMonitor {
+    // Програма від'єднується разом з цим об'єктом: на handover
+    // egress-спостереження не передається, лише стан у bpffs
+    _bpf: Bpf,
+    state: BpfHashMap<MapData, u32, EgressDst>,
+    dests: HashMap<u32, DestEntry>,
+    total_alerts: u64,
+    /// Minimum time between two alerts for one destination.
+    pub alert_cooldown: Duration,
+    pub idle_ttl: Duration,
+}
+
+impl EgressMonitor {
+    /// Loads the TC object next to the XDP maps (so `config_map` is the
+    /// same pinned map) and attaches it to the egress hook of every
+    /// interface of the map set.
+    pub fn attach(ifaces: &[&str], layout: &PinLayout) -> anyhow::Result<Self> {
+        let mut bpf = BpfLoader::new()
+            .map_pin_path(layout.maps_dir())
+            .load(include_bytes_aligned!("../../../src/kernel/tiger_delta_tc.o"))?;
+
+        let program: &mut SchedClassifier = bpf
+            .program_mut("tiger_delta_egress")
+            .context("program 'tiger_delta_egress' not found in object")?
+            .try_into()?;
+        program.load()?;
+        for iface in ifaces {
+            // clsact може вже існувати (попередній запуск, інші фільтри)
+            let _ = tc::qdisc_add_clsact(iface);
+            program
+                .attach(iface, TcAttachType::Egress)
+                .with_context(|| format!("TC egress observer on {}", iface))?;
+        }
+
+        let state = BpfHashMap::try_from(take_map(&mut bpf, "egress_state")?)?;
+        Ok(Self {
+            _bpf: bpf,
+            state,
+            dests: HashMap::new(),
+            total_alerts: 0,
+            alert_cooldown: Duration::from_secs(60),
+            idle_ttl: Duration::from_secs(300),
+        })
+    }
+
+    /// Runs every destination that sent traffic since the last poll
+    /// through its brain.
+    pub fn poll(&mut self, state: &mut StringState) -> anyhow::Result<EgressTick> {
+        let mut tick = EgressTick::default();
+        let now = Instant::now();
+
+        let snapshot: Vec<(u32, EgressDst)> = self.state.iter().filter_map(|r| r.ok()).collect();
+        for (daddr, cur) in snapshot {
+            let entry = self.dests.entry(daddr).or_insert_with(|| DestEntry {
+                brain: Brain::with_lumis_clock(LumisClock::Monotonic),
+                last: EgressDst::default(),
+                last_seen: now,
+                alerted_at: None,
+            });
+
+            // LRU могла витіснити й перестворити запис: лічильники з нуля
+            let restarted = cur.packets < entry.last.packets;
+            let base = if restarted { EgressDst::default() } else { entry.last };
+            let packets = cur.packets - base.packets;
+            if packets == 0 {
+                continue;
+            }
+            let bytes = cur.bytes.saturating_sub(base.bytes);
+            entry.last = cur;
+            entry.last_seen = now;
+            tick.active += 1;
+
+            // last_ns — час останнього пакета за годинником ядра; уся
+            // дельта лічильника потрапляє в його кошик
+            let arrival = entry
+                .brain
+                .timing
+                .record_n(cur.last_ns, packets.min(u32::MAX as u64) as u32, bytes);
+            let verdict = entry
+                .brain
+                .process_with_arrival(&egress_attrs(&cur, packets, bytes), arrival, state);
+            tick.peak_threat = tick.peak_threat.max(verdict.threat_p);
+
+            if verdict.action == Action::Pass {
+                continue;
+            }
+            let cooling = entry
+                .alerted_at
+                .map(|t| now.duration_since(t) < self.alert_cooldown)
+                .unwrap_or(false);
+            if !cooling {
+                entry.alerted_at = Some(now);
+                tick.alerts += 1;
+                self.total_alerts += 1;
+                println!(
+                    "📤 EGRESS ANOMALY dst={} dport={} p={:.2} | {} pkts / {} B since last poll, gap≈{} ms",
+                    source_label(daddr, cur.is_v6 != 0),
+                    u16::from_be(cur.dport),
+                    verdict.threat_p,
+                    packets,
+                    bytes,
+                    cur.gap_ewma_ns / 1_000_000
+                );
+            }
+        }
+
+        let idle_ttl = self.idle_ttl;
+        self.dests.retain(|_, e| now.duration_since(e.last_seen) < idle_ttl);
+        Ok(tick)
+    }
+
+    pub fn destinations(&self) -> usize {
+        self.dests.len()
+    }
+
+    pub fn total_alerts(&self) -> u64 {
+        self.total_alerts
+    }
+}
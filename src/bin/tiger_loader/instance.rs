@@ -0,0 +1,416 @@
+// src/bin/tiger_loader/instance.rs
+//
+// One loaded XDP object: its map set and the interfaces attached to it.
+// Окремий інтерфейс — окремий екземпляр зі своїми картами; інтерфейси з
+// `maps = shared` ділять один екземпляр. Солі (StringState) спільні для
+// всіх екземплярів процесу, тож усі напрямки й інтерфейси згортаються
+// в одній епосі. Health check раз на HEALTH_INTERVAL перевіряє, що на
+// кожному інтерфейсі стоїть саме наша програма, і повертає її, якщо
+// хтось її зняв.
+
+use anyhow::Context;
+use aya::maps::{Array, MapData, PerCpuArray};
+use aya::programs::Xdp;
+use aya::{include_bytes_aligned, Bpf, BpfLoader};
+use std::time::{Duration, Instant};
+use tiger_delta_ai_safety::salt::EpochSalts;
+use tiger_delta_ai_safety::string_state::StringState;
+
+use crate::aggregate::Aggregator;
+use crate::cidr::CidrLists;
+use crate::config::{IfaceConfig, LoaderConfig, MapSharing, SHARED_GROUP};
+use crate::daemon::{self, Daemon};
+use crate::egress::EgressMonitor;
+use crate::netlink;
+use crate::pin::{self, AttachOutcome, PinLayout};
+use crate::policy::{DropPolicy, PolicyMode};
+use crate::stats::XdpStats;
+use crate::take_map;
+use crate::topk::TopTalkers;
+use crate::xsk::Inspector;
+
+const PROGRAM: &str = "tiger_delta_xdp";
+
+/// How often the attach state of every interface is verified.
+const HEALTH_INTERVAL: Duration = Duration::from_secs(5);
+
+struct Attached {
+    config: IfaceConfig,
+    layout: PinLayout,
+}
+
+/// What one tick contributes to the process-wide state.
+pub struct InstanceTick {
+    /// Salt pressure wanted by this map set.
+    pub pressure: f64,
+    pub json: String,
+}
+
+pub struct Instance {
+    name: String,
+    bpf: Bpf,
+    ifaces: Vec<Attached>,
+    prog_id: u32,
+    cidr: Option<CidrLists>,
+    config_map: Array<MapData, u64>,
+    policy: DropPolicy,
+    resonance_map: PerCpuArray<MapData, u64>,
+    xdp_stats: XdpStats,
+    top_talkers: TopTalkers,
+    daemon: Option<Daemon>,
+    egress: Option<EgressMonitor>,
+    aggregator: Aggregator,
+    last_cidr_report: Instant,
+    last_health: Instant,
+}
+
+fn publish_salts(config_map: &mut Array<MapData, u64>, salts: EpochSalts) -> anyhow::Result<()> {
+    config_map.set(0, salts.phi, 0)?;
+    config_map.set(1, salts.pi, 0)?;
+    Ok(())
+}
+
+fn program(bpf: &mut Bpf) -> anyhow::Result<&mut Xdp> {
+    Ok(bpf
+        .program_mut(PROGRAM)
+        .with_context(|| format!("program '{}' not found in object", PROGRAM))?
+        .try_into()?)
+}
+
+impl Instance {
+    /// Loads the object for one map set and attaches it to every
+    /// interface of the set.
+    pub fn load(set: &[IfaceConfig], config: &LoaderConfig, salts: EpochSalts) -> anyhow::Result<Self> {
+        let first = set.first().context("empty interface set")?;
+        let shared = first.maps == MapSharing::Shared;
+        let name = if shared { SHARED_GROUP.to_string() } else { first.name.clone() };
+
+        let mut ifaces = Vec::new();
+        for iface in set {
+            let layout = if shared {
+                PinLayout::shared(&config.pin_root, SHARED_GROUP, &iface.name)?
+            } else {
+                PinLayout::for_iface(&config.pin_root, &iface.name)?
+            };
+            ifaces.push(Attached { config: iface.clone(), layout });
+        }
+
+        // Карти з bpffs перевикористовуються: солі, resonance_state і лічильники
+        // переживають перезапуск лоадера
+        let mut bpf = BpfLoader::new()
+            .map_pin_path(ifaces[0].layout.maps_dir())
+            .load(include_bytes_aligned!("../../../src/kernel/tiger_delta_xdp.o"))?;
+
+        // Списки заповнюються до attach, щоб allow діяв з першого пакета
+        // (без списку — прибираються записи, закріплені попереднім запуском)
+        let cidr = match &first.cidr {
+            Some(path) => Some(CidrLists::new(&mut bpf, path)?),
+            None => {
+                CidrLists::clear(&mut bpf)?;
+                None
+            }
+        };
+
+        let prog = program(&mut bpf)?;
+        prog.load()?;
+        let prog_id = prog.info()?.id();
+        for iface in &ifaces {
+            let name = &iface.config.name;
+            match pin::attach_or_replace(prog, name, iface.config.attach.flags(), &iface.layout)? {
+                AttachOutcome::Fresh => {
+                    println!("📌 Attached to {} (pinned at {})", name, iface.layout.link_path().display())
+                }
+                AttachOutcome::Replaced => println!("♻️ Replaced running program on {} without a gap", name),
+            }
+        }
+
+        let mut config_map: Array<MapData, u64> = Array::try_from(take_map(&mut bpf, "config_map")?)?;
+        publish_salts(&mut config_map, salts)?;
+
+        let mut policy = DropPolicy::new(&mut bpf, first.mode)?;
+        policy.ramp_low = first.ramp_low;
+        policy.ramp_high = first.ramp_high;
+
+        let resonance_map = PerCpuArray::try_from(take_map(&mut bpf, "resonance_state")?)?;
+        let xdp_stats = XdpStats::new(&mut bpf)?;
+        let top_talkers = TopTalkers::new(&mut bpf)?;
+
+        let daemon = if config.daemon {
+            policy.set_sample_rate(config.sample_rate)?;
+            println!(
+                "🧠 [{}] Daemon mode: cognitive layer on 1/{} sampled packets, {} twin",
+                name,
+                config.sample_rate,
+                config.simul.as_str()
+            );
+            let mut daemon = Daemon::new(&mut bpf)?;
+            daemon.sample_rate = config.sample_rate;
+            daemon.simul = config.simul;
+
+            // `xsks` індексується чергою, тож у спільного набору карт
+            // інспектор може слухати лише один інтерфейс
+            if config.xsk_queues > 0 {
+                if ifaces.len() > 1 {
+                    eprintln!("⚠️ [{}] AF_XDP inspector only on {} (maps are shared)", name, first.name);
+                }
+                let inspector = Inspector::new(&mut bpf, &first.name, config.xsk_queues).with_context(|| {
+                    format!("AF_XDP sockets on {} queues 0..{}", first.name, config.xsk_queues)
+                })?;
+                println!("🔬 AF_XDP inspector on {} queue(s)", inspector.queues());
+                Some(daemon.with_inspector(inspector))
+            } else {
+                Some(daemon)
+            }
+        } else {
+            policy.set_sample_rate(0)?;
+            None
+        };
+
+        // Після XDP-об'єкта: config_map уже закріплена і спільна для обох напрямків
+        let egress = if config.egress {
+            let names: Vec<&str> = ifaces.iter().map(|i| i.config.name.as_str()).collect();
+            let monitor = EgressMonitor::attach(&names, &ifaces[0].layout)?;
+            println!("📤 Egress observer attached to {}", names.join(", "));
+            Some(monitor)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            name,
+            bpf,
+            ifaces,
+            prog_id,
+            cidr,
+            config_map,
+            policy,
+            resonance_map,
+            xdp_stats,
+            top_talkers,
+            daemon,
+            egress,
+            // Вікна: 10 с для рішення, 5 хв для тренду
+            aggregator: Aggregator::new(Duration::from_secs(10), Duration::from_secs(300)),
+            last_cidr_report: Instant::now(),
+            last_health: Instant::now(),
+        })
+    }
+
+    pub fn publish_salts(&mut self, salts: EpochSalts) -> anyhow::Result<()> {
+        publish_salts(&mut self.config_map, salts)
+    }
+
+    /// Re-attaches the program to interfaces where it was removed from
+    /// outside (ip link set ... xdp off, bpftool link detach, the
+    /// interface recreated). Only a confirmed absence triggers repair; an
+    /// unreadable state is reported and left alone. Failures are retried.
+    pub fn check_health(&mut self) {
+        if self.last_health.elapsed() < HEALTH_INTERVAL {
+            return;
+        }
+        self.last_health = Instant::now();
+
+        for i in 0..self.ifaces.len() {
+            let name = self.ifaces[i].config.name.clone();
+            match netlink::xdp_prog_ids(&name) {
+                Ok(ids) if ids.contains(&self.prog_id) => continue,
+                Ok(_) => eprintln!("🩺 [{}] program missing on {}, re-attaching", self.name, name),
+                // Невідомий стан — не привід знімати робочий захист
+                Err(e) => {
+                    eprintln!("🩺 [{}] attach state of {} unknown, leaving it as is: {}", self.name, name, e);
+                    continue;
+                }
+            }
+
+            let iface = &self.ifaces[i];
+            let result = program(&mut self.bpf).and_then(|prog| {
+                // Лінк у bpffs уже мертвий: прибираємо pin і ставимо заново
+                pin::detach(&iface.layout)?;
+                pin::attach_or_replace(prog, &name, iface.config.attach.flags(), &iface.layout)
+            });
+            match result {
+                Ok(_) => println!("📌 Re-attached to {}", name),
+                Err(e) => eprintln!("⚠️ Re-attach to {} failed (retry in {}s): {:#}", name, HEALTH_INTERVAL.as_secs(), e),
+            }
+        }
+    }
+
+    /// One loader tick for this map set: cognitive layers, CIDR lists,
+    /// aggregation, policy and the report fragment.
+    pub fn tick(&mut self, state: &mut StringState) -> anyhow::Result<InstanceTick> {
+        // --- Cognitive slow path: семпли → Brain → source_policy ---
+        let mut peak_threat = 0.0;
+        if let Some(d) = self.daemon.as_mut() {
+            let tick = d.poll(state)?;
+            d.expire()?;
+            peak_threat = tick.peak_threat;
+            if tick.samples > 0 || tick.inspected > 0 {
+                println!(
+                    "🧠 [{}] samples={} inspected={} attacks={} new_blocks={} | tracked={} blocked={}",
+                    self.name,
+                    tick.samples,
+                    tick.inspected,
+                    tick.attacks,
+                    tick.new_blocks,
+                    d.tracked_sources(),
+                    d.blocked_sources()
+                );
+            }
+        }
+
+        // --- Top talkers зі скетчу → ті самі per-source ядра ---
+        if let Some(window) = self.top_talkers.poll()? {
+            if let Some(d) = self.daemon.as_mut() {
+                let tick = d.observe_heavy(&window, state)?;
+                peak_threat = f64::max(peak_threat, tick.peak_threat);
+            }
+            if let Some(&(saddr, packets)) = window.top.first() {
+                println!(
+                    "📊 [{}] top talker {} ≈{} pkts of {} in {:.1}s (±{:.0})",
+                    self.name,
+                    daemon::source_label(saddr, false),
+                    packets,
+                    window.packets,
+                    window.secs,
+                    window.error_bound
+                );
+            }
+        }
+
+        // --- Egress: вихідні потоки в тій самій резонансній рамці ---
+        if let Some(e) = self.egress.as_mut() {
+            let tick = e.poll(state)?;
+            peak_threat = f64::max(peak_threat, tick.peak_threat);
+            if tick.alerts > 0 {
+                println!(
+                    "📤 [{}] egress active={} alerts={} | destinations={}",
+                    self.name,
+                    tick.active,
+                    tick.alerts,
+                    e.destinations()
+                );
+            }
+        }
+
+        // --- CIDR lists: live reload + лічильники (кожні 10 сек) ---
+        if let Some(lists) = self.cidr.as_mut() {
+            if let Err(e) = lists.reload_if_changed() {
+                // Помилка у файлі не знімає вже активні списки
+                eprintln!("⚠️ [{}] CIDR reload failed: {:#}", self.name, e);
+            }
+
+            if self.last_cidr_report.elapsed() >= Duration::from_secs(10) {
+                for (entry, hits) in lists.hits() {
+                    println!("   {:<48} hits={}", entry.to_string(), hits);
+                }
+                self.last_cidr_report = Instant::now();
+            }
+        }
+
+        // --- Агрегація ентропії з усіх ядер ---
+        // resonance_map.get(&0, 0) повертає PerCpuValues<u64>
+        let per_cpu: Vec<u64> = match self.resonance_map.get(&0, 0) {
+            Ok(values) => values.iter().copied().collect(),
+            Err(_) => Vec::new(),
+        };
+        let resonance = self.aggregator.update(Instant::now(), &per_cpu);
+        // Після ротації per-CPU стан перезбігається під нові солі: політика
+        // тримає попередню ймовірність до кінця overlap-вікна
+        let drop_prob = if state.salts().in_overlap() {
+            self.policy.probability()
+        } else {
+            self.policy.update(resonance.decision_level)?
+        };
+        let pressure = self.policy.target(resonance.decision_level).max(peak_threat);
+        let status = match (self.policy.mode(), drop_prob > 0.0) {
+            (_, false) => "🟢 STABLE",
+            (PolicyMode::Monitor, true) => "👁 WOULD DROP",
+            (PolicyMode::Enforce, true) => "🔥 MITIGATING",
+        };
+
+        let snapshot = self.xdp_stats.sample()?;
+
+        println!(
+            "[{}] Entropy (median/max): {:016X}/{:016X} spread={:.3} trend={:?} | Status: {} p={:.2} | {:.0} pps (pass {:.0} / drop {:.0} / non-IP {:.0})",
+            self.name,
+            resonance.snapshot.median,
+            resonance.snapshot.max,
+            resonance.snapshot.spread,
+            resonance.trend,
+            status,
+            drop_prob,
+            snapshot.rates.total,
+            snapshot.rates.passed,
+            snapshot.rates.dropped,
+            snapshot.rates.non_ip,
+        );
+
+        // --- Machine-readable report fragment ---
+        let cidr_json = self
+            .cidr
+            .as_ref()
+            .map(|lists| {
+                lists
+                    .hits()
+                    .iter()
+                    .map(|(entry, hits)| format!("{{\"entry\":\"{}\",\"hits\":{}}}", entry, hits))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .unwrap_or_default();
+        let ifaces_json = self
+            .ifaces
+            .iter()
+            .map(|i| format!("\"{}\"", i.config.name))
+            .collect::<Vec<_>>()
+            .join(",");
+        let json = format!(
+            "{{\"name\":\"{}\",\"ifaces\":[{}],\"resonance\":{},\"mode\":\"{:?}\",\"drop_probability\":{:.4},\"xdp\":{},\"daemon\":{},\"egress\":{},\"top_talkers\":{},\"cidr\":[{}]}}",
+            self.name,
+            ifaces_json,
+            resonance.to_json(),
+            self.policy.mode(),
+            drop_prob,
+            snapshot.to_json(),
+            self.daemon
+                .as_ref()
+                .map(|d| {
+                    format!(
+                        "{{\"tracked\":{},\"blocked\":{},\"xsk_queues\":{},\"rhythm\":{},\"spectrum\":{}}}",
+                        d.tracked_sources(),
+                        d.blocked_sources(),
+                        d.inspector_queues(),
+                        d.rhythm_json(16),
+                        d.beacon_json(16)
+                    )
+                })
+                .unwrap_or_else(|| "null".to_string()),
+            self.egress
+                .as_ref()
+                .map(|e| format!("{{\"destinations\":{},\"alerts\":{}}}", e.destinations(), e.total_alerts()))
+                .unwrap_or_else(|| "null".to_string()),
+            self.top_talkers.last().to_json(),
+            cidr_json,
+        );
+
+        Ok(InstanceTick { pressure, json })
+    }
+
+    /// Clean shutdown detaches every interface; a handover leaves the
+    /// programs attached and the state pinned for the next loader.
+    pub fn shutdown(self, handover: bool) -> anyhow::Result<()> {
+        for iface in &self.ifaces {
+            if handover {
+                println!("🤝 Handover: program stays attached to {}, state pinned", iface.config.name);
+            } else {
+                pin::detach(&iface.layout)?;
+                println!(
+                    "🛑 Detached from {} (maps kept in {})",
+                    iface.config.name,
+                    iface.layout.maps_dir().display()
+                );
+            }
+        }
+        Ok(())
+    }
+}
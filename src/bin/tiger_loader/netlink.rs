@@ -0,0 +1,211 @@
+// src/bin/tiger_loader/netlink.rs
+//
+// XDP attach state of an interface, straight from rtnetlink
+// (RTM_GETLINK → IFLA_XDP). Health check має розрізняти «програми точно
+// немає» і «стан невідомий»: перепідключення дозволене лише в першому
+// випадку, інакше збій запиту знімав би робочий захист.
+
+use std::ffi::CString;
+use std::io;
+use std::mem::size_of;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+// linux/netlink.h, linux/rtnetlink.h, linux/if_link.h
+const NLMSG_ERROR: u16 = 2;
+const NLM_F_REQUEST: u16 = 1;
+const RTM_NEWLINK: u16 = 16;
+const RTM_GETLINK: u16 = 18;
+const IFLA_XDP: u16 = 43;
+const IFLA_XDP_ATTACHED: u16 = 2;
+const IFLA_XDP_PROG_ID: u16 = 4;
+const IFLA_XDP_SKB_PROG_ID: u16 = 6;
+const IFLA_XDP_DRV_PROG_ID: u16 = 7;
+const IFLA_XDP_HW_PROG_ID: u16 = 8;
+const XDP_ATTACHED_NONE: u8 = 0;
+/// Attribute type without NLA_F_NESTED / NLA_F_NET_BYTEORDER.
+const NLA_TYPE_MASK: u16 = 0x3FFF;
+
+#[repr(C)]
+struct NlMsgHdr {
+    len: u32,
+    kind: u16,
+    flags: u16,
+    seq: u32,
+    pid: u32,
+}
+
+#[repr(C)]
+struct IfInfoMsg {
+    family: u8,
+    _pad: u8,
+    kind: u16,
+    index: i32,
+    flags: u32,
+    change: u32,
+}
+
+#[repr(C)]
+struct LinkRequest {
+    hdr: NlMsgHdr,
+    info: IfInfoMsg,
+}
+
+const HDR_LEN: usize = size_of::<NlMsgHdr>();
+const INFO_LEN: usize = size_of::<IfInfoMsg>();
+
+fn check(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed RTM_NEWLINK reply")
+}
+
+fn u16_at(b: &[u8], at: usize) -> u16 {
+    u16::from_ne_bytes([b[at], b[at + 1]])
+}
+
+fn u32_at(b: &[u8], at: usize) -> u32 {
+    u32::from_ne_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]])
+}
+
+/// Netlink attributes of `b` as (type, payload); stops at the first
+/// truncated one.
+fn attrs(mut b: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    std::iter::from_fn(move || {
+        if b.len() < 4 {
+            return None;
+        }
+        let len = u16_at(b, 0) as usize;
+        if len < 4 || len > b.len() {
+            return None;
+        }
+        let item = (u16_at(b, 2) & NLA_TYPE_MASK, &b[4..len]);
+        b = &b[((len + 3) & !3).min(b.len())..];
+        Some(item)
+    })
+}
+
+/// Ids of the XDP programs on `iface`, one per attach mode in use.
+/// Empty: nothing is attached or the interface is gone. Err: the state
+/// could not be read, and the caller must not act on it.
+pub fn xdp_prog_ids(iface: &str) -> io::Result<Vec<u32>> {
+    let name = CString::new(iface)?;
+    // SAFETY: name — валідний C-рядок
+    let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    if ifindex == 0 {
+        let e = io::Error::last_os_error();
+        return match e.raw_os_error() {
+            Some(libc::ENODEV) => Ok(Vec::new()),
+            _ => Err(e),
+        };
+    }
+
+    // SAFETY: звичайний системний виклик без вказівників
+    let fd = check(unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_RAW | libc::SOCK_CLOEXEC, libc::NETLINK_ROUTE) })?;
+    // SAFETY: fd щойно створений і більше нікому не належить
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // Ядро відповідає одразу; тайм-аут лише страхує цикл лоадера
+    let timeout = libc::timeval { tv_sec: 1, tv_usec: 0 };
+    // SAFETY: timeout — повна timeval
+    check(unsafe {
+        libc::setsockopt(
+            fd.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            &timeout as *const libc::timeval as *const libc::c_void,
+            size_of::<libc::timeval>() as libc::socklen_t,
+        )
+    })?;
+
+    let request = LinkRequest {
+        hdr: NlMsgHdr {
+            len: size_of::<LinkRequest>() as u32,
+            kind: RTM_GETLINK,
+            flags: NLM_F_REQUEST,
+            seq: 1,
+            pid: 0,
+        },
+        info: IfInfoMsg {
+            family: libc::AF_UNSPEC as u8,
+            _pad: 0,
+            kind: 0,
+            index: ifindex as i32,
+            flags: 0,
+            change: 0,
+        },
+    };
+    // SAFETY: request — repr(C) буфер указаного розміру
+    let sent = unsafe {
+        libc::send(
+            fd.as_raw_fd(),
+            &request as *const LinkRequest as *const libc::c_void,
+            size_of::<LinkRequest>(),
+            0,
+        )
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut buf = vec![0u8; 32 * 1024];
+    // SAFETY: buf — записуваний буфер довжини buf.len()
+    let n = unsafe { libc::recv(fd.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+    if n < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    parse_reply(&buf[..n as usize])
+}
+
+fn parse_reply(b: &[u8]) -> io::Result<Vec<u32>> {
+    if b.len() < HDR_LEN {
+        return Err(malformed());
+    }
+    let len = (u32_at(b, 0) as usize).min(b.len());
+    match u16_at(b, 4) {
+        NLMSG_ERROR if len >= HDR_LEN + 4 => {
+            let errno = -(u32_at(b, HDR_LEN) as i32);
+            // Інтерфейс зник між if_nametoindex і запитом
+            if errno == libc::ENODEV {
+                Ok(Vec::new())
+            } else {
+                Err(io::Error::from_raw_os_error(errno))
+            }
+        }
+        RTM_NEWLINK if len >= HDR_LEN + INFO_LEN => {
+            let xdp = attrs(&b[HDR_LEN + INFO_LEN..len])
+                .find(|&(kind, _)| kind == IFLA_XDP)
+                .map(|(_, payload)| payload)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "no IFLA_XDP in the link reply"))?;
+
+            let mut attached = None;
+            let mut ids = Vec::new();
+            for (kind, payload) in attrs(xdp) {
+                match kind {
+                    IFLA_XDP_ATTACHED if !payload.is_empty() => attached = Some(payload[0]),
+                    IFLA_XDP_PROG_ID | IFLA_XDP_SKB_PROG_ID | IFLA_XDP_DRV_PROG_ID | IFLA_XDP_HW_PROG_ID
+                        if payload.len() >= 4 =>
+                    {
+                        let id = u32_at(payload, 0);
+                        if id != 0 && !ids.contains(&id) {
+                            ids.push(id);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            match attached {
+                Some(XDP_ATTACHED_NONE) => Ok(Vec::new()),
+                // Щось прикріплене, але id не прочитали: стан невідомий
+                Some(_) if !ids.is_empty() => Ok(ids),
+                _ => Err(malformed()),
+            }
+        }
+        _ => Err(malformed()),
+    }
+}
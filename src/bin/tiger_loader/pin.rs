@@ -0,0 +1,110 @@
+// src/bin/tiger_loader/pin.rs
+//
+// bpffs pinning and restart-safe attach.
+// Карти закріплюються в <root>/<iface>/ (LIBBPF_PIN_BY_NAME), XDP-лінк —
+// у <root>/<iface>/link. Спільний набір карт кількох інтерфейсів живе в
+// <root>/shared/, лінки — як і раніше, окремо на кожен інтерфейс. Якщо лоадер вбито, лінк лишається в ядрі разом
+// зі станом; новий лоадер знаходить його і атомарно підміняє програму
+// через bpf_link_update, без вікна без захисту.
+
+use anyhow::Context;
+use aya::programs::links::{FdLink, PinnedLink};
+use aya::programs::xdp::XdpLink;
+use aya::programs::{Xdp, XdpFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default bpffs root for all TigerΔ pins.
+pub const DEFAULT_PIN_ROOT: &str = "/sys/fs/bpf/tiger_delta";
+
+/// Per-interface pin directory layout.
+#[derive(Clone, Debug)]
+pub struct PinLayout {
+    maps: PathBuf,
+    links: PathBuf,
+}
+
+fn create_dir(dir: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("cannot create pin dir {} (is bpffs mounted?)", dir.display()))
+}
+
+impl PinLayout {
+    pub fn for_iface(root: impl AsRef<Path>, iface: &str) -> anyhow::Result<Self> {
+        let dir = root.as_ref().join(iface);
+        create_dir(&dir)?;
+        Ok(Self { maps: dir.clone(), links: dir })
+    }
+
+    /// Maps in <root>/<group>, the link still in <root>/<iface>.
+    pub fn shared(root: impl AsRef<Path>, group: &str, iface: &str) -> anyhow::Result<Self> {
+        let maps = root.as_ref().join(group);
+        let links = root.as_ref().join(iface);
+        create_dir(&maps)?;
+        create_dir(&links)?;
+        Ok(Self { maps, links })
+    }
+
+    /// Directory handed to the loader for by-name map pinning.
+    pub fn maps_dir(&self) -> &Path {
+        &self.maps
+    }
+
+    pub fn link_path(&self) -> PathBuf {
+        self.links.join("link")
+    }
+
+    /// True when a previous loader left a live program behind.
+    pub fn has_link(&self) -> bool {
+        self.link_path().exists()
+    }
+}
+
+/// How the program ended up on the interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachOutcome {
+    Fresh,
+    Replaced,
+}
+
+/// Attaches the loaded program, or swaps it into the pinned link if one
+/// already exists. The link is (re)pinned so it outlives this process.
+pub fn attach_or_replace(
+    program: &mut Xdp,
+    iface: &str,
+    flags: XdpFlags,
+    layout: &PinLayout,
+) -> anyhow::Result<AttachOutcome> {
+    let link_path = layout.link_path();
+
+    if layout.has_link() {
+        let pinned = PinnedLink::from_pin(&link_path)
+            .with_context(|| format!("cannot open pinned link {}", link_path.display()))?;
+        let link = XdpLink::try_from(FdLink::from(pinned))?;
+
+        // bpf_link_update: стара програма обробляє пакети до моменту підміни.
+        // Закриття нашого fd не від'єднує лінк — його тримає pin.
+        program.attach_to_link(link)?;
+        return Ok(AttachOutcome::Replaced);
+    }
+
+    let id = program.attach(iface, flags)?;
+    let link = program.take_link(id)?;
+    FdLink::try_from(link)?
+        .pin(&link_path)
+        .with_context(|| format!("cannot pin link to {}", link_path.display()))?;
+    Ok(AttachOutcome::Fresh)
+}
+
+/// Clean shutdown: removes the link pin, which detaches the program once
+/// the last descriptor is closed. Map pins stay, so history survives.
+pub fn detach(layout: &PinLayout) -> anyhow::Result<()> {
+    let link_path = layout.link_path();
+    if !layout.has_link() {
+        return Ok(());
+    }
+    let pinned = PinnedLink::from_pin(&link_path)?;
+    let fd_link = pinned.unpin()?;
+    drop(fd_link);
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// src/bin/tiger_loader/policy.rs
+//
+// Graduated drop policy.
+// Замість перемикача 0/1 лоадер публікує ймовірність скидання, яка
+// плавно росте з рівнем загрози: лінійна рампа між `ramp_low` і
+// `ramp_high` агрегованого рівня резонансу (частка діапазону u64, див.
+// aggregate.rs), з обмеженням кроку за один тік (швидше вгору,
+// повільніше вниз).
+
+use aya::maps::{Array, MapData};
+use aya::Bpf;
+use tiger_delta_ai_safety::xdp_emulator::prob_to_fixed;
+
+use crate::take_map;
+
+/// Mirrors `enum tiger_policy` in src/kernel/tiger_delta_xdp.c.
+const POLICY_MODE: u32 = 0;
+const POLICY_DROP_PROB: u32 = 1;
+const POLICY_SAMPLE_RATE: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyMode {
+    Monitor = 0,
+    Enforce = 1,
+}
+
+impl PolicyMode {
+    pub fn from_env_value(value: &str) -> Option<Self> {
+        match value {
+            "monitor" => Some(PolicyMode::Monitor),
+            "enforce" => Some(PolicyMode::Enforce),
+            _ => None,
+        }
+    }
+}
+
+pub struct DropPolicy {
+    map: Array<MapData, u32>,
+    mode: PolicyMode,
+    probability: f64,
+    /// Level where dropping starts (the old binary cliff at 0x8000…).
+    pub ramp_low: f64,
+    /// Level at which every packet is dropped.
+    pub ramp_high: f64,
+    /// Max probability increase per update.
+    pub step_up: f64,
+    /// Max probability decrease per update.
+    pub step_down: f64,
+}
+
+impl DropPolicy {
+    pub fn new(bpf: &mut Bpf, mode: PolicyMode) -> anyhow::Result<Self> {
+        let mut policy = Self {
+            map: Array::try_from(take_map(bpf, "policy_map")?)?,
+            mode,
+            probability: 0.0,
+            ramp_low: 0.5,
+            ramp_high: 0.875,
+            step_up: 0.2,
+            step_down: 0.05,
+        };
+        policy.map.set(POLICY_MODE, mode as u32, 0)?;
+        // Перезапуск продовжує з уже опублікованої ймовірності
+        let pinned = policy.map.get(&POLICY_DROP_PROB, 0).unwrap_or(0);
+        policy.probability = pinned as f64 / 4_294_967_296.0;
+        Ok(policy)
+    }
+
+    /// Target probability for a given aggregated level.
+    pub fn target(&self, level: f64) -> f64 {
+        ((level - self.ramp_low) / (self.ramp_high - self.ramp_low)).clamp(0.0, 1.0)
+    }
+
+    /// Moves towards the target with bounded steps and publishes it.
+    pub fn update(&mut self, level: f64) -> anyhow::Result<f64> {
+        let target = self.target(level);
+        let delta = (target - self.probability).clamp(-self.step_down, self.step_up);
+        self.probability = (self.probability + delta).clamp(0.0, 1.0);
+
+        self.map.set(POLICY_DROP_PROB, prob_to_fixed(self.probability), 0)?;
+        Ok(self.probability)
+    }
+
+    /// Copies 1-in-`rate` packets to the `samples` ring (0 disables).
+    pub fn set_sample_rate(&mut self, rate: u32) -> anyhow::Result<()> {
+        self.map.set(POLICY_SAMPLE_RATE, rate, 0)?;
+        Ok(())
+    }
+
+    /// Last published probability.
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    pub fn mode(&self) -> PolicyMode {
+        self.mode
+    }
+}
@@ -0,0 +1,154 @@
+// src/bin/tiger_loader/stats.rs
+//
+// XDP packet/verdict counters.
+// Ядро рахує пакети в per-CPU слотах `stats_map`; лоадер сумує їх по
+// всіх CPU, рахує швидкість (pps) між двома вибірками і віддає знімок
+// у консоль та у JSON-файл для моніторингу.
+
+use aya::maps::{MapData, PerCpuArray};
+use aya::Bpf;
+use std::fs;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::take_map;
+
+/// Mirrors `enum tiger_stat` in src/kernel/tiger_delta_xdp.c.
+const STAT_TOTAL: u32 = 0;
+const STAT_NON_IP: u32 = 1;
+const STAT_PASSED: u32 = 2;
+const STAT_DROPPED: u32 = 3;
+const STAT_ALLOW_HIT: u32 = 4;
+const STAT_DENY_HIT: u32 = 5;
+const STAT_REDIRECTED: u32 = 6;
+
+/// Cumulative counters, summed over all CPUs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Counters {
+    pub total: u64,
+    pub non_ip: u64,
+    pub passed: u64,
+    pub dropped: u64,
+    pub allow_hits: u64,
+    pub deny_hits: u64,
+    /// Packets handed to the AF_XDP inspector.
+    pub redirected: u64,
+}
+
+/// Per-second rates between two consecutive samples.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rates {
+    pub total: f64,
+    pub non_ip: f64,
+    pub passed: f64,
+    pub dropped: f64,
+    pub allow_hits: f64,
+    pub deny_hits: f64,
+    pub redirected: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StatsSnapshot {
+    pub counters: Counters,
+    pub rates: Rates,
+}
+
+impl StatsSnapshot {
+    /// Share of packets dropped since the previous sample, in [0, 1].
+    pub fn drop_ratio(&self) -> f64 {
+        if self.rates.total > 0.0 {
+            (self.rates.dropped / self.rates.total).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let c = &self.counters;
+        let r = &self.rates;
+        format!(
+            "{{\"counters\":{{\"total\":{},\"non_ip\":{},\"passed\":{},\"dropped\":{},\"allow_hits\":{},\"deny_hits\":{},\"redirected\":{}}},\
+             \"rates_pps\":{{\"total\":{:.1},\"non_ip\":{:.1},\"passed\":{:.1},\"dropped\":{:.1},\"allow_hits\":{:.1},\"deny_hits\":{:.1},\"redirected\":{:.1}}}}}",
+            c.total, c.non_ip, c.passed, c.dropped, c.allow_hits, c.deny_hits, c.redirected,
+            r.total, r.non_ip, r.passed, r.dropped, r.allow_hits, r.deny_hits, r.redirected,
+        )
+    }
+}
+
+pub struct XdpStats {
+    map: PerCpuArray<MapData, u64>,
+    last: Counters,
+    last_at: Instant,
+}
+
+impl XdpStats {
+    pub fn new(bpf: &mut Bpf) -> anyhow::Result<Self> {
+        let mut stats = Self {
+            map: PerCpuArray::try_from(take_map(bpf, "stats_map")?)?,
+            last: Counters::default(),
+            last_at: Instant::now(),
+        };
+        stats.last = stats.read()?;
+        Ok(stats)
+    }
+
+    fn sum(&self, index: u32) -> anyhow::Result<u64> {
+        let values = self.map.get(&index, 0)?;
+        Ok(values.iter().fold(0u64, |acc, v| acc.saturating_add(*v)))
+    }
+
+    fn read(&self) -> anyhow::Result<Counters> {
+        Ok(Counters {
+            total: self.sum(STAT_TOTAL)?,
+            non_ip: self.sum(STAT_NON_IP)?,
+            passed: self.sum(STAT_PASSED)?,
+            dropped: self.sum(STAT_DROPPED)?,
+            allow_hits: self.sum(STAT_ALLOW_HIT)?,
+            deny_hits: self.sum(STAT_DENY_HIT)?,
+            redirected: self.sum(STAT_REDIRECTED)?,
+        })
+    }
+
+    /// Reads the counters and derives rates against the previous sample.
+    pub fn sample(&mut self) -> anyhow::Result<StatsSnapshot> {
+        let now = Instant::now();
+        let counters = self.read()?;
+        let dt = now.duration_since(self.last_at).as_secs_f64().max(1e-3);
+
+        // saturating_sub: лічильники обнуляються при перезавантаженні програми
+        let rate = |cur: u64, prev: u64| cur.saturating_sub(prev) as f64 / dt;
+        let p = self.last;
+        let rates = Rates {
+            total: rate(counters.total, p.total),
+            non_ip: rate(counters.non_ip, p.non_ip),
+            passed: rate(counters.passed, p.passed),
+            dropped: rate(counters.dropped, p.dropped),
+            allow_hits: rate(counters.allow_hits, p.allow_hits),
+            deny_hits: rate(counters.deny_hits, p.deny_hits),
+            redirected: rate(counters.redirected, p.redirected),
+        };
+
+        self.last = counters;
+        self.last_at = now;
+        Ok(StatsSnapshot { counters, rates })
+    }
+}
+
+/// Writes a JSON document atomically (tmp file + rename), so readers
+/// never observe a half-written report.
+pub fn write_report(path: &Path, json: &str) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
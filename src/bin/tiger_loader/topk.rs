@@ -0,0 +1,124 @@
+// src/bin/tiger_loader/topk.rs
+//
+// Top talkers from the in-kernel count-min sketch.
+// Ядро рахує пакети кожного джерела в per-CPU скетчі `cms_rows`; тут
+// CPU сумуються, два послідовні зчитування дають вікно, а кандидати з
+// `cms_candidates` ранжуються за оцінкою. Межі похибки — src/sketch.rs.
+
+use aya::maps::{HashMap as BpfHashMap, MapData, PerCpuArray};
+use aya::{Bpf, Pod};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tiger_delta_ai_safety::sketch::{self, CountMinSketch, CMS_DEPTH, CMS_WIDTH};
+
+use crate::daemon::source_label;
+use crate::take_map;
+
+/// Mirror of `struct cms_row`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CmsRow {
+    cells: [u32; CMS_WIDTH],
+}
+
+// SAFETY: repr(C) масив u32 без padding
+unsafe impl Pod for CmsRow {}
+
+/// One window of the sketch: the heaviest sources and the error bound
+/// every estimate in it carries.
+#[derive(Clone, Debug, Default)]
+pub struct HeavyWindow {
+    pub top: Vec<(u32, u32)>,
+    pub packets: u64,
+    pub secs: f64,
+    /// ε·N: estimates exceed true counts by at most this, w.p. 1 − δ.
+    pub error_bound: f64,
+}
+
+impl HeavyWindow {
+    pub fn to_json(&self) -> String {
+        let top = self
+            .top
+            .iter()
+            .map(|&(saddr, packets)| {
+                // Ключ скетчу не несе is_v6: згорнуті IPv6 теж друкуються як IPv4
+                format!("{{\"source\":\"{}\",\"packets\":{}}}", source_label(saddr, false), packets)
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"window_s\":{:.2},\"packets\":{},\"error_bound\":{:.1},\"epsilon\":{:.5},\"delta\":{:.4},\"top\":[{}]}}",
+            self.secs,
+            self.packets,
+            self.error_bound,
+            sketch::epsilon(),
+            sketch::delta(),
+            top
+        )
+    }
+}
+
+pub struct TopTalkers {
+    rows: PerCpuArray<MapData, CmsRow>,
+    candidates: BpfHashMap<MapData, u32, u32>,
+    prev: CountMinSketch,
+    prev_at: Instant,
+    last: HeavyWindow,
+    pub k: usize,
+    pub window: Duration,
+}
+
+impl TopTalkers {
+    pub fn new(bpf: &mut Bpf) -> anyhow::Result<Self> {
+        let mut talkers = Self {
+            rows: PerCpuArray::try_from(take_map(bpf, "cms_rows")?)?,
+            candidates: BpfHashMap::try_from(take_map(bpf, "cms_candidates")?)?,
+            prev: CountMinSketch::new(),
+            prev_at: Instant::now(),
+            last: HeavyWindow::default(),
+            k: 16,
+            window: Duration::from_secs(2),
+        };
+        // Закріплений скетч пережив перезапуск: перше вікно — від цієї миті
+        talkers.prev = talkers.read()?;
+        Ok(talkers)
+    }
+
+    fn read(&self) -> anyhow::Result<CountMinSketch> {
+        let mut merged = CountMinSketch::new();
+        for row in 0..CMS_DEPTH {
+            for cpu_row in self.rows.get(&(row as u32), 0)?.iter() {
+                merged.add_row(row, &cpu_row.cells);
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Closes the window once it is due; None otherwise.
+    pub fn poll(&mut self) -> anyhow::Result<Option<HeavyWindow>> {
+        let now = Instant::now();
+        if now.duration_since(self.prev_at) < self.window {
+            return Ok(None);
+        }
+
+        let current = self.read()?;
+        let window = current.since(&self.prev);
+        let keys: HashSet<u32> = self.candidates.keys().filter_map(|k| k.ok()).collect();
+
+        let heavy = HeavyWindow {
+            top: sketch::top_k(&window, &keys, self.k),
+            packets: window.total(),
+            secs: now.duration_since(self.prev_at).as_secs_f64(),
+            error_bound: window.error_bound(),
+        };
+        self.prev = current;
+        self.prev_at = now;
+        self.last = heavy.clone();
+        Ok(Some(heavy))
+    }
+
+    /// The most recent closed window (for the stats report).
+    pub fn last(&self) -> &HeavyWindow {
+        &self.last
+    }
+}
@@ -0,0 +1,392 @@
+// src/bin/tiger_loader/xsk.rs
+//
+// AF_XDP inspection path (RX only, copy mode — працює і на veth).
+// Джерела з невизначеним вердиктом отримують бюджет пакетів у
+// `inspect_sources`; ядро перенаправляє їх у XSK-сокет черги, на якій
+// пакет прийшов, і тут повний payload іде через ті самі екстрактори,
+// що й у main.rs. Вердикт повертається в ядро через `source_policy`.
+
+use aya::maps::{HashMap as BpfHashMap, MapData, XskMap};
+use aya::Bpf;
+use std::ffi::CString;
+use std::io;
+use std::os::fd::RawFd;
+use std::ptr;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::take_map;
+
+// linux/if_xdp.h
+const AF_XDP: libc::c_int = 44;
+const SOL_XDP: libc::c_int = 283;
+const XDP_MMAP_OFFSETS: libc::c_int = 1;
+const XDP_RX_RING: libc::c_int = 2;
+const XDP_UMEM_REG: libc::c_int = 4;
+const XDP_UMEM_FILL_RING: libc::c_int = 5;
+const XDP_UMEM_COMPLETION_RING: libc::c_int = 6;
+const XDP_PGOFF_RX_RING: libc::off_t = 0;
+const XDP_UMEM_PGOFF_FILL_RING: libc::off_t = 0x1_0000_0000;
+const XDP_UMEM_PGOFF_COMPLETION_RING: libc::off_t = 0x1_8000_0000;
+const XDP_COPY: u16 = 1 << 1;
+
+/// UMEM geometry: every frame sits in the fill ring or in the RX ring.
+const FRAME_SIZE: u32 = 2048;
+const RING_SIZE: u32 = 2048;
+const NUM_FRAMES: u32 = RING_SIZE;
+
+/// Packets a source may send to the inspector per marking.
+pub const INSPECT_BUDGET: u32 = 32;
+
+#[repr(C)]
+struct XdpUmemReg {
+    addr: u64,
+    len: u64,
+    chunk_size: u32,
+    headroom: u32,
+    flags: u32,
+    tx_metadata_len: u32,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct XdpRingOffset {
+    producer: u64,
+    consumer: u64,
+    desc: u64,
+    flags: u64,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct XdpMmapOffsets {
+    rx: XdpRingOffset,
+    tx: XdpRingOffset,
+    fr: XdpRingOffset,
+    cr: XdpRingOffset,
+}
+
+#[repr(C)]
+struct SockaddrXdp {
+    sxdp_family: u16,
+    sxdp_flags: u16,
+    sxdp_ifindex: u32,
+    sxdp_queue_id: u32,
+    sxdp_shared_umem_fd: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct XdpDesc {
+    addr: u64,
+    len: u32,
+    options: u32,
+}
+
+/// One mmap'ed single-producer/single-consumer ring.
+struct Ring {
+    area: *mut libc::c_void,
+    area_len: usize,
+    producer: *const AtomicU32,
+    consumer: *const AtomicU32,
+    desc: *mut u8,
+    mask: u32,
+}
+
+impl Ring {
+    fn map(fd: RawFd, off: &XdpRingOffset, entry: usize, pgoff: libc::off_t) -> io::Result<Self> {
+        let area_len = off.desc as usize + RING_SIZE as usize * entry;
+        // SAFETY: fd — зв'язаний AF_XDP сокет, розмір і зсув дав сам kernel
+        let area = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                area_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_POPULATE,
+                fd,
+                pgoff,
+            )
+        };
+        if area == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let base = area as *mut u8;
+        // SAFETY: зсуви лежать всередині щойно відображеної області
+        unsafe {
+            Ok(Self {
+                area,
+                area_len,
+                producer: base.add(off.producer as usize) as *const AtomicU32,
+                consumer: base.add(off.consumer as usize) as *const AtomicU32,
+                desc: base.add(off.desc as usize),
+                mask: RING_SIZE - 1,
+            })
+        }
+    }
+
+    fn producer(&self) -> &AtomicU32 {
+        // SAFETY: вказівник у межах mmap, живе доки живе Ring
+        unsafe { &*self.producer }
+    }
+
+    fn consumer(&self) -> &AtomicU32 {
+        // SAFETY: як вище
+        unsafe { &*self.consumer }
+    }
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        // SAFETY: область відображена в Ring::map і більше ніде не використовується
+        unsafe {
+            libc::munmap(self.area, self.area_len);
+        }
+    }
+}
+
+fn check(ret: libc::c_int) -> io::Result<()> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+fn set_opt<T>(fd: RawFd, name: libc::c_int, value: &T) -> io::Result<()> {
+    // SAFETY: value — repr(C) структура або u32 потрібного для name розміру
+    check(unsafe {
+        libc::setsockopt(
+            fd,
+            SOL_XDP,
+            name,
+            value as *const T as *const libc::c_void,
+            std::mem::size_of::<T>() as libc::socklen_t,
+        )
+    })
+}
+
+/// RX-only AF_XDP socket bound to one queue of one interface.
+pub struct XskSocket {
+    fd: RawFd,
+    queue: u32,
+    umem: *mut libc::c_void,
+    umem_len: usize,
+    fill: Ring,
+    rx: Ring,
+    // Не використовується для RX, але bind вимагає completion ring
+    _completion: Ring,
+}
+
+impl XskSocket {
+    pub fn bind(iface: &str, queue: u32) -> anyhow::Result<Self> {
+        let name = CString::new(iface)?;
+        // SAFETY: name — валідний C-рядок
+        let ifindex = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if ifindex == 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        // SAFETY: звичайний системний виклик без вказівників
+        let fd = unsafe { libc::socket(AF_XDP, libc::SOCK_RAW, 0) };
+        check(fd)?;
+
+        let umem_len = (NUM_FRAMES * FRAME_SIZE) as usize;
+        // SAFETY: анонімне відображення, вирівняне на сторінку
+        let umem = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                umem_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if umem == libc::MAP_FAILED {
+            let e = io::Error::last_os_error();
+            // SAFETY: fd відкритий вище
+            unsafe { libc::close(fd) };
+            return Err(e.into());
+        }
+
+        let result = Self::setup(fd, ifindex, queue, umem, umem_len);
+        if result.is_err() {
+            // SAFETY: ресурси створені вище і ще нікому не віддані
+            unsafe {
+                libc::munmap(umem, umem_len);
+                libc::close(fd);
+            }
+        }
+        result
+    }
+
+    fn setup(
+        fd: RawFd,
+        ifindex: u32,
+        queue: u32,
+        umem: *mut libc::c_void,
+        umem_len: usize,
+    ) -> anyhow::Result<Self> {
+        let reg = XdpUmemReg {
+            addr: umem as u64,
+            len: umem_len as u64,
+            chunk_size: FRAME_SIZE,
+            headroom: 0,
+            flags: 0,
+            tx_metadata_len: 0,
+        };
+        set_opt(fd, XDP_UMEM_REG, &reg)?;
+        set_opt(fd, XDP_UMEM_FILL_RING, &RING_SIZE)?;
+        set_opt(fd, XDP_UMEM_COMPLETION_RING, &RING_SIZE)?;
+        set_opt(fd, XDP_RX_RING, &RING_SIZE)?;
+
+        let mut off = XdpMmapOffsets::default();
+        let mut optlen = std::mem::size_of::<XdpMmapOffsets>() as libc::socklen_t;
+        // SAFETY: off — repr(C) буфер розміру optlen
+        check(unsafe {
+            libc::getsockopt(
+                fd,
+                SOL_XDP,
+                XDP_MMAP_OFFSETS,
+                &mut off as *mut XdpMmapOffsets as *mut libc::c_void,
+                &mut optlen,
+            )
+        })?;
+
+        let fill = Ring::map(fd, &off.fr, std::mem::size_of::<u64>(), XDP_UMEM_PGOFF_FILL_RING)?;
+        let completion = Ring::map(fd, &off.cr, std::mem::size_of::<u64>(), XDP_UMEM_PGOFF_COMPLETION_RING)?;
+        let rx = Ring::map(fd, &off.rx, std::mem::size_of::<XdpDesc>(), XDP_PGOFF_RX_RING)?;
+
+        // Усі кадри UMEM одразу віддаються ядру
+        for i in 0..NUM_FRAMES {
+            // SAFETY: i < RING_SIZE, слот у межах fill ring
+            unsafe {
+                (fill.desc as *mut u64).add(i as usize).write((i * FRAME_SIZE) as u64);
+            }
+        }
+        fill.producer().store(NUM_FRAMES, Ordering::Release);
+
+        let addr = SockaddrXdp {
+            sxdp_family: AF_XDP as u16,
+            sxdp_flags: XDP_COPY,
+            sxdp_ifindex: ifindex,
+            sxdp_queue_id: queue,
+            sxdp_shared_umem_fd: 0,
+        };
+        // SAFETY: addr — повна sockaddr_xdp
+        check(unsafe {
+            libc::bind(
+                fd,
+                &addr as *const SockaddrXdp as *const libc::sockaddr,
+                std::mem::size_of::<SockaddrXdp>() as libc::socklen_t,
+            )
+        })?;
+
+        Ok(Self {
+            fd,
+            queue,
+            umem,
+            umem_len,
+            fill,
+            rx,
+            _completion: completion,
+        })
+    }
+
+    /// Hands every received frame to `f`, then returns the frames to
+    /// the fill ring. Non-blocking: an empty RX ring returns 0.
+    pub fn drain(&mut self, mut f: impl FnMut(&[u8])) -> usize {
+        let prod = self.rx.producer().load(Ordering::Acquire);
+        let mut cons = self.rx.consumer().load(Ordering::Relaxed);
+        let mut fill_prod = self.fill.producer().load(Ordering::Relaxed);
+        let mut n = 0;
+
+        while cons != prod {
+            // SAFETY: слот cons & mask заповнений ядром (cons != prod)
+            let desc = unsafe { (self.rx.desc as *const XdpDesc).add((cons & self.rx.mask) as usize).read() };
+            if desc.addr as usize + desc.len as usize <= self.umem_len {
+                // SAFETY: кадр лежить в UMEM, межі перевірено
+                let frame = unsafe {
+                    std::slice::from_raw_parts((self.umem as *const u8).add(desc.addr as usize), desc.len as usize)
+                };
+                f(frame);
+            }
+
+            // Повертаємо кадр (вирівняний на початок chunk) у fill ring
+            let base = desc.addr - desc.addr % FRAME_SIZE as u64;
+            // SAFETY: кадр щойно звільнено, тож вільний слот у fill ring є
+            unsafe {
+                (self.fill.desc as *mut u64).add((fill_prod & self.fill.mask) as usize).write(base);
+            }
+            fill_prod = fill_prod.wrapping_add(1);
+            cons = cons.wrapping_add(1);
+            n += 1;
+        }
+
+        if n > 0 {
+            self.fill.producer().store(fill_prod, Ordering::Release);
+            self.rx.consumer().store(cons, Ordering::Release);
+        }
+        n
+    }
+}
+
+impl Drop for XskSocket {
+    fn drop(&mut self) {
+        // Кільця — окремі відображення, вони знімаються вже після цього
+        // SAFETY: fd і umem належать лише цьому сокету
+        unsafe {
+            libc::close(self.fd);
+            libc::munmap(self.umem, self.umem_len);
+        }
+    }
+}
+
+/// XSK sockets on every inspected queue plus the kernel-side budget map.
+pub struct Inspector {
+    // Сокети мають жити, доки їхні fd лежать у `xsks`
+    _xsks: XskMap<MapData>,
+    sockets: Vec<XskSocket>,
+    budgets: BpfHashMap<MapData, u32, u32>,
+}
+
+impl Inspector {
+    pub fn new(bpf: &mut Bpf, iface: &str, queues: u32) -> anyhow::Result<Self> {
+        let mut xsks = XskMap::try_from(take_map(bpf, "xsks")?)?;
+        let mut sockets = Vec::new();
+        for queue in 0..queues {
+            let socket = XskSocket::bind(iface, queue)?;
+            xsks.set(socket.queue, socket.fd, 0)?;
+            sockets.push(socket);
+        }
+        Ok(Self {
+            _xsks: xsks,
+            sockets,
+            budgets: BpfHashMap::try_from(take_map(bpf, "inspect_sources")?)?,
+        })
+    }
+
+    /// Routes the next `INSPECT_BUDGET` packets of `saddr` to userspace.
+    /// Джерело, що вже має бюджет, не поповнюється.
+    pub fn mark(&mut self, saddr: u32) -> anyhow::Result<bool> {
+        if self.budgets.get(&saddr, 0).is_ok() {
+            return Ok(false);
+        }
+        self.budgets.insert(saddr, INSPECT_BUDGET, 0)?;
+        Ok(true)
+    }
+
+    /// Stops redirecting `saddr` (a verdict has been reached).
+    pub fn clear(&mut self, saddr: u32) {
+        // LRU могла вже витіснити запис — це не помилка
+        let _ = self.budgets.remove(&saddr);
+    }
+
+    /// Drains all queues; returns the number of frames seen.
+    pub fn drain(&mut self, mut f: impl FnMut(&[u8])) -> usize {
+        self.sockets.iter_mut().map(|s| s.drain(&mut f)).sum()
+    }
+
+    pub fn queues(&self) -> usize {
+        self.sockets.len()
+    }
+}
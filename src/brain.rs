@@ -0,0 +1,253 @@
+// =================================================================
+// Project: TigerΔ (Tiger Delta)
+// Module: brain.rs
+// Description: Cognitive pipeline — one packet's features through
+//              Simul → Atomic → StringState/Lagrange → Lumis.
+// =================================================================
+
+use crate::atomic_core::AtomicCore;
+use crate::lagrange::{LagrangeEquilibrium, MultiLagrange};
+use crate::lumis::{LumisClock, LumisCore, PHI};
+use crate::schema::ATTR_COUNT;
+use crate::simul::{Observation, Projection, SimulMode, SimulUnit};
+use crate::string_state::StringState;
+use crate::threshold::ThresholdController;
+use crate::timing::{Arrival, TimeBuckets, ARRIVAL_IMPACT_GAIN, SECOND};
+
+/// Threat probability above which a packet is treated as an attack,
+/// before the adaptive controller (src/threshold.rs) moves it.
+pub const ATTACK_THRESHOLD: f64 = 0.85;
+
+/// Impact of a packet whose schema slots all normalize to 1. The mean
+/// normalized slot times this gain keeps typical payloads near the old
+/// `raw / 10^6` scale (≈ 0.02–0.2), where SimulUnit's φ trigger needs a
+/// sustained heavy stream, not one large packet.
+pub const PAYLOAD_IMPACT_GAIN: f64 = 0.25;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Digital twin projected a breach before the cores were touched.
+    Preempt,
+    /// Cores classified the packet as hostile.
+    Attack,
+    /// Stable; `decoy` tells whether to answer with a decoy status.
+    Pass,
+}
+
+/// Outcome of one pipeline pass, with the signals that produced it.
+#[derive(Clone, Copy, Debug)]
+pub struct Verdict {
+    pub action: Action,
+    pub threat_p: f64,
+    /// Attack threshold `threat_p` was compared with.
+    pub threshold: f64,
+    pub impact_energy: f64,
+    pub entropy_input: f64,
+    pub resonance: f64,
+    pub decoy: f64,
+    /// Arrival rate / burstiness the verdict was made with.
+    pub arrival: Arrival,
+    /// Inter-arrival rhythm anomaly in [0, 1] (src/jitter.rs).
+    pub jitter_score: f64,
+    /// Beacon frequency and periodicity score (src/spectrum.rs).
+    pub dominant_hz: f64,
+    pub periodicity: f64,
+    /// Equilibrium zones outside their bounds (bit i = zone i of
+    /// `Brain::zones`; the full report is `zones.report()`).
+    pub outside_zones: u64,
+}
+
+/// Brain: the cognitive cores of one protected entity
+/// --------------------------------------------------
+/// Holds AtomicCore, LagrangeEquilibrium, LumisCore and SimulUnit together
+/// with the defense mass they trade. StringState is passed in, so several
+/// brains (e.g. one per source) can share one salt schedule.
+/// `timing` holds the arrival buckets of whatever the brain watches; for
+/// a per-source brain these are the source's own.
+pub struct Brain {
+    pub lumis: LumisCore,
+    pub atomic: AtomicCore,
+    pub simul: SimulUnit,
+    pub lagrange: LagrangeEquilibrium,
+    /// Per-feature equilibrium zones of the normalized vector.
+    pub zones: MultiLagrange,
+    pub timing: TimeBuckets,
+    /// Adaptive attack threshold, fed by every full pipeline pass.
+    pub threshold: ThresholdController,
+    pub defense_mass: f64,
+}
+
+impl Brain {
+    pub fn new() -> Self {
+        Self::with_lumis_clock(LumisClock::Ticks)
+    }
+
+    /// Brain whose Lumis life cycle runs on `clock` (src/lumis.rs)
+    /// instead of one tick per packet.
+    pub fn with_lumis_clock(clock: LumisClock) -> Self {
+        let defense_mass = 1000.0;
+        Self {
+            lumis: LumisCore::with_clock(clock),
+            atomic: AtomicCore::new(100),
+            simul: SimulUnit::new(),
+            lagrange: LagrangeEquilibrium::new(defense_mass),
+            zones: MultiLagrange::per_feature(),
+            timing: TimeBuckets::new(),
+            threshold: ThresholdController::default(),
+            defense_mass,
+        }
+    }
+
+    /// Runs one feature vector through the pipeline, without timing.
+    pub fn process(&mut self, attrs_vec: &[i64], state: &mut StringState) -> Verdict {
+        self.process_with_arrival(attrs_vec, Arrival::default(), state)
+    }
+
+    /// Runs one packet seen at `ts_ns` (CLOCK_MONOTONIC, e.g. a kernel
+    /// sample's `ts_ns`) through the brain's own time buckets and the pipeline.
+    /// An injected Lumis clock reads the same timestamp.
+    pub fn process_at(&mut self, attrs_vec: &[i64], ts_ns: u64, state: &mut StringState) -> Verdict {
+        self.lumis.set_now(ts_ns);
+        let arrival = self.timing.record(ts_ns);
+        self.process_with_arrival(attrs_vec, arrival, state)
+    }
+
+    /// Digital twin in `mode` (src/simul.rs); the scalar sandbox by default.
+    pub fn with_simul_mode(mut self, mode: SimulMode) -> Self {
+        self.simul = self.simul.with_mode(mode);
+        self
+    }
+
+    /// Monte Carlo futures of the current cores under the observed
+    /// traffic, on demand and in either mode; `None` until the twin has
+    /// seen enough packets.
+    pub fn project(&mut self) -> Option<Projection> {
+        self.simul
+            .project(&self.atomic, &self.lagrange, &self.lumis, self.defense_mass, &self.threshold)
+    }
+
+    /// Feedback from whoever acted on an alert: it was benign. Raises the
+    /// attack threshold within its bounds.
+    pub fn report_false_positive(&mut self) {
+        self.threshold.report_false_positive();
+    }
+
+    /// Idle timer: advances Lumis when no packets arrive, so decay and
+    /// rest follow time rather than traffic.
+    pub fn idle(&mut self) {
+        self.lumis.idle();
+    }
+
+    /// Runs one feature vector with arrival features measured elsewhere
+    /// (a brain shared by several sources keeps them per source).
+    pub fn process_with_arrival(&mut self, attrs_vec: &[i64], arrival: Arrival, state: &mut StringState) -> Verdict {
+        self.run(attrs_vec, arrival, state, true)
+    }
+
+    /// Same as `process_with_arrival` for vectors built from packet headers
+    /// or window aggregates instead of a payload: they are scored against
+    /// the learned normalization but never train it, so they cannot skew
+    /// the statistics the payload path (and its snapshots) relies on.
+    pub fn process_synthetic(&mut self, attrs_vec: &[i64], arrival: Arrival, state: &mut StringState) -> Verdict {
+        self.run(attrs_vec, arrival, state, false)
+    }
+
+    fn run(&mut self, attrs_vec: &[i64], arrival: Arrival, state: &mut StringState, learn: bool) -> Verdict {
+        // 1. Feature vector normalization: every dimension becomes a
+        //    Q32.32 fraction (src/normalize.rs); the schema slots drive
+        //    energy, extra dimensions only enter the compact fold
+        let mut attrs = [0i64; ATTR_COUNT];
+        for (i, &v) in attrs_vec.iter().take(ATTR_COUNT).enumerate() {
+            attrs[i] = v;
+        }
+        let features = if learn { state.normalize(attrs_vec) } else { state.score(attrs_vec) };
+        let unit: Vec<f64> = features.iter().map(|&f| StringState::to_float(f)).collect();
+
+        // 2. Impact energy (physical): payload mass plus arrival pressure
+        let payload_mass = unit.iter().take(ATTR_COUNT).sum::<f64>() / ATTR_COUNT as f64;
+        let arrival_pressure = arrival.pressure();
+        let impact_energy =
+            (payload_mass * PAYLOAD_IMPACT_GAIN + arrival_pressure * ARRIVAL_IMPACT_GAIN).clamp(0.0, 10.0);
+
+        // 3. Entropy estimation (informational): mean byte value, a ratio
+        //    of raw slots, so it is independent of the normalization
+        let entropy_input = (attrs[3].abs() as f64 / (attrs[1].max(1) as f64)).clamp(0.0, 10.0);
+
+        let mut verdict = Verdict {
+            action: Action::Pass,
+            threat_p: 0.0,
+            threshold: self.threshold.threshold(),
+            impact_energy,
+            entropy_input,
+            resonance: 0.0,
+            decoy: 0.0,
+            arrival,
+            jitter_score: arrival.hrv.score(),
+            dominant_hz: arrival.periodicity.dominant_hz,
+            periodicity: arrival.periodicity.score,
+            // Per-dimension zones name what left its usual range,
+            // preempted packets included
+            outside_zones: self.zones.stabilize(&unit).outside_mask(),
+        };
+
+        // 4. Digital Twin pre-filter: the scalar sandbox, or Monte Carlo
+        //    futures of cloned cores every `period` packets
+        let preempt = match self.simul.mode() {
+            SimulMode::Scalar => self.simul.project_impact(impact_energy),
+            SimulMode::MonteCarlo => {
+                self.simul
+                    .forecast(&self.atomic, &self.lagrange, &self.lumis, self.defense_mass, &self.threshold)
+            }
+        };
+        if preempt {
+            verdict.action = Action::Preempt;
+            return verdict;
+        }
+
+        // 5. Atomic core processing
+        self.atomic.sharpen_angles(impact_energy);
+        let _drift = self.atomic.find_the_middle(entropy_input);
+        let threat_p = self.atomic.threat_probability(entropy_input);
+        state.salts_mut().report_pressure(threat_p);
+
+        // 6. Lagrange stabilization (the whole normalized vector,
+        //    dimensions beyond the schema included)
+        let compact = state.compactify_slice(&features);
+        let compact_f = StringState::to_float(compact);
+        let equilibrium = self.lagrange.stabilize(compact_f, impact_energy);
+
+        let resonance = (1.0 - (equilibrium.unwrap_or(PHI) - PHI).abs() / PHI).clamp(0.0, 1.0);
+
+        // 7. Lumis life-cycle update
+        self.lumis.set_arrival_pressure(arrival_pressure);
+        self.lumis.tick_cycle(impact_energy, resonance, &mut self.defense_mass);
+        self.defense_mass = self.defense_mass.clamp(100.0, 10_000.0);
+        self.lagrange.update_mass(self.defense_mass);
+        // The twin draws its futures from what the cores actually saw
+        self.simul.observe(Observation {
+            impact: impact_energy,
+            entropy: entropy_input,
+            compact: compact_f,
+            arrival_pressure,
+            rate_pps: arrival.rate_pps[SECOND],
+        });
+
+        // 8. Adaptive response logic
+        verdict.threat_p = threat_p;
+        verdict.resonance = resonance;
+        if threat_p > verdict.threshold || equilibrium.is_none() {
+            verdict.action = Action::Attack;
+        } else {
+            verdict.decoy = self.simul.get_decoy_state() * resonance;
+        }
+        self.threshold.update(verdict.action == Action::Attack, &self.lumis);
+
+        verdict
+    }
+}
+
+impl Default for Brain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
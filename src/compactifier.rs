@@ -0,0 +1,216 @@
+// src/compactifier.rs
+
+//! Compactifiers: interchangeable folds of an attribute vector
+//! -----------------------------------------------------------
+//! Every compactifier maps an attribute vector of any length (the
+//! schema's `Attrs`, or longer ones from new extractors) and the epoch's
+//! salts to a Q32.32 fraction in [0, 1), so Lagrange, Lumis and the
+//! verdict do not care which one is in use:
+//!
+//! | kind      | keyed by              | construction                          |
+//! |-----------|-----------------------|---------------------------------------|
+//! | `sine`    | 64-bit nonce          | sine Taylor fold, keyed index weights |
+//! | `siphash` | 128 bits of epoch key | SipHash-2-4 over the attribute bytes  |
+//! | `blake3`  | 256-bit epoch key     | BLAKE3 keyed hash                     |
+//! | `poly`    | 128 bits of epoch key | polynomial hash mod 2^61 − 1 + mask   |
+//!
+//! The sine fold is the default. Its per-index weights and offsets come
+//! from the nonce (`StringState::index_key`), but each term is still a
+//! smooth function of `a + nonce`, so nearby inputs give nearby outputs
+//! and the nonce is only 8 bytes (see src/audit.rs). The keyed kinds
+//! draw on the epoch key from SaltManager (HKDF block 2), so they rotate
+//! with the same epochs and stay fleet-consistent under a shared master
+//! key.
+//! The hashed bytes are the little-endian words in order; the hashes
+//! count the length, so `[a]` and `[a, 0]` fold apart.
+
+use crate::salt::EpochSalts;
+use crate::string_state::StringState;
+use siphasher::sip::SipHasher24;
+use std::hash::Hasher;
+use std::io;
+
+/// Environment variable selecting the compactifier (main.rs, loader default).
+pub const COMPACTIFIER_ENV: &str = "TIGER_COMPACTIFIER";
+
+/// Mersenne prime 2^61 − 1 of the polynomial hash.
+pub const POLY_PRIME: u64 = (1 << 61) - 1;
+
+/// Folds one attribute vector into a Q32.32 fraction in [0, 1).
+pub trait Compactifier: Send + Sync {
+    fn kind(&self) -> CompactifierKind;
+
+    fn compactify(&self, attributes: &[i64], salts: &EpochSalts) -> i64;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompactifierKind {
+    #[default]
+    Sine,
+    SipHash,
+    Blake3,
+    Poly,
+}
+
+impl CompactifierKind {
+    pub const ALL: [CompactifierKind; 4] = [
+        CompactifierKind::Sine,
+        CompactifierKind::SipHash,
+        CompactifierKind::Blake3,
+        CompactifierKind::Poly,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "sine" | "sin" => Some(CompactifierKind::Sine),
+            "siphash" | "sip" => Some(CompactifierKind::SipHash),
+            "blake3" => Some(CompactifierKind::Blake3),
+            "poly" | "polynomial" => Some(CompactifierKind::Poly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CompactifierKind::Sine => "sine",
+            CompactifierKind::SipHash => "siphash",
+            CompactifierKind::Blake3 => "blake3",
+            CompactifierKind::Poly => "poly",
+        }
+    }
+
+    pub fn build(self) -> Box<dyn Compactifier> {
+        match self {
+            CompactifierKind::Sine => Box::new(SineFold),
+            CompactifierKind::SipHash => Box::new(SipFold),
+            CompactifierKind::Blake3 => Box::new(Blake3Fold),
+            CompactifierKind::Poly => Box::new(PolyFold),
+        }
+    }
+
+    /// `TIGER_COMPACTIFIER` when set, otherwise the sine fold.
+    pub fn from_env() -> io::Result<Self> {
+        match std::env::var(COMPACTIFIER_ENV) {
+            Ok(value) => Self::parse(&value).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} must be sine, siphash, blake3 or poly, got '{}'", COMPACTIFIER_ENV, value),
+                )
+            }),
+            Err(_) => Ok(CompactifierKind::Sine),
+        }
+    }
+}
+
+/// Top 32 bits of a 64-bit hash as a Q32.32 fraction in [0, 1).
+fn fraction(hash: u64) -> i64 {
+    (hash >> 32) as i64
+}
+
+/// The StringState sine fold, keyed by the 64-bit nonce.
+pub struct SineFold;
+
+impl Compactifier for SineFold {
+    fn kind(&self) -> CompactifierKind {
+        CompactifierKind::Sine
+    }
+
+    fn compactify(&self, attributes: &[i64], salts: &EpochSalts) -> i64 {
+        StringState::sine_fold(attributes, salts.nonce)
+    }
+}
+
+/// SipHash-2-4 keyed by the first 128 bits of the epoch key.
+pub struct SipFold;
+
+impl Compactifier for SipFold {
+    fn kind(&self) -> CompactifierKind {
+        CompactifierKind::SipHash
+    }
+
+    fn compactify(&self, attributes: &[i64], salts: &EpochSalts) -> i64 {
+        let key: [u8; 16] = salts.key[..16].try_into().unwrap();
+        let mut hasher = SipHasher24::new_with_key(&key);
+        for a in attributes {
+            hasher.write(&a.to_le_bytes());
+        }
+        fraction(hasher.finish())
+    }
+}
+
+/// BLAKE3 in keyed mode under the full epoch key.
+pub struct Blake3Fold;
+
+impl Compactifier for Blake3Fold {
+    fn kind(&self) -> CompactifierKind {
+        CompactifierKind::Blake3
+    }
+
+    fn compactify(&self, attributes: &[i64], salts: &EpochSalts) -> i64 {
+        let mut hasher = blake3::Hasher::new_keyed(&salts.key);
+        for a in attributes {
+            hasher.update(&a.to_le_bytes());
+        }
+        fraction(u64::from_le_bytes(hasher.finalize().as_bytes()[..8].try_into().unwrap()))
+    }
+}
+
+/// Carter–Wegman polynomial hash over GF(2^61 − 1)
+/// ------------------------------------------------
+/// The length, then each attribute split into two 32-bit limbs (so the
+/// message is injective in the field), is evaluated as a polynomial at
+/// the key point `r` by Horner's rule and masked with `s`. Two distinct
+/// vectors of length ≤ n collide with probability at most `(2n + 1) / p`
+/// over the key. It is universal, not a PRF, so it fits a per-epoch key
+/// and not adversaries who see many outputs of one epoch.
+pub struct PolyFold;
+
+fn mul_mod(a: u64, b: u64) -> u64 {
+    let x = a as u128 * b as u128;
+    let folded = (x as u64 & POLY_PRIME) + (x >> 61) as u64;
+    reduce(folded)
+}
+
+fn reduce(x: u64) -> u64 {
+    let x = (x & POLY_PRIME) + (x >> 61);
+    if x >= POLY_PRIME {
+        x - POLY_PRIME
+    } else {
+        x
+    }
+}
+
+impl PolyFold {
+    /// Evaluation point and mask from the epoch key.
+    pub fn key_pair(salts: &EpochSalts) -> (u64, u64) {
+        let word = |i: usize| u64::from_le_bytes(salts.key[16 + i * 8..24 + i * 8].try_into().unwrap());
+        // r = 0 would hash everything to the mask
+        (reduce(word(0)).max(1), reduce(word(1)))
+    }
+
+    /// Raw polynomial value in [0, p).
+    pub fn evaluate(attributes: &[i64], r: u64) -> u64 {
+        // Leading length limb: without it [0, a] and [a] would collide
+        let mut h = reduce(attributes.len() as u64);
+        for &a in attributes {
+            let a = a as u64;
+            for limb in [a >> 32, a & 0xFFFF_FFFF] {
+                h = reduce(mul_mod(h, r) + limb);
+            }
+        }
+        h
+    }
+}
+
+impl Compactifier for PolyFold {
+    fn kind(&self) -> CompactifierKind {
+        CompactifierKind::Poly
+    }
+
+    fn compactify(&self, attributes: &[i64], salts: &EpochSalts) -> i64 {
+        let (r, s) = Self::key_pair(salts);
+        let h = reduce(Self::evaluate(attributes, r) + s);
+        // p < 2^61: the top 32 of 61 bits
+        (h >> 29) as i64
+    }
+}
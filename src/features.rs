@@ -0,0 +1,27 @@
+// src/features.rs
+
+use crate::schema::{attr, Attrs, ATTR_COUNT};
+
+/// Payload features: the attribute vector of the UDP interceptor
+/// --------------------------------------------------------------
+/// Shared by main.rs (UDP 8888) and the AF_XDP inspector in tiger_loader,
+/// so a packet pulled out of XDP is judged on exactly the same features
+/// as one received on the socket.
+///
+/// Slots, widths and normalization are defined in src/schema.rs
+/// (`ATTR_SLOTS`); this only fills them.
+pub fn payload_attrs(src_port: u16, src_ip_text_len: usize, data: &[u8]) -> Attrs {
+    let len = data.len() as i64;
+    let weight: i64 = data.iter().map(|&b| b as i64).sum();
+
+    let mut a = [0i64; ATTR_COUNT];
+    a[attr::SRC_PORT] = src_port as i64;
+    a[attr::LENGTH] = len;
+    a[attr::FIRST_BYTE] = data.first().copied().unwrap_or(0) as i64;
+    a[attr::BYTE_WEIGHT] = weight;
+    a[attr::WEIGHT_MOD_111] = weight % 111;
+    a[attr::LENGTH_MOD_7] = len % 7;
+    a[attr::HEAD_WEIGHT] = data.iter().take(5).map(|&x| x as i64).sum();
+    a[attr::SRC_IP_TEXT_LEN] = src_ip_text_len as i64;
+    a
+}
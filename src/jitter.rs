@@ -0,0 +1,210 @@
+// src/jitter.rs
+
+//! Inter-arrival jitter: heart-rate variability for packets
+//! --------------------------------------------------------
+//! A source's inter-arrival intervals are treated like NN intervals of a
+//! heartbeat, and scored with the classic time-domain HRV metrics:
+//!
+//! * **SDNN** — standard deviation of the intervals;
+//! * **RMSSD** — root mean square of successive interval differences;
+//! * **pNNx** — share of successive differences larger than
+//!   `PNN_FRACTION` of the mean interval. HRV's pNN50 is 50 ms on a
+//!   ~800 ms beat, i.e. 6.25 %; the same ratio is used here, so the
+//!   metric is scale-free across slow and fast senders.
+//!
+//! Humans and ordinary clients arrive roughly Poisson-like (coefficient
+//! of variation SDNN / mean ≈ 1). Scripted senders are either unnaturally
+//! **regular** (timers, C2 heartbeats: CV → 0, pNNx → 0) or unnaturally
+//! **chaotic** (on/off floods: CV ≫ 1). `HrvMetrics::score` maps both
+//! ends to [0, 1]; the middle scores 0.
+//!
+//! Intervals must come from consecutive packets of one source. Random
+//! 1-in-N sampling (daemon mode) smears a fixed period into a geometric
+//! mixture, so regularity is only visible at a sample rate of 1.
+
+use std::collections::VecDeque;
+
+/// Intervals kept per source.
+pub const JITTER_WINDOW: usize = 64;
+
+/// Intervals needed before a rhythm is classified.
+pub const MIN_INTERVALS: usize = 16;
+
+/// pNNx threshold as a fraction of the mean interval (50 ms / 800 ms).
+pub const PNN_FRACTION: f64 = 0.0625;
+
+/// CV at or below which a sender counts as regular.
+pub const REGULAR_CV: f64 = 0.1;
+
+/// CV at or above which a sender counts as chaotic.
+pub const CHAOTIC_CV: f64 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rhythm {
+    /// Too few intervals yet.
+    Unknown,
+    Natural,
+    /// Machine-paced: timers, beacons.
+    Regular,
+    /// Bursty on/off, far beyond Poisson.
+    Chaotic,
+}
+
+impl Rhythm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Rhythm::Unknown => "unknown",
+            Rhythm::Natural => "natural",
+            Rhythm::Regular => "regular",
+            Rhythm::Chaotic => "chaotic",
+        }
+    }
+}
+
+/// Time-domain HRV metrics over the current window (all times in ns).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HrvMetrics {
+    pub intervals: usize,
+    pub mean_ns: f64,
+    pub sdnn_ns: f64,
+    pub rmssd_ns: f64,
+    /// pNNx in [0, 1].
+    pub pnn: f64,
+}
+
+impl HrvMetrics {
+    /// Coefficient of variation SDNN / mean.
+    pub fn cv(&self) -> f64 {
+        if self.mean_ns > 0.0 {
+            self.sdnn_ns / self.mean_ns
+        } else {
+            0.0
+        }
+    }
+
+    pub fn rhythm(&self) -> Rhythm {
+        if self.intervals < MIN_INTERVALS {
+            return Rhythm::Unknown;
+        }
+        let cv = self.cv();
+        if cv <= REGULAR_CV && self.pnn < 0.05 {
+            Rhythm::Regular
+        } else if cv >= CHAOTIC_CV {
+            Rhythm::Chaotic
+        } else {
+            Rhythm::Natural
+        }
+    }
+
+    /// Anomaly score in [0, 1]: rises linearly as CV falls from
+    /// `2.5 × REGULAR_CV` to 0, or climbs from 1.5 to `1.5 × CHAOTIC_CV`.
+    /// 0 while the rhythm is unknown.
+    pub fn score(&self) -> f64 {
+        if self.intervals < MIN_INTERVALS {
+            return 0.0;
+        }
+        let cv = self.cv();
+        let edge = 2.5 * REGULAR_CV;
+        let regular = ((edge - cv) / edge).clamp(0.0, 1.0);
+        let chaotic = ((cv - 1.5) / (1.5 * CHAOTIC_CV - 1.5)).clamp(0.0, 1.0);
+        regular.max(chaotic)
+    }
+
+    /// Dashboard fragment, times in milliseconds.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"rhythm\":\"{}\",\"score\":{:.3},\"intervals\":{},\"mean_ms\":{:.3},\"sdnn_ms\":{:.3},\"rmssd_ms\":{:.3},\"pnn\":{:.3},\"cv\":{:.3}}}",
+            self.rhythm().as_str(),
+            self.score(),
+            self.intervals,
+            self.mean_ns / 1e6,
+            self.sdnn_ns / 1e6,
+            self.rmssd_ns / 1e6,
+            self.pnn,
+            self.cv()
+        )
+    }
+}
+
+/// Sliding window of inter-arrival intervals of one source.
+#[derive(Clone, Debug)]
+pub struct JitterTracker {
+    intervals: VecDeque<u64>,
+    last_ns: Option<u64>,
+    window: usize,
+}
+
+impl JitterTracker {
+    pub fn new() -> Self {
+        Self::with_window(JITTER_WINDOW)
+    }
+
+    pub fn with_window(window: usize) -> Self {
+        Self {
+            intervals: VecDeque::with_capacity(window.max(2)),
+            last_ns: None,
+            window: window.max(2),
+        }
+    }
+
+    /// Adds the arrival at `ts_ns`. Reordered timestamps are ignored;
+    /// equal ones count as a zero interval.
+    pub fn observe(&mut self, ts_ns: u64) {
+        match self.last_ns {
+            Some(last) if ts_ns < last => return,
+            Some(last) => {
+                if self.intervals.len() == self.window {
+                    self.intervals.pop_front();
+                }
+                self.intervals.push_back(ts_ns - last);
+            }
+            None => {}
+        }
+        self.last_ns = Some(ts_ns);
+    }
+
+    pub fn metrics(&self) -> HrvMetrics {
+        let n = self.intervals.len();
+        if n == 0 {
+            return HrvMetrics::default();
+        }
+        let mean = self.intervals.iter().map(|&x| x as f64).sum::<f64>() / n as f64;
+        let sdnn = (self
+            .intervals
+            .iter()
+            .map(|&x| (x as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n as f64)
+            .sqrt();
+
+        let diffs: Vec<f64> = self
+            .intervals
+            .iter()
+            .zip(self.intervals.iter().skip(1))
+            .map(|(&a, &b)| b as f64 - a as f64)
+            .collect();
+        let (rmssd, pnn) = if diffs.is_empty() {
+            (0.0, 0.0)
+        } else {
+            let m = diffs.len() as f64;
+            let rmssd = (diffs.iter().map(|d| d * d).sum::<f64>() / m).sqrt();
+            let limit = PNN_FRACTION * mean;
+            let pnn = diffs.iter().filter(|d| d.abs() > limit).count() as f64 / m;
+            (rmssd, pnn)
+        };
+
+        HrvMetrics {
+            intervals: n,
+            mean_ns: mean,
+            sdnn_ns: sdnn,
+            rmssd_ns: rmssd,
+            pnn,
+        }
+    }
+}
+
+impl Default for JitterTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
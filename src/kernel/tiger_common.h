@@ -0,0 +1,159 @@
+/*
+ * TigerΔ: shared kernel definitions
+ * --------------------------------------------------
+ * Packet parsing, salts and the folding manifold, common to the XDP
+ * ingress filter (tiger_delta_xdp.c) and the TC egress observer
+ * (tiger_delta_tc.c), so both directions land in the same manifold.
+ */
+
+#ifndef TIGER_COMMON_H
+#define TIGER_COMMON_H
+
+#include <linux/bpf.h>
+#include <linux/if_ether.h>
+#include <linux/ip.h>
+#include <linux/ipv6.h>
+#include <linux/in.h>
+#include <linux/tcp.h>
+#include <linux/udp.h>
+#include <bpf/bpf_helpers.h>
+#include <bpf/bpf_endian.h>
+
+/* struct pkt_meta, FEATURE_COUNT, KF_* and tiger_pack_features():
+ * generated from src/schema.rs, checked by tests/schema.rs */
+#include "tiger_schema.h"
+
+/* Shared salts for manifold rotation. Pinned by name: every object
+ * loaded with the same pin directory reuses one map, so ingress and
+ * egress always fold under the salts the loader last published. */
+struct {
+    __uint(type, BPF_MAP_TYPE_ARRAY);
+    __uint(max_entries, 2);
+    __type(key, __u32);
+    __type(value, __u64);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} config_map SEC(".maps");
+
+#define DEFAULT_PHI_SALT 0x6A09E667F3BCC909ULL
+#define DEFAULT_PI_SALT  0x243F6A8885A308D3ULL
+
+static __always_inline __u64 rotl64(__u64 x, __u32 r) {
+    return (x << r) | (x >> (64 - r));
+}
+
+/* 802.1Q / 802.1ad tag; not exported by the uapi headers */
+struct vlan_hdr {
+    __be16 h_vlan_TCI;
+    __be16 h_vlan_encapsulated_proto;
+};
+
+#define VLAN_MAX_DEPTH 2   /* single tag or QinQ */
+
+static __always_inline __u32 fold_in6(const struct in6_addr *a) {
+    return a->in6_u.u6_addr32[0] ^ a->in6_u.u6_addr32[1] ^
+           a->in6_u.u6_addr32[2] ^ a->in6_u.u6_addr32[3];
+}
+
+/* TCP/UDP ports and TCP flags; other protocols leave them at zero */
+static __always_inline void parse_l4(void *l4, void *data_end, struct pkt_meta *m) {
+    if (m->protocol == IPPROTO_TCP) {
+        struct tcphdr *th = l4;
+        if ((void *)(th + 1) > data_end)
+            return;
+        m->sport = th->source;
+        m->dport = th->dest;
+        m->tcp_flags = ((__u8 *)th)[13]; /* CWR..FIN */
+    } else if (m->protocol == IPPROTO_UDP) {
+        struct udphdr *uh = l4;
+        if ((void *)(uh + 1) > data_end)
+            return;
+        m->sport = uh->source;
+        m->dport = uh->dest;
+    }
+}
+
+enum parse_result {
+    PARSE_OK = 0,
+    PARSE_TRUNCATED,   /* headers cut short: pass unfolded */
+    PARSE_NON_IP,
+};
+
+/* Ethernet → VLAN (up to QinQ) → IPv4/IPv6 → TCP/UDP.
+ * On PARSE_OK `*l3` points at the bounds-checked IP header. */
+static __always_inline int parse_packet(void *data, void *data_end,
+                                        struct pkt_meta *m, void **l3) {
+    if (data + sizeof(struct ethhdr) > data_end)
+        return PARSE_TRUNCATED;
+
+    struct ethhdr *eth = data;
+    void *cursor = data + sizeof(struct ethhdr);
+    __u16 proto = eth->h_proto;
+
+    /* VLAN tags: the innermost id is kept */
+    #pragma unroll
+    for (int i = 0; i < VLAN_MAX_DEPTH; i++) {
+        if (proto != __constant_htons(ETH_P_8021Q) &&
+            proto != __constant_htons(ETH_P_8021AD))
+            break;
+        struct vlan_hdr *vh = cursor;
+        if ((void *)(vh + 1) > data_end)
+            return PARSE_TRUNCATED;
+        m->vlan = bpf_ntohs(vh->h_vlan_TCI) & 0x0FFF;
+        proto = vh->h_vlan_encapsulated_proto;
+        cursor = vh + 1;
+    }
+
+    if (proto == __constant_htons(ETH_P_IP)) {
+        struct iphdr *ip = cursor;
+        if ((void *)(ip + 1) > data_end)
+            return PARSE_TRUNCATED;
+
+        m->saddr = ip->saddr;
+        m->daddr = ip->daddr;
+        m->len = ip->tot_len;
+        m->protocol = ip->protocol;
+
+        /* L4 only for the first fragment with a sane header length */
+        if (ip->ihl >= 5 && !(ip->frag_off & __constant_htons(0x1FFF)))
+            parse_l4(cursor + ip->ihl * 4, data_end, m);
+        *l3 = ip;
+        return PARSE_OK;
+    }
+
+    if (proto == __constant_htons(ETH_P_IPV6)) {
+        struct ipv6hdr *ip6 = cursor;
+        if ((void *)(ip6 + 1) > data_end)
+            return PARSE_TRUNCATED;
+
+        m->saddr = fold_in6(&ip6->saddr);
+        m->daddr = fold_in6(&ip6->daddr);
+        m->len = ip6->payload_len;
+        m->protocol = ip6->nexthdr;
+        m->is_v6 = 1;
+
+        /* Extension headers are not walked: L4 only when it follows directly */
+        parse_l4(ip6 + 1, data_end, m);
+        *l3 = ip6;
+        return PARSE_OK;
+    }
+
+    return PARSE_NON_IP;
+}
+
+/* Folding manifold over the current salts */
+static __always_inline __u64 fold_features(const __u64 *v) {
+    __u32 k_phi = 0, k_pi = 1;
+    __u64 *phi_s = bpf_map_lookup_elem(&config_map, &k_phi);
+    __u64 *pi_s  = bpf_map_lookup_elem(&config_map, &k_pi);
+    __u64 phi = phi_s ? *phi_s : DEFAULT_PHI_SALT;
+    __u64 pi  = pi_s  ? *pi_s  : DEFAULT_PI_SALT;
+
+    __u64 acc = 0;
+    #pragma unroll
+    for (int i = 0; i < FEATURE_COUNT; i++) {
+        acc = (acc + rotl64(v[i] ^ pi, 13 + i)) * phi;
+    }
+    return acc;
+}
+
+#endif /* TIGER_COMMON_H */
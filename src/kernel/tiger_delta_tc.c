@@ -0,0 +1,97 @@
+/*
+ * TigerΔ: Egress Resonance Observer — v1.0 "Ulenspiegel"
+ * --------------------------------------------------
+ * TC (clsact egress) program: outbound packets are folded through the
+ * same manifold and salts as ingress, with state kept per destination,
+ * so a compromised host beaconing or exfiltrating shows up in the same
+ * resonance framework. Observation only: every packet is let through.
+ */
+
+#include "tiger_common.h"
+#include <linux/pkt_cls.h>
+
+char LICENSE[] SEC("license") = "GPL";
+
+/* Per-destination state, keyed by pkt_meta.daddr.
+ * Layout mirrored by `EgressDst` in src/bin/tiger_loader/egress.rs.
+ * Fields are updated without locks: counters use atomics, the rest are
+ * last-writer-wins approximations, which is all the consumer needs. */
+struct egress_dst {
+    __u64 resonance;    /* EMA of the fold, same update as resonance_state */
+    __u64 packets;
+    __u64 bytes;
+    __u64 first_ns;
+    __u64 last_ns;
+    __u64 gap_ewma_ns;  /* EWMA (1/8) of the inter-packet gap */
+    __u16 dport;        /* last seen, raw network order */
+    __u8  protocol;
+    __u8  is_v6;
+    __u32 pad;
+};
+
+struct {
+    __uint(type, BPF_MAP_TYPE_LRU_HASH);
+    __uint(max_entries, 16384);
+    __type(key, __u32);
+    __type(value, struct egress_dst);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} egress_state SEC(".maps");
+
+static __always_inline void observe(struct __sk_buff *skb) {
+    void *data = (void *)(long)skb->data;
+    void *data_end = (void *)(long)skb->data_end;
+    struct pkt_meta m = {};
+    void *l3 = 0;
+
+    if (parse_packet(data, data_end, &m, &l3) != PARSE_OK)
+        return;
+
+    /* Same layout as ingress with the roles of the addresses swapped:
+     * the destination is the subject, the egress ifindex replaces the
+     * receive queue */
+    __u64 now = bpf_ktime_get_ns();
+    __u64 f[KERNEL_FIELD_COUNT] = {
+        [KF_SADDR] = m.daddr,         [KF_DADDR] = m.saddr,
+        [KF_IS_V6] = m.is_v6,         [KF_PROTOCOL] = m.protocol,
+        [KF_LEN] = m.len,             [KF_VLAN] = m.vlan,
+        [KF_QUEUE] = skb->ifindex,
+        [KF_TIME_BUCKET] = now >> TIME_BUCKET_SHIFT,
+        [KF_SPORT] = m.dport,         [KF_DPORT] = m.sport,
+        [KF_TCP_FLAGS] = m.tcp_flags,
+    };
+    __u64 v[FEATURE_COUNT];
+    tiger_pack_features(v, f);
+
+    __u64 acc = fold_features(v);
+
+    struct egress_dst *d = bpf_map_lookup_elem(&egress_state, &m.daddr);
+    if (!d) {
+        struct egress_dst fresh = {
+            .resonance = acc,
+            .packets = 1,
+            .bytes = skb->len,
+            .first_ns = now,
+            .last_ns = now,
+            .dport = m.dport,
+            .protocol = m.protocol,
+            .is_v6 = m.is_v6,
+        };
+        bpf_map_update_elem(&egress_state, &m.daddr, &fresh, BPF_NOEXIST);
+        return;
+    }
+
+    __u64 gap = now > d->last_ns ? now - d->last_ns : 0;
+    d->gap_ewma_ns = d->gap_ewma_ns ? d->gap_ewma_ns - (d->gap_ewma_ns >> 3) + (gap >> 3) : gap;
+    d->last_ns = now;
+    d->resonance = (d->resonance + acc) >> 1;
+    d->dport = m.dport;
+    d->protocol = m.protocol;
+    __sync_fetch_and_add(&d->packets, 1);
+    __sync_fetch_and_add(&d->bytes, skb->len);
+}
+
+SEC("tc")
+int tiger_delta_egress(struct __sk_buff *skb) {
+    observe(skb);
+    return TC_ACT_OK;
+}
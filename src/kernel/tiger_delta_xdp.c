@@ -4,20 +4,14 @@
  * Per-CPU high-performance XDP filter for entropy-based defense.
  */
 
-#include <linux/bpf.h>
-#include <linux/if_ether.h>
-#include <linux/ip.h>
-#include <bpf/bpf_helpers.h>
+#include "tiger_common.h"
 
 char LICENSE[] SEC("license") = "GPL";
 
-/* Shared salts for manifold rotation */
-struct {
-    __uint(type, BPF_MAP_TYPE_ARRAY);
-    __uint(max_entries, 2);
-    __type(key, __u32);
-    __type(value, __u64);
-} config_map SEC(".maps");
+/* All maps are pinned by name under the loader's bpffs directory, so a
+ * restarted or upgraded loader picks up salts, resonance history and
+ * counters instead of starting from zero. `config_map` (salts) lives in
+ * tiger_common.h and is shared with the egress program through its pin. */
 
 /* Per-CPU state for zero-contention scaling */
 struct {
@@ -25,68 +19,390 @@ struct {
     __uint(max_entries, 1);
     __type(key, __u32);
     __type(value, __u64);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
 } resonance_state SEC(".maps");
 
-/* Active Shield: 0=Monitor, 1=Drop */
+/* Active Shield, graduated.
+ * [POLICY_MODE]        0=Monitor, 1=Enforce
+ * [POLICY_DROP_PROB]   drop probability as a u32 fraction of 2^32,
+ *                      DROP_ALWAYS drops unconditionally.
+ * [POLICY_SAMPLE_RATE] 1-in-N packets copied to `samples`, 0=off.
+ * The loader ramps the probability with the threat level, so mitigation
+ * grows smoothly instead of flipping between pass-all and drop-all. */
+enum tiger_policy {
+    POLICY_MODE = 0,
+    POLICY_DROP_PROB,
+    POLICY_SAMPLE_RATE,
+    POLICY_MAX,
+};
+
+#define POLICY_ENFORCE 1
+#define DROP_ALWAYS    0xFFFFFFFFU
+
 struct {
     __uint(type, BPF_MAP_TYPE_ARRAY);
-    __uint(max_entries, 1);
+    __uint(max_entries, POLICY_MAX);
     __type(key, __u32);
     __type(value, __u32);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
 } policy_map SEC(".maps");
 
-static __always_inline __u64 rotl64(__u64 x, __u32 r) {
-    return (x << r) | (x >> (64 - r));
+/* Per-source drop probability (same encoding), keyed by pkt_meta.saddr.
+ * An entry overrides the global probability for that source. */
+struct {
+    __uint(type, BPF_MAP_TYPE_LRU_HASH);
+    __uint(max_entries, 65536);
+    __type(key, __u32);
+    __type(value, __u32);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} source_policy SEC(".maps");
+
+/* Packet/verdict counters, summed and rated by the loader.
+ * Index layout is mirrored in src/bin/tiger_loader/stats.rs. */
+enum tiger_stat {
+    STAT_TOTAL = 0,
+    STAT_NON_IP,
+    STAT_PASSED,
+    STAT_DROPPED,
+    STAT_ALLOW_HIT,
+    STAT_DENY_HIT,
+    STAT_REDIRECTED,
+    STAT_MAX,
+};
+
+struct {
+    __uint(type, BPF_MAP_TYPE_PERCPU_ARRAY);
+    __uint(max_entries, STAT_MAX);
+    __type(key, __u32);
+    __type(value, __u64);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} stats_map SEC(".maps");
+
+static __always_inline void count(__u32 stat) {
+    __u64 *c = bpf_map_lookup_elem(&stats_map, &stat);
+    if (c)
+        *c += 1; /* per-CPU slot: no atomics needed */
 }
 
-SEC("xdp")
-int tiger_delta_xdp(struct xdp_md *ctx) {
-    void *data = (void *)(long)ctx->data;
-    void *data_end = (void *)(long)ctx->data_end;
+/* CIDR lists: LPM keys carry the prefix length followed by the address
+ * in network byte order. Values are per-entry hit counters. */
+struct lpm_v4_key {
+    __u32 prefixlen;
+    __u32 addr;
+};
+
+struct lpm_v6_key {
+    __u32 prefixlen;
+    __u8  addr[16];
+};
+
+#define CIDR_MAX_ENTRIES 4096
+
+/* Allow: management networks, never dropped by the manifold */
+struct {
+    __uint(type, BPF_MAP_TYPE_LPM_TRIE);
+    __uint(max_entries, CIDR_MAX_ENTRIES);
+    __uint(map_flags, BPF_F_NO_PREALLOC);
+    __type(key, struct lpm_v4_key);
+    __type(value, __u64);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} allow_v4 SEC(".maps");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_LPM_TRIE);
+    __uint(max_entries, CIDR_MAX_ENTRIES);
+    __uint(map_flags, BPF_F_NO_PREALLOC);
+    __type(key, struct lpm_v6_key);
+    __type(value, __u64);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} allow_v6 SEC(".maps");
+
+/* Deny: known-bad ranges, dropped before folding */
+struct {
+    __uint(type, BPF_MAP_TYPE_LPM_TRIE);
+    __uint(max_entries, CIDR_MAX_ENTRIES);
+    __uint(map_flags, BPF_F_NO_PREALLOC);
+    __type(key, struct lpm_v4_key);
+    __type(value, __u64);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} deny_v4 SEC(".maps");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_LPM_TRIE);
+    __uint(max_entries, CIDR_MAX_ENTRIES);
+    __uint(map_flags, BPF_F_NO_PREALLOC);
+    __type(key, struct lpm_v6_key);
+    __type(value, __u64);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} deny_v6 SEC(".maps");
+
+/* Returns XDP_PASS / XDP_DROP on a list hit, -1 when no list matched.
+ * Allow is checked first so a management prefix nested inside a denied
+ * range stays reachable. */
+static __always_inline int cidr_verdict_v4(__u32 saddr) {
+    struct lpm_v4_key key = { .prefixlen = 32, .addr = saddr };
 
-    if (data + sizeof(struct ethhdr) > data_end)
+    __u64 *hits = bpf_map_lookup_elem(&allow_v4, &key);
+    if (hits) {
+        __sync_fetch_and_add(hits, 1);
+        count(STAT_ALLOW_HIT);
         return XDP_PASS;
+    }
+    hits = bpf_map_lookup_elem(&deny_v4, &key);
+    if (hits) {
+        __sync_fetch_and_add(hits, 1);
+        count(STAT_DENY_HIT);
+        return XDP_DROP;
+    }
+    return -1;
+}
+
+static __always_inline int cidr_verdict_v6(const struct in6_addr *saddr) {
+    struct lpm_v6_key key = { .prefixlen = 128 };
+    __builtin_memcpy(key.addr, saddr, sizeof(key.addr));
 
-    struct ethhdr *eth = data;
-    if (eth->h_proto != __constant_htons(ETH_P_IP))
+    __u64 *hits = bpf_map_lookup_elem(&allow_v6, &key);
+    if (hits) {
+        __sync_fetch_and_add(hits, 1);
+        count(STAT_ALLOW_HIT);
         return XDP_PASS;
+    }
+    hits = bpf_map_lookup_elem(&deny_v6, &key);
+    if (hits) {
+        __sync_fetch_and_add(hits, 1);
+        count(STAT_DENY_HIT);
+        return XDP_DROP;
+    }
+    return -1;
+}
+
+/* Bernoulli trial against the kernel PRNG */
+static __always_inline int policy_verdict(__u32 saddr) {
+    __u32 k_mode = POLICY_MODE;
+    __u32 *mode = bpf_map_lookup_elem(&policy_map, &k_mode);
+    if (!mode || *mode != POLICY_ENFORCE)
+        return XDP_PASS;
+
+    __u32 *prob = bpf_map_lookup_elem(&source_policy, &saddr);
+    if (!prob) {
+        __u32 k_prob = POLICY_DROP_PROB;
+        prob = bpf_map_lookup_elem(&policy_map, &k_prob);
+    }
+    if (!prob || *prob == 0)
+        return XDP_PASS;
+
+    if (*prob == DROP_ALWAYS || bpf_get_prandom_u32() < *prob)
+        return XDP_DROP;
+    return XDP_PASS;
+}
+
+/* AF_XDP inspection: sources the loader is unsure about get a packet
+ * budget in `inspect_sources`; while it lasts, their packets go to the
+ * XSK socket bound to the receiving queue, where the full payload runs
+ * through the userspace feature extractors. Redirected packets are
+ * consumed by the inspector, so the budget bounds what the source loses.
+ * Not pinned: the sockets die with the loader. */
+struct {
+    __uint(type, BPF_MAP_TYPE_XSKMAP);
+    __uint(max_entries, 64);
+    __type(key, __u32);
+    __type(value, __u32);
+} xsks SEC(".maps");
 
-    struct iphdr *ip = data + sizeof(struct ethhdr);
-    if ((void *)(ip + 1) > data_end)
+struct {
+    __uint(type, BPF_MAP_TYPE_LRU_HASH);
+    __uint(max_entries, 4096);
+    __type(key, __u32);
+    __type(value, __u32);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} inspect_sources SEC(".maps");
+
+/* Attempts to take one unit of budget against other CPUs; on losing
+ * every race the packet simply takes the normal path. */
+#define INSPECT_CAS_TRIES 4
+
+static __always_inline int maybe_inspect(__u32 saddr, __u32 rx_queue) {
+    __u32 *budget = bpf_map_lookup_elem(&inspect_sources, &saddr);
+    if (!budget)
         return XDP_PASS;
 
-    /* Dynamic salt lookup */
-    __u32 k_phi = 0, k_pi = 1;
-    __u64 *phi_s = bpf_map_lookup_elem(&config_map, &k_phi);
-    __u64 *pi_s  = bpf_map_lookup_elem(&config_map, &k_pi);
-    __u64 phi = phi_s ? *phi_s : 0x6A09E667F3BCC909ULL;
-    __u64 pi  = pi_s  ? *pi_s  : 0x243F6A8885A308D3ULL;
-
-    /* Feature vector with time-quantization */
-    __u64 v[4];
-    v[0] = ((__u64)ip->saddr << 32) | ip->daddr;
-    v[1] = ((__u64)ip->protocol << 48) | ip->tot_len;
-    v[2] = (__u64)ctx->rx_queue_index;
-    v[3] = bpf_ktime_get_ns() >> 22; // ~4ms buckets
-
-    /* Folding manifold */
-    __u64 acc = 0;
+    /* Decrement only from a value seen to be > 0: a separate check and
+     * fetch-and-sub lets several CPUs pass at 1 and wrap the u32 into an
+     * unlimited budget. */
+    __u32 left = *budget;
+    #pragma unroll
+    for (int i = 0; i < INSPECT_CAS_TRIES; i++) {
+        if (left == 0)
+            return XDP_PASS;
+        __u32 seen = __sync_val_compare_and_swap(budget, left, left - 1);
+        if (seen == left)
+            /* No socket on this queue: fall back to XDP_PASS */
+            return bpf_redirect_map(&xsks, rx_queue, XDP_PASS);
+        left = seen;
+    }
+    return XDP_PASS;
+}
+
+/* Packet samples for the userspace cognitive layer (daemon mode).
+ * Layout mirrored by `RawSample` in src/schema.rs.
+ * Not pinned: samples only mean something to the live consumer. */
+struct tiger_sample {
+    __u64 ts_ns;
+    __u64 fold;
+    struct pkt_meta meta;
+    __u32 rx_queue;
+};
+
+_Static_assert(sizeof(struct tiger_sample) == TIGER_SAMPLE_SIZE, "tiger_sample size");
+
+struct {
+    __uint(type, BPF_MAP_TYPE_RINGBUF);
+    __uint(max_entries, 1 << 20);
+} samples SEC(".maps");
+
+static __always_inline void maybe_sample(const struct pkt_meta *m, __u64 fold,
+                                         __u64 ts_ns, __u32 rx_queue) {
+    __u32 k_rate = POLICY_SAMPLE_RATE;
+    __u32 *rate = bpf_map_lookup_elem(&policy_map, &k_rate);
+    if (!rate || *rate == 0)
+        return;
+    if (*rate > 1 && bpf_get_prandom_u32() % *rate)
+        return;
+
+    struct tiger_sample s = {
+        .ts_ns = ts_ns,
+        .fold = fold,
+        .meta = *m,
+        .rx_queue = rx_queue,
+    };
+    /* Full ring: the sample is lost, the packet is not */
+    bpf_ringbuf_output(&samples, &s, sizeof(s), 0);
+}
+
+/* Count-min sketch of packets per source, one per CPU (no atomics).
+ * Row i hashes pkt_meta.saddr with ((a_i * x + b_i) >> (64 - log2 w));
+ * seeds and bounds are mirrored and documented in src/sketch.rs. The
+ * loader sums the CPUs and diffs reads into windows, so cells only grow
+ * (u32 wrap-around is handled there). */
+#define CMS_DEPTH      4
+#define CMS_LOG2_WIDTH 11
+#define CMS_WIDTH      (1 << CMS_LOG2_WIDTH)
+#define CMS_CANDIDATE_MIN 64
+
+struct cms_row {
+    __u32 cells[CMS_WIDTH];
+};
+
+struct {
+    __uint(type, BPF_MAP_TYPE_PERCPU_ARRAY);
+    __uint(max_entries, CMS_DEPTH);
+    __type(key, __u32);
+    __type(value, struct cms_row);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} cms_rows SEC(".maps");
+
+/* Sources whose per-CPU estimate crossed a power of two >= CMS_CANDIDATE_MIN;
+ * the sketch cannot enumerate keys, so top-K is drawn from these */
+struct {
+    __uint(type, BPF_MAP_TYPE_LRU_HASH);
+    __uint(max_entries, 8192);
+    __type(key, __u32);
+    __type(value, __u32);
+    __uint(pinning, LIBBPF_PIN_BY_NAME);
+} cms_candidates SEC(".maps");
+
+static __always_inline __u64 cms_seed_a(__u32 row) {
+    return row == 0 ? 0x9E3779B97F4A7C15ULL : row == 1 ? 0x94D049BB133111EBULL :
+           row == 2 ? 0xD6E8FEB86659FD93ULL : 0xE7037ED1A0B428DBULL;
+}
+
+static __always_inline __u64 cms_seed_b(__u32 row) {
+    return row == 0 ? 0xBF58476D1CE4E5B9ULL : row == 1 ? 0x2545F4914F6CDD1DULL :
+           row == 2 ? 0xA0761D6478BD642FULL : 0x8EBC6AF09C88C6E3ULL;
+}
+
+static __always_inline void cms_update(__u32 saddr) {
+    __u32 est = 0xFFFFFFFFU;
+
     #pragma unroll
-    for (int i = 0; i < 4; i++) {
-        acc = (acc + rotl64(v[i] ^ pi, 13 + i)) * phi;
+    for (__u32 row = 0; row < CMS_DEPTH; row++) {
+        struct cms_row *r = bpf_map_lookup_elem(&cms_rows, &row);
+        if (!r)
+            return;
+        __u32 idx = ((cms_seed_a(row) * saddr + cms_seed_b(row)) >> (64 - CMS_LOG2_WIDTH))
+                    & (CMS_WIDTH - 1);
+        __u32 c = ++r->cells[idx];
+        if (c < est)
+            est = c;
     }
 
+    /* Power-of-two marks keep candidate writes logarithmic in the count */
+    if (est >= CMS_CANDIDATE_MIN && !(est & (est - 1)))
+        bpf_map_update_elem(&cms_candidates, &saddr, &est, BPF_ANY);
+}
+
+static __always_inline int tiger_delta_verdict(struct xdp_md *ctx) {
+    void *data = (void *)(long)ctx->data;
+    void *data_end = (void *)(long)ctx->data_end;
+    struct pkt_meta m = {};
+    void *l3 = 0;
+
+    int parsed = parse_packet(data, data_end, &m, &l3);
+    if (parsed == PARSE_NON_IP) {
+        count(STAT_NON_IP);
+        return XDP_PASS;
+    }
+    if (parsed != PARSE_OK)
+        return XDP_PASS;
+
+    /* Static lists take precedence over the folding manifold */
+    int verdict = m.is_v6 ? cidr_verdict_v6(&((struct ipv6hdr *)l3)->saddr)
+                          : cidr_verdict_v4(((struct iphdr *)l3)->saddr);
+    if (verdict >= 0)
+        return verdict;
+
+    cms_update(m.saddr);
+
+    /* Feature vector with time-quantization (layout: src/schema.rs) */
+    __u64 now = bpf_ktime_get_ns();
+    __u64 f[KERNEL_FIELD_COUNT] = {
+        [KF_SADDR] = m.saddr,         [KF_DADDR] = m.daddr,
+        [KF_IS_V6] = m.is_v6,         [KF_PROTOCOL] = m.protocol,
+        [KF_LEN] = m.len,             [KF_VLAN] = m.vlan,
+        [KF_QUEUE] = ctx->rx_queue_index,
+        [KF_TIME_BUCKET] = now >> TIME_BUCKET_SHIFT,
+        [KF_SPORT] = m.sport,         [KF_DPORT] = m.dport,
+        [KF_TCP_FLAGS] = m.tcp_flags,
+    };
+    __u64 v[FEATURE_COUNT];
+    tiger_pack_features(v, f);
+
+    __u64 acc = fold_features(v);
+
     /* Lock-less Per-CPU update */
     __u32 key = 0;
     __u64 *state = bpf_map_lookup_elem(&resonance_state, &key);
-    if (state) {
+    if (state)
         *state = (*state + acc) >> 1;
-        
-        /* Decision logic */
-        __u32 *mode = bpf_map_lookup_elem(&policy_map, &key);
-        if (mode && *mode == 1 && *state > 0x8000000000000000ULL)
-            return XDP_DROP;
-    }
 
-    return XDP_PASS;
+    maybe_sample(&m, acc, now, ctx->rx_queue_index);
+
+    /* Decision logic: the threat level lives in the loader-published
+     * probability, the per-CPU state only feeds the loader */
+    verdict = policy_verdict(m.saddr);
+    if (verdict == XDP_PASS)
+        verdict = maybe_inspect(m.saddr, ctx->rx_queue_index);
+    return verdict;
+}
+
+SEC("xdp")
+int tiger_delta_xdp(struct xdp_md *ctx) {
+    count(STAT_TOTAL);
+
+    int verdict = tiger_delta_verdict(ctx);
+    if (verdict == XDP_REDIRECT)
+        count(STAT_REDIRECTED);
+    else
+        count(verdict == XDP_DROP ? STAT_DROPPED : STAT_PASSED);
+    return verdict;
 }
@@ -0,0 +1,68 @@
+/*
+ * TigerΔ: feature schema — GENERATED from src/schema.rs, do not edit.
+ * Regenerate with `TIGER_BLESS=1 cargo test --test schema`.
+ */
+
+#ifndef TIGER_SCHEMA_H
+#define TIGER_SCHEMA_H
+
+/* Parsed packet metadata. Addresses, lengths and ports keep the raw
+ * network-order loads; the userspace emulator (src/xdp_emulator.rs)
+ * reproduces the same values. */
+struct pkt_meta {
+    __u32 saddr;       /* IPv4 address, or IPv6 address xor-folded to 32 bit */
+    __u32 daddr;
+    __u16 len;         /* tot_len (v4) / payload_len (v6) */
+    __u8  protocol;    /* protocol (v4) / nexthdr (v6) */
+    __u8  is_v6;
+    __u16 vlan;        /* innermost VLAN id, 0 when untagged */
+    __u16 sport;
+    __u16 dport;
+    __u8  tcp_flags;
+};
+
+_Static_assert(__builtin_offsetof(struct pkt_meta, saddr) == 0, "pkt_meta.saddr moved");
+_Static_assert(__builtin_offsetof(struct pkt_meta, daddr) == 4, "pkt_meta.daddr moved");
+_Static_assert(__builtin_offsetof(struct pkt_meta, len) == 8, "pkt_meta.len moved");
+_Static_assert(__builtin_offsetof(struct pkt_meta, protocol) == 10, "pkt_meta.protocol moved");
+_Static_assert(__builtin_offsetof(struct pkt_meta, is_v6) == 11, "pkt_meta.is_v6 moved");
+_Static_assert(__builtin_offsetof(struct pkt_meta, vlan) == 12, "pkt_meta.vlan moved");
+_Static_assert(__builtin_offsetof(struct pkt_meta, sport) == 14, "pkt_meta.sport moved");
+_Static_assert(__builtin_offsetof(struct pkt_meta, dport) == 16, "pkt_meta.dport moved");
+_Static_assert(__builtin_offsetof(struct pkt_meta, tcp_flags) == 18, "pkt_meta.tcp_flags moved");
+_Static_assert(sizeof(struct pkt_meta) == 20, "pkt_meta size");
+
+#define TIGER_SAMPLE_SIZE 40
+
+#define FEATURE_COUNT       5
+#define TIME_BUCKET_SHIFT   22
+#define KERNEL_FIELD_COUNT  11
+
+#define KF_SADDR        0
+#define KF_DADDR        1
+#define KF_IS_V6        2
+#define KF_PROTOCOL     3
+#define KF_LEN          4
+#define KF_VLAN         5
+#define KF_QUEUE        6
+#define KF_TIME_BUCKET  7
+#define KF_SPORT        8
+#define KF_DPORT        9
+#define KF_TCP_FLAGS    10
+
+/* Kernel feature vector: f[] in KF_* order, packed into v[] */
+static __always_inline void tiger_pack_features(__u64 *v, const __u64 *f) {
+    v[0] = ((f[KF_SADDR] & 0xFFFFFFFFULL) << 32)
+         | ((f[KF_DADDR] & 0xFFFFFFFFULL) << 0);
+    v[1] = ((f[KF_IS_V6] & 0xFFULL) << 56)
+         | ((f[KF_PROTOCOL] & 0xFFULL) << 48)
+         | ((f[KF_LEN] & 0xFFFFULL) << 0);
+    v[2] = ((f[KF_VLAN] & 0xFFFFULL) << 32)
+         | ((f[KF_QUEUE] & 0xFFFFFFFFULL) << 0);
+    v[3] = ((f[KF_TIME_BUCKET] & 0xFFFFFFFFFFFFFFFFULL) << 0);
+    v[4] = ((f[KF_SPORT] & 0xFFFFULL) << 48)
+         | ((f[KF_DPORT] & 0xFFFFULL) << 32)
+         | ((f[KF_TCP_FLAGS] & 0xFFULL) << 0);
+}
+
+#endif /* TIGER_SCHEMA_H */
@@ -1,9 +1,11 @@
 // src/lagrange.rs
 
 use crate::lumis::{PHI, PHI_INVERSE};
+use crate::schema::{used_slots, ATTR_SLOTS};
 
 /// LagrangeEquilibrium реалізує нелінійну гравітаційну пастку 
 /// для стабілізації енергетичних сплесків.
+#[derive(Clone, Debug)]
 pub struct LagrangeEquilibrium {
     defense_mass: f64,
     in_zone: bool,
@@ -71,3 +73,241 @@ impl LagrangeEquilibrium {
         }
     }
 }
+
+/// Зона за замовчуванням: вихід за 4 σ від центру, повернення в межах 2 σ.
+pub const ZONE_EXIT_SIGMA: f64 = 4.0;
+pub const ZONE_ENTER_SIGMA: f64 = 2.0;
+
+/// Вага одного зразка в EWMA центру і розкиду зони.
+pub const ZONE_ALPHA: f64 = 1.0 / 128.0;
+
+/// Зразків, за які зона вчиться, перш ніж щось може з неї вийти.
+pub const ZONE_WARMUP: u64 = 64;
+
+/// Нижня межа розкиду: константна ознака не робить кожен шум виходом.
+pub const MIN_ZONE_SCALE: f64 = 0.01;
+
+/// Одна зона рівноваги: ознака або група ознак (середнє нормованих значень).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZoneSpec {
+    pub name: String,
+    pub dims: Vec<usize>,
+    /// Повернення в зону, коли відхилення (в σ) не більше `enter`.
+    pub enter: f64,
+    /// Вихід із зони, коли відхилення більше `exit` (`exit >= enter`).
+    pub exit: f64,
+}
+
+impl ZoneSpec {
+    pub fn new(name: &str, dims: Vec<usize>) -> Self {
+        Self {
+            name: name.to_string(),
+            dims,
+            enter: ZONE_ENTER_SIGMA,
+            exit: ZONE_EXIT_SIGMA,
+        }
+    }
+
+    /// Власні межі гістерезису зони.
+    pub fn with_bounds(mut self, enter: f64, exit: f64) -> Self {
+        self.enter = enter.min(exit);
+        self.exit = exit.max(enter);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoneTransition {
+    Left,
+    Returned,
+}
+
+impl ZoneTransition {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ZoneTransition::Left => "left",
+            ZoneTransition::Returned => "returned",
+        }
+    }
+}
+
+/// Стан однієї зони в звіті.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZoneDeviation {
+    pub name: String,
+    pub value: f64,
+    pub center: f64,
+    /// |value − center| у одиницях розкиду зони.
+    pub sigma: f64,
+    pub in_zone: bool,
+    /// Перехід, що стався на цьому зразку.
+    pub transition: Option<ZoneTransition>,
+}
+
+/// Структурований звіт про відхилення: яка зона, наскільки, і чи
+/// перетнула вона межу саме зараз.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviationReport {
+    pub zones: Vec<ZoneDeviation>,
+}
+
+impl DeviationReport {
+    /// Усі зони в рівновазі.
+    pub fn is_stable(&self) -> bool {
+        self.zones.iter().all(|z| z.in_zone)
+    }
+
+    /// Зони поза рівновагою.
+    pub fn outside(&self) -> impl Iterator<Item = &ZoneDeviation> {
+        self.zones.iter().filter(|z| !z.in_zone)
+    }
+
+    /// Бітова маска зон поза рівновагою (біт i — i-та зона, до 64).
+    pub fn outside_mask(&self) -> u64 {
+        self.zones
+            .iter()
+            .take(64)
+            .enumerate()
+            .filter(|(_, z)| !z.in_zone)
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    }
+
+    /// Найдальша від центру зона.
+    pub fn worst(&self) -> Option<&ZoneDeviation> {
+        self.zones.iter().max_by(|a, b| a.sigma.total_cmp(&b.sigma))
+    }
+
+    /// Імена зон поза рівновагою через кому (для журналу).
+    pub fn outside_names(&self) -> String {
+        self.outside().map(|z| z.name.as_str()).collect::<Vec<_>>().join(",")
+    }
+
+    pub fn to_json(&self) -> String {
+        let zones: Vec<String> = self
+            .zones
+            .iter()
+            .map(|z| {
+                format!(
+                    "{{\"name\":\"{}\",\"value\":{:.4},\"center\":{:.4},\"sigma\":{:.2},\"in_zone\":{},\"transition\":{}}}",
+                    z.name,
+                    z.value,
+                    z.center,
+                    z.sigma,
+                    z.in_zone,
+                    z.transition.map_or("null".to_string(), |t| format!("\"{}\"", t.as_str()))
+                )
+            })
+            .collect();
+        format!("{{\"stable\":{},\"zones\":[{}]}}", self.is_stable(), zones.join(","))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ZoneState {
+    spec: ZoneSpec,
+    center: f64,
+    scale: f64,
+    seen: u64,
+    in_zone: bool,
+}
+
+/// MultiLagrange: зона рівноваги на кожен вимір (або групу вимірів)
+/// -----------------------------------------------------------------
+/// На відміну від скалярної пастки, яка бачить лише згорнуте `l`, кожна
+/// зона стежить за своїм центром (EWMA) і розкидом (EWMA |відхилення|)
+/// нормованих ознак (src/normalize.rs, [0, 1)) і має власний гістерезис:
+/// виходить за `exit` σ, повертається в межах `enter` σ. Центр і розкид
+/// вчаться лише в зоні, тож тривала атака не перетягує рівновагу на себе.
+#[derive(Clone, Debug)]
+pub struct MultiLagrange {
+    zones: Vec<ZoneState>,
+    report: DeviationReport,
+}
+
+impl MultiLagrange {
+    pub fn new(specs: Vec<ZoneSpec>) -> Self {
+        let zones = specs
+            .into_iter()
+            .map(|spec| ZoneState {
+                spec,
+                center: 0.0,
+                scale: MIN_ZONE_SCALE,
+                seen: 0,
+                in_zone: true,
+            })
+            .collect();
+        Self {
+            zones,
+            report: DeviationReport::default(),
+        }
+    }
+
+    /// Одна зона на кожен заповнений слот схеми, з іменем слота.
+    pub fn per_feature() -> Self {
+        Self::new(
+            used_slots().map(|i| ZoneSpec::new(ATTR_SLOTS[i].name, vec![i])).collect(),
+        )
+    }
+
+    pub fn specs(&self) -> impl Iterator<Item = &ZoneSpec> {
+        self.zones.iter().map(|z| &z.spec)
+    }
+
+    /// Останній звіт.
+    pub fn report(&self) -> &DeviationReport {
+        &self.report
+    }
+
+    /// Оцінює нормований вектор (значення в [0, 1]); виміри, яких немає
+    /// у векторі, вважаються нулями.
+    pub fn stabilize(&mut self, features: &[f64]) -> &DeviationReport {
+        self.report.zones.clear();
+        for zone in self.zones.iter_mut() {
+            let dims = &zone.spec.dims;
+            let value = if dims.is_empty() {
+                0.0
+            } else {
+                dims.iter().map(|&d| features.get(d).copied().unwrap_or(0.0)).sum::<f64>() / dims.len() as f64
+            };
+
+            if zone.seen == 0 {
+                zone.center = value;
+            }
+            let sigma = (value - zone.center).abs() / zone.scale.max(MIN_ZONE_SCALE);
+
+            let was_in = zone.in_zone;
+            if zone.seen >= ZONE_WARMUP {
+                // Гістерезис: вийти важче, ніж залишитися
+                zone.in_zone = if was_in { sigma <= zone.spec.exit } else { sigma <= zone.spec.enter };
+            }
+            let transition = match (was_in, zone.in_zone) {
+                (true, false) => Some(ZoneTransition::Left),
+                (false, true) => Some(ZoneTransition::Returned),
+                _ => None,
+            };
+
+            if zone.in_zone {
+                let delta = value - zone.center;
+                zone.center += ZONE_ALPHA * delta;
+                zone.scale += ZONE_ALPHA * (delta.abs() - zone.scale);
+            }
+            zone.seen += 1;
+
+            self.report.zones.push(ZoneDeviation {
+                name: zone.spec.name.clone(),
+                value,
+                center: zone.center,
+                sigma,
+                in_zone: zone.in_zone,
+                transition,
+            });
+        }
+        &self.report
+    }
+}
+
+impl Default for MultiLagrange {
+    fn default() -> Self {
+        Self::per_feature()
+    }
+}
@@ -0,0 +1,26 @@
+// =================================================================
+// Project: TigerΔ (Tiger Delta)
+// Module: lib.rs
+// Description: Shared cores for the async nerve center (main.rs),
+//              the XDP loader and the red-team / audit binaries.
+// =================================================================
+
+pub mod atomic_core;
+pub mod audit;
+pub mod brain;
+pub mod compactifier;
+pub mod features;
+pub mod fixed;
+pub mod jitter;
+pub mod lagrange;
+pub mod lumis;
+pub mod normalize;
+pub mod salt;
+pub mod schema;
+pub mod simul;
+pub mod sketch;
+pub mod spectrum;
+pub mod string_state;
+pub mod threshold;
+pub mod timing;
+pub mod xdp_emulator;
@@ -1,59 +1,221 @@
 // src/lumis.rs
 
 use std::f64::consts::PI;
+use std::time::Duration;
 use log::info; // Використовуємо стандартний фасад логування
 
-pub const PHI: f64 = 1.6180339887498948;
+use crate::timing::monotonic_ns;
+
+pub const PHI: f64 = 1.618_033_988_749_895;
 pub const PHI_INVERSE: f64 = 0.6180339887498948;
 
+/// Номінальна частота тіків: стільки tick_cycle за секунду відповідає
+/// одному виклику на тік у тіковому режимі.
+pub const NOMINAL_TICK_HZ: f64 = 100.0;
+
+/// Тихих тіків до переходу в сон (5 с при номінальній частоті).
+pub const REST_AFTER_TICKS: f64 = 500.0;
+
+/// Загасання ентропії у сні та природне охолодження — за один тік;
+/// за секунду це `^ tick_hz`.
+pub const REST_DECAY_PER_TICK: f64 = 0.90;
+pub const COOLING_PER_TICK: f64 = 0.99;
+/// Критичний скид понад 0.8 — теж за тік, щоб у часовому режимі
+/// швидкість скиду не залежала від частоти пакетів.
+pub const CRITICAL_DECAY_PER_TICK: f64 = 0.5;
+
+/// Період напіврозпаду тиску надходження, поки пакетів немає (idle).
+pub const IDLE_PRESSURE_HALF_LIFE_S: f64 = 1.0;
+
+/// Чим вимірюється час життєвого циклу.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LumisClock {
+    /// Один тік на виклик tick_cycle: поведінка залежить від частоти пакетів.
+    #[default]
+    Ticks,
+    /// CLOCK_MONOTONIC (той самий годинник, що й у src/timing.rs).
+    Monotonic,
+    /// Час задає викликач через `set_now` (тести, відтворення записів).
+    Injected,
+}
+
+/// Розклад у часовому режимі: скільки номінальних тіків у секунді і
+/// скільки тиші до сну.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LumisSchedule {
+    pub tick_hz: f64,
+    pub rest_after: Duration,
+}
+
+impl Default for LumisSchedule {
+    fn default() -> Self {
+        Self {
+            tick_hz: NOMINAL_TICK_HZ,
+            rest_after: Duration::from_secs_f64(REST_AFTER_TICKS / NOMINAL_TICK_HZ),
+        }
+    }
+}
+
 /// LumisCore — адаптивне ядро захисту, натхненне біологічними циклами.
 /// Керує ентропією системи та енергетичним балансом.
+///
+/// Шум, коливання порогу, охолодження і сон залежать від фази — часу в
+/// номінальних тіках. У тіковому режимі кожен виклик додає рівно один
+/// тік (історична поведінка); у часовому фаза росте на `dt · tick_hz`,
+/// тож сон настає через `rest_after` секунд тиші за будь-якої частоти
+/// пакетів, а `idle` просуває час, коли пакетів немає зовсім.
+/// Видих і вдих лишаються подіями: їх спричиняє пакет, а не час.
+#[derive(Clone, Debug)]
 pub struct LumisCore {
     entropy: f64,
+    /// Лічильник циклів з пакетами (для журналу EXHALE).
     tick: u64,
-    rest_ticks: u64,
+    /// Час у номінальних тіках.
+    phase: f64,
+    quiet_ticks: f64,
     in_rest: bool,
+    /// Тиск частоти/сплесків надходження пакетів (src/timing.rs), [0, 1]
+    arrival_pressure: f64,
+    clock: LumisClock,
+    schedule: LumisSchedule,
+    now_ns: u64,
+    last_ns: Option<u64>,
+    /// Двійник для проекцій (src/simul.rs): не пише в журнал.
+    muted: bool,
 }
 
 impl LumisCore {
     pub fn new() -> Self {
+        Self::with_clock(LumisClock::Ticks)
+    }
+
+    pub fn with_clock(clock: LumisClock) -> Self {
         Self {
             entropy: 0.0,
             tick: 0,
-            rest_ticks: 0,
+            phase: 0.0,
+            quiet_ticks: 0.0,
             in_rest: false,
+            arrival_pressure: 0.0,
+            clock,
+            schedule: LumisSchedule::default(),
+            now_ns: 0,
+            last_ns: None,
+            muted: false,
+        }
+    }
+
+    pub fn with_schedule(mut self, schedule: LumisSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    pub fn clock(&self) -> LumisClock {
+        self.clock
+    }
+
+    /// Поточний час для `LumisClock::Injected` (нс, монотонний).
+    pub fn set_now(&mut self, now_ns: u64) {
+        self.now_ns = self.now_ns.max(now_ns);
+    }
+
+    /// Копія стану для проекцій у майбутнє (src/simul.rs). Часовий
+    /// годинник двійника стає інжектованим від моменту останнього циклу,
+    /// тож час у проекції рухає лише `advance`; журнал мовчить.
+    pub fn twin(&self) -> Self {
+        let mut twin = self.clone();
+        twin.muted = true;
+        if twin.clock != LumisClock::Ticks {
+            twin.clock = LumisClock::Injected;
+            twin.now_ns = twin.last_ns.unwrap_or(twin.now_ns);
+        }
+        twin
+    }
+
+    /// Просуває інжектований годинник на `dt_ns`.
+    pub fn advance(&mut self, dt_ns: u64) {
+        self.now_ns = self.now_ns.saturating_add(dt_ns);
+    }
+
+    /// Номінальних тіків від попереднього циклу. Перший цикл у часовому
+    /// режимі рахується одним тіком, як у тіковому.
+    fn elapsed_ticks(&mut self) -> f64 {
+        let now = match self.clock {
+            LumisClock::Ticks => return 1.0,
+            LumisClock::Monotonic => monotonic_ns(),
+            LumisClock::Injected => self.now_ns,
+        };
+        let Some(last) = self.last_ns else {
+            self.last_ns = Some(now);
+            return 1.0;
+        };
+        self.last_ns = Some(last.max(now));
+        now.saturating_sub(last) as f64 * self.schedule.tick_hz / 1e9
+    }
+
+    fn rest_after_ticks(&self) -> f64 {
+        match self.clock {
+            LumisClock::Ticks => REST_AFTER_TICKS,
+            _ => self.schedule.rest_after.as_secs_f64() * self.schedule.tick_hz,
         }
     }
 
     /// Основний життєвий цикл: дихання, очищення, відновлення.
     /// tick_cycle викликається на кожну ітерацію обробки пакетів.
     pub fn tick_cycle(&mut self, external_impact: f64, resonance: f64, mass: &mut f64) {
+        let ticks = self.elapsed_ticks();
         self.tick = self.tick.wrapping_add(1);
+        self.phase += ticks;
+
+        // Умова спокою: низький зовнішній вплив, низький резонанс ядра
+        // і відсутність потоку пакетів
+        let quiet = external_impact.abs() < 0.001 && resonance < 0.05 && self.arrival_pressure < 0.05;
+        self.track_quiet(quiet, ticks);
+
+        if self.in_rest {
+            self.rest_mode(ticks);
+        } else {
+            self.active_mode(external_impact, mass);
+            self.dissipate_entropy(ticks);
+        }
+    }
 
-        // Умова спокою: низький зовнішній вплив та низький резонанс ядра
-        let quiet = external_impact.abs() < 0.001 && resonance < 0.05;
+    /// Таймер простою: просуває час без пакета — тиша накопичується,
+    /// тиск надходження згасає, ентропія охолоджується або спить.
+    /// У тіковому режимі це один тихий тік.
+    pub fn idle(&mut self) {
+        let ticks = self.elapsed_ticks();
+        self.phase += ticks;
+        let seconds = ticks / self.schedule.tick_hz;
+        self.arrival_pressure *= 0.5f64.powf(seconds / IDLE_PRESSURE_HALF_LIFE_S);
+        self.track_quiet(self.arrival_pressure < 0.05, ticks);
+
+        if self.in_rest {
+            self.rest_mode(ticks);
+        } else {
+            self.entropy = self.entropy.clamp(0.0, 1.0);
+            self.dissipate_entropy(ticks);
+        }
+    }
 
+    fn track_quiet(&mut self, quiet: bool, ticks: f64) {
         if quiet {
-            self.rest_ticks += 1;
+            self.quiet_ticks += ticks;
         } else {
-            self.rest_ticks = 0;
+            self.quiet_ticks = 0.0;
             self.in_rest = false;
         }
 
         // Автоматичний перехід у режим сну після 500 "тихих" тіків
-        if self.rest_ticks > 500 {
+        // (у часовому режимі — після rest_after секунд тиші)
+        if self.quiet_ticks > self.rest_after_ticks() {
             self.in_rest = true;
         }
-
-        if self.in_rest {
-            self.rest_mode();
-        } else {
-            self.active_mode(external_impact, mass);
-        }
     }
 
     fn active_mode(&mut self, external_impact: f64, mass: &mut f64) {
-        let pressure = external_impact.abs();
+        // Сплеск або висока частота тиснуть навіть при легких пакетах
+        let pressure = external_impact.abs().max(self.arrival_pressure);
 
         // Механізм EXHALE (Видих): сильний тиск змушує систему скидати ентропію
         if pressure > 0.5 {
@@ -67,7 +229,7 @@ impl LumisCore {
                 *mass = 100.0;
             }
 
-            if self.tick % 50 == 0 {
+            if self.tick.is_multiple_of(50) && !self.muted {
                 info!("EXHALE: Purge={:.3}, Mass={:.2}", purge, *mass);
             }
         } else {
@@ -75,17 +237,16 @@ impl LumisCore {
             self.accumulate_entropy(pressure * 0.1);
         }
 
-        // Внутрішній гармонічний шум (тремор системи)
-        let internal_noise = (self.tick as f64 * 0.0001).sin() * 0.01;
+        // Внутрішній гармонічний шум (тремор системи), за фазою часу
+        let internal_noise = (self.phase * 0.0001).sin() * 0.01;
         self.entropy += internal_noise;
 
         self.entropy = self.entropy.clamp(0.0, 1.0);
-        self.dissipate_entropy();
     }
 
-    fn rest_mode(&mut self) {
+    fn rest_mode(&mut self, ticks: f64) {
         // Швидке відновлення в стані спокою
-        self.entropy *= 0.90;
+        self.entropy *= REST_DECAY_PER_TICK.powf(ticks);
         if self.entropy < 0.05 {
             self.entropy = 0.0;
         }
@@ -95,18 +256,27 @@ impl LumisCore {
         self.entropy = (self.entropy + delta).clamp(0.0, 1.0);
     }
 
-    fn dissipate_entropy(&mut self) {
+    fn dissipate_entropy(&mut self, ticks: f64) {
         // Нелінійна дисипація: чим вища ентропія, тим активніше скидання
         if self.entropy > 0.8 {
-            self.entropy *= 0.5; // Критичний скид
+            self.entropy *= CRITICAL_DECAY_PER_TICK.powf(ticks); // Критичний скид
         } else {
-            self.entropy *= 0.99; // Природне охолодження
+            self.entropy *= COOLING_PER_TICK.powf(ticks); // Природне охолодження
         }
     }
 
+    /// Задає тиск надходження для наступних tick_cycle.
+    pub fn set_arrival_pressure(&mut self, pressure: f64) {
+        self.arrival_pressure = pressure.clamp(0.0, 1.0);
+    }
+
+    pub fn arrival_pressure(&self) -> f64 {
+        self.arrival_pressure
+    }
+
     /// Обчислює динамічний поріг детекції на основі ентропії та золотого перетину.
     pub fn dynamic_threshold(&self) -> f64 {
-        let phase = self.tick as f64;
+        let phase = self.phase;
         let oscillation = (phase * PI * PHI_INVERSE).sin();
         let adaptation = oscillation * self.entropy * 0.1;
         PHI + adaptation
@@ -120,3 +290,9 @@ impl LumisCore {
         self.in_rest
     }
 }
+
+impl Default for LumisCore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,22 +5,30 @@
 // Framework: Tokio (Async Runtime) / Tracing (Logging)
 // =================================================================
 
-mod lumis;
-mod string_state;
-mod lagrange;
-mod atomic_core;
-mod simul;
-
-use crate::string_state::{StringState, to_float};
-use crate::lagrange::LagrangeEquilibrium;
-use crate::lumis::{LumisCore, PHI_INVERSE, PHI};
-use crate::atomic_core::AtomicCore;
-use crate::simul::SimulUnit;
+use tiger_delta_ai_safety::brain::{Action, Brain};
+use tiger_delta_ai_safety::lumis::LumisClock;
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::schema::{attr, Attrs};
+use tiger_delta_ai_safety::compactifier::CompactifierKind;
+use tiger_delta_ai_safety::normalize::Normalizer;
+use tiger_delta_ai_safety::string_state::{StringState, STATE_FILE_ENV};
+use tiger_delta_ai_safety::salt::SaltManager;
+use tiger_delta_ai_safety::simul::SimulMode;
+use tiger_delta_ai_safety::spectrum::BEACON_SCORE;
+use tiger_delta_ai_safety::timing::{monotonic_ns, SourceClocks};
 
 use tokio::sync::mpsc;
 use tokio::net::UdpSocket;
 use tracing::{info, warn, error};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the brain thread writes the state snapshot
+const SNAPSHOT_PERIOD: Duration = Duration::from_secs(60);
+
+/// Lumis idle timer: decay and rest keep running between packets
+const IDLE_TICK: Duration = Duration::from_millis(250);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -28,122 +36,130 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
     info!("🐯 TigerΔ v3.3 \"Ulenspiegel\" — Platinum Core Online");
 
-    // Channel between interceptor and cognitive core
-    let (tx, mut rx) = mpsc::channel::<(Vec<i64>, std::net::SocketAddr)>(1024);
+    // Channel between interceptor and cognitive core; the receive time
+    // travels with the features (CLOCK_MONOTONIC, same as the XDP buckets)
+    let (tx, mut rx) = mpsc::channel::<(Attrs, std::net::SocketAddr, u64)>(1024);
 
     // UDP socket
     let socket = Arc::new(UdpSocket::bind("0.0.0.0:8888").await?);
     let socket_responder = socket.clone();
 
+    // Shared master key (TIGER_MASTER_KEY_FILE) keeps the userspace nonce
+    // in step with the XDP salts published by tiger_loader
+    let salts = SaltManager::from_env()?;
+    // Fold of the attribute vector: sine (default), siphash, blake3 or poly
+    let compactifier = CompactifierKind::from_env()?;
+    info!("🧬 Compactifier: {}", compactifier.as_str());
+    // Raw features → [0, 1) fixed-point before the fold (TIGER_NORMALIZE)
+    let normalizer = Normalizer::from_env()?;
+    info!("📐 Normalization: {}", normalizer.spec());
+    // Digital twin: scalar sandbox or Monte Carlo futures (TIGER_SIMUL)
+    let simul_mode = SimulMode::from_env()?;
+    info!("🔮 Digital twin: {}", simul_mode.as_str());
+
+    // Learned normalization statistics survive restarts (TIGER_STATE_FILE)
+    let state_file = std::env::var(STATE_FILE_ENV).ok().map(PathBuf::from);
+    let mut state = StringState::with_salts(salts)
+        .with_compactifier(compactifier.build())
+        .with_normalizer(normalizer);
+    if let Some(path) = &state_file {
+        if state.load_snapshot(path)? {
+            info!("💾 State restored from {}", path.display());
+        }
+    }
+
     // =============================================================
     // BRAIN THREAD
     // =============================================================
     tokio::spawn(async move {
-        let mut lumis = LumisCore::new();
-        let mut atomic = AtomicCore::new(100);
-        let mut simul = SimulUnit::new();
-
-        let mut defense_mass = 1000.0;
-        let mut lagrange = LagrangeEquilibrium::new(defense_mass);
-        let mut state = StringState::new();
-
-        while let Some((attrs_vec, addr)) = rx.recv().await {
-            // -----------------------------------------------------
-            // 1. Feature vector normalization
-            // -----------------------------------------------------
-            let mut attrs = [0i64; 10];
-            for (i, &v) in attrs_vec.iter().take(10).enumerate() {
-                attrs[i] = v;
+        // Lumis runs on the receive timestamps, not on the packet count
+        let mut brain = Brain::with_lumis_clock(LumisClock::Injected).with_simul_mode(simul_mode);
+        let mut last_snapshot = Instant::now();
+        // One brain, but arrival rate and burstiness are per source
+        let mut clocks = SourceClocks::new(65_536);
+        let mut idle = tokio::time::interval(IDLE_TICK);
+
+        loop {
+            let (attrs_vec, addr, ts_ns) = tokio::select! {
+                received = rx.recv() => match received {
+                    Some(packet) => packet,
+                    None => break,
+                },
+                _ = idle.tick() => {
+                    brain.lumis.set_now(monotonic_ns());
+                    brain.idle();
+                    continue;
+                }
+            };
+            // Цей цикл — власник солей: згортка лише читає поточну епоху
+            state.salts_mut().refresh();
+            let arrival = clocks.record(addr.ip(), ts_ns, attrs_vec[attr::LENGTH] as u64);
+            brain.lumis.set_now(ts_ns);
+            let verdict = brain.process_with_arrival(&attrs_vec, arrival, &mut state);
+
+            if let Some(path) = state_file.as_ref().filter(|_| last_snapshot.elapsed() >= SNAPSHOT_PERIOD) {
+                last_snapshot = Instant::now();
+                if let Err(e) = state.save_snapshot(path) {
+                    warn!("State snapshot {} not written: {}", path.display(), e);
+                }
             }
 
             // -----------------------------------------------------
-            // 2. Impact energy (physical)
-            // -----------------------------------------------------
-            let raw_energy: f64 =
-                attrs.iter().map(|&x| x as f64).sum::<f64>() * PHI_INVERSE;
-
-            let impact_energy = (raw_energy / 1_000_000.0).clamp(0.0, 10.0);
-
-            // -----------------------------------------------------
-            // 3. Entropy estimation (informational)
-            // -----------------------------------------------------
-            let entropy_input =
-                (attrs[3].abs() as f64 / (attrs[1].max(1) as f64))
-                .clamp(0.0, 10.0);
-
-            // -----------------------------------------------------
-            // 4. Digital Twin pre-filter
+            // Adaptive response logic
             // -----------------------------------------------------
-            if simul.project_impact(impact_energy) {
-                warn!("⚠️ PREEMPTIVE BLOCK from {}", addr);
+            if verdict.action == Action::Preempt {
+                match brain.simul.last_projection().filter(|_| simul_mode == SimulMode::MonteCarlo) {
+                    Some(projection) => warn!("⚠️ PREEMPTIVE BLOCK from {} | {}", addr, projection.summary()),
+                    None => warn!("⚠️ PREEMPTIVE BLOCK from {}", addr),
+                }
                 let _ = socket_responder
                     .send_to(b"DELTA_SHIELD_PREEMPT", addr)
                     .await;
                 continue;
             }
 
-            // -----------------------------------------------------
-            // 5. Atomic core processing
-            // -----------------------------------------------------
-            atomic.sharpen_angles(impact_energy);
-            let _drift = atomic.find_the_middle(entropy_input);
-            let threat_p = atomic.threat_probability(entropy_input);
-
-            // -----------------------------------------------------
-            // 6. Lagrange stabilization
-            // -----------------------------------------------------
-            let compact = state.compactify(&attrs);
-            let compact_f = to_float(compact);
-            let equilibrium = lagrange.stabilize(compact_f, impact_energy);
-
-            let resonance =
-                (1.0 - (equilibrium.unwrap_or(PHI) - PHI).abs() / PHI)
-                .clamp(0.0, 1.0);
-
-            // -----------------------------------------------------
-            // 7. Lumis life-cycle update
-            // -----------------------------------------------------
-            lumis.tick_cycle(impact_energy, resonance, &mut defense_mass);
-            defense_mass = defense_mass.clamp(100.0, 10_000.0);
-            lagrange.update_mass(defense_mass);
+            if verdict.periodicity >= BEACON_SCORE {
+                warn!(
+                    "📡 BEACON | src={} | every {:.1}s | periodicity={:.2}",
+                    addr,
+                    1.0 / verdict.dominant_hz,
+                    verdict.periodicity
+                );
+            }
 
-            // -----------------------------------------------------
-            // 8. Adaptive response logic
-            // -----------------------------------------------------
-            if atomic.is_critical {
+            if brain.atomic.is_critical {
                 warn!(
                     "🧬 MUTATION ACTIVE | phase={} | scars={:.3}",
-                    atomic.mutation_phase,
-                    atomic.scars_energy
+                    brain.atomic.mutation_phase,
+                    brain.atomic.scars_energy
                 );
             }
 
-            if threat_p > 0.85 || equilibrium.is_none() {
+            if verdict.action == Action::Attack {
                 error!(
-                    "🔥 ATTACK | src={} | p={:.2} | phase={} | scars={:.2}",
+                    "🔥 ATTACK | src={} | p={:.2} > {:.3} | phase={} | scars={:.2} | off-zone=[{}]",
                     addr,
-                    threat_p,
-                    atomic.mutation_phase,
-                    atomic.scars_energy
+                    verdict.threat_p,
+                    verdict.threshold,
+                    brain.atomic.mutation_phase,
+                    brain.atomic.scars_energy,
+                    brain.zones.report().outside_names()
                 );
                 let _ = socket_responder
                     .send_to(b"DELTA_SHIELD_NULL", addr)
                     .await;
-            } else {
-                let decoy = simul.get_decoy_state() * resonance;
-                if decoy > 0.6 {
-                    let msg = format!("STATUS_OK_{:.2}", decoy);
-                    let _ = socket_responder.send_to(msg.as_bytes(), addr).await;
-                }
+            } else if verdict.decoy > 0.6 {
+                let msg = format!("STATUS_OK_{:.2}", verdict.decoy);
+                let _ = socket_responder.send_to(msg.as_bytes(), addr).await;
             }
 
             // -----------------------------------------------------
-            // 9. Rest mode
+            // Rest mode
             // -----------------------------------------------------
-            if lumis.is_resting() {
+            if brain.lumis.is_resting() {
                 info!(
                     "🌙 LUMIS REST MODE | entropy={:.4}",
-                    lumis.entropy_level()
+                    brain.lumis.entropy_level()
                 );
             }
         }
@@ -157,28 +173,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     loop {
         let (len, addr) = socket.recv_from(&mut buf).await?;
+        let ts_ns = monotonic_ns();
 
-        if len < 8 || len > 1024 {
+        if !(8..=1024).contains(&len) {
             continue;
         }
 
         let data = &buf[..len];
-        let weight: i64 = data.iter().map(|&b| b as i64).sum();
-
-        let attrs = vec![
-            addr.port() as i64,
-            len as i64,
-            data[0] as i64,
-            weight,
-            weight % 111,
-            (len as i64) % 7,
-            data.iter().take(5).map(|&x| x as i64).sum(),
-            addr.ip().to_string().len() as i64,
-            0,
-            0,
-        ];
-
-        if tx.try_send((attrs, addr)).is_err() {
+        let attrs = payload_attrs(addr.port(), addr.ip().to_string().len(), data);
+
+        if tx.try_send((attrs, addr, ts_ns)).is_err() {
             error!("QUEUE OVERFLOW | Negative Radius | {}", addr);
         }
     }
@@ -0,0 +1,501 @@
+// src/normalize.rs
+
+//! Feature normalization before compactification
+//! ---------------------------------------------
+//! The cores expect every attribute as a Q32.32 fraction in [0, 1); the
+//! extractors produce raw ports, lengths and byte sums. A `Normalizer`
+//! sits in between with one strategy per feature:
+//!
+//! | strategy   | statistics kept               | output                              |
+//! |------------|-------------------------------|-------------------------------------|
+//! | `schema`   | none (`ATTR_SLOTS` bounds)    | the slot's static `Normalization`   |
+//! | `minmax`   | running min and max           | `(x − min) / (max − min)`           |
+//! | `zscore`   | EWMA mean and variance        | logistic of the z-score (≈ Φ(z))    |
+//! | `log`      | running max (schema seeded)   | `ln(1 + x) / ln(1 + max)`           |
+//! | `quantile` | log-bucket histogram, decayed | empirical CDF of x                  |
+//!
+//! Every adaptive strategy scores a value against what it has seen so far
+//! and only then learns it, so a value that breaks the range scores at the
+//! edge instead of being absorbed first; with nothing seen yet the output
+//! is ½. Running min/max never forgets — one outlier widens the range for
+//! good — which is what the decayed quantile sketch is for.
+//!
+//! Specs select strategies: a default, then per-feature overrides by slot
+//! name or index, e.g. `quantile, src_port=minmax, 12=zscore`. Dimensions
+//! beyond the schema have no static bounds, so `schema` falls back to
+//! `quantile` for them. The learned statistics travel in the state
+//! snapshot (`StringState::snapshot`).
+
+use crate::fixed::{self, ONE};
+use crate::schema::{Normalization, ATTR_SLOTS};
+use std::io;
+
+/// Environment variable with the normalization spec (main.rs, loader default).
+pub const NORMALIZE_ENV: &str = "TIGER_NORMALIZE";
+
+/// EWMA weight of one sample in the z-score statistics (≈ 256-sample memory).
+pub const ZSCORE_ALPHA: f64 = 1.0 / 256.0;
+
+/// Logistic slope that makes σ(k·z) track the normal CDF within 0.01.
+const LOGISTIC_SLOPE: f64 = 1.702;
+
+/// Quantile buckets: 4 per octave over the whole u64 range.
+pub const QUANTILE_BUCKETS: usize = 252;
+
+/// The quantile sketch halves its counts every this many inserts.
+pub const QUANTILE_HALF_LIFE: u32 = 4096;
+
+/// Highest feature index a snapshot may restore (guards the allocation).
+pub const MAX_FEATURES: usize = 256;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strategy {
+    #[default]
+    Schema,
+    MinMax,
+    ZScore,
+    Log,
+    Quantile,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 5] = [
+        Strategy::Schema,
+        Strategy::MinMax,
+        Strategy::ZScore,
+        Strategy::Log,
+        Strategy::Quantile,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "schema" | "static" => Some(Strategy::Schema),
+            "minmax" | "min_max" => Some(Strategy::MinMax),
+            "zscore" | "z" | "ewma" => Some(Strategy::ZScore),
+            "log" => Some(Strategy::Log),
+            "quantile" | "cdf" => Some(Strategy::Quantile),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Strategy::Schema => "schema",
+            Strategy::MinMax => "minmax",
+            Strategy::ZScore => "zscore",
+            Strategy::Log => "log",
+            Strategy::Quantile => "quantile",
+        }
+    }
+}
+
+/// Q32.32 fraction in [0, 1) of a unit-interval value.
+fn fraction(y: f64) -> i64 {
+    fixed::from_f64(y).clamp(0, ONE - 1)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Histogram over 4 log-spaced buckets per octave
+/// ----------------------------------------------
+/// Relative bucket width is at most 25 %, so the CDF is exact between
+/// buckets and linearly interpolated inside one. Counts halve every
+/// `QUANTILE_HALF_LIFE` inserts, so the distribution follows the traffic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantileSketch {
+    counts: Vec<u32>,
+    total: u64,
+    inserts: u32,
+}
+
+impl QuantileSketch {
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; QUANTILE_BUCKETS],
+            total: 0,
+            inserts: 0,
+        }
+    }
+
+    fn bucket(x: u64) -> usize {
+        if x < 4 {
+            return x as usize;
+        }
+        let msb = 63 - x.leading_zeros();
+        let sub = (x >> (msb - 2)) & 3;
+        ((msb - 1) * 4) as usize + sub as usize
+    }
+
+    /// `[lower, lower + width)` of bucket `b`.
+    fn bounds(b: usize) -> (u64, u64) {
+        if b < 4 {
+            return (b as u64, 1);
+        }
+        let msb = (b / 4 + 1) as u32;
+        let width = 1u64 << (msb - 2);
+        ((4 + (b % 4) as u64) * width, width)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Counts may come from a restored snapshot, so they saturate rather
+    /// than overflow; the next halving brings them back into range.
+    pub fn insert(&mut self, x: u64) {
+        let c = &mut self.counts[Self::bucket(x)];
+        *c = c.saturating_add(1);
+        self.total = self.total.saturating_add(1);
+        self.inserts = self.inserts.saturating_add(1);
+        if self.inserts >= QUANTILE_HALF_LIFE {
+            self.inserts = 0;
+            for c in self.counts.iter_mut() {
+                *c >>= 1;
+            }
+            self.total = self.counts.iter().map(|&c| c as u64).sum();
+        }
+    }
+
+    /// Share of the sketch below `x`, in [0, 1]; ½ while empty.
+    pub fn cdf(&self, x: u64) -> f64 {
+        if self.total == 0 {
+            return 0.5;
+        }
+        let b = Self::bucket(x);
+        let below: u64 = self.counts[..b].iter().map(|&c| c as u64).sum();
+        let (lower, width) = Self::bounds(b);
+        let inside = ((x - lower) as f64 + 0.5) / width as f64;
+        (below as f64 + self.counts[b] as f64 * inside) / self.total as f64
+    }
+
+    /// Midpoint of the bucket holding the `q`-quantile; 0 while empty.
+    pub fn quantile(&self, q: f64) -> u64 {
+        let target = q.clamp(0.0, 1.0) * self.total as f64;
+        let mut seen = 0.0;
+        for (b, &c) in self.counts.iter().enumerate() {
+            seen += c as f64;
+            if c > 0 && seen >= target {
+                let (lower, width) = Self::bounds(b);
+                return lower + width / 2;
+            }
+        }
+        0
+    }
+}
+
+impl Default for QuantileSketch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One feature's strategy and the statistics it has learned.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Feature {
+    Schema(Normalization),
+    MinMax { min: f64, max: f64 },
+    ZScore { mean: f64, var: f64, seen: u64 },
+    Log { max: f64 },
+    Quantile(QuantileSketch),
+}
+
+impl Feature {
+    /// Fresh statistics for feature `index` under `strategy`.
+    pub fn new(strategy: Strategy, index: usize) -> Self {
+        let slot = ATTR_SLOTS.get(index).map(|s| s.normalization);
+        match strategy {
+            Strategy::Schema => match slot {
+                Some(n) => Feature::Schema(n),
+                None => Feature::Quantile(QuantileSketch::new()),
+            },
+            Strategy::MinMax => Feature::MinMax { min: f64::INFINITY, max: f64::NEG_INFINITY },
+            Strategy::ZScore => Feature::ZScore { mean: 0.0, var: 0.0, seen: 0 },
+            // The schema bound is a sound first guess at the range
+            Strategy::Log => Feature::Log {
+                max: match slot {
+                    Some(Normalization::Linear { max }) | Some(Normalization::Log { max }) => max,
+                    _ => 0.0,
+                },
+            },
+            Strategy::Quantile => Feature::Quantile(QuantileSketch::new()),
+        }
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        match self {
+            Feature::Schema(_) => Strategy::Schema,
+            Feature::MinMax { .. } => Strategy::MinMax,
+            Feature::ZScore { .. } => Strategy::ZScore,
+            Feature::Log { .. } => Strategy::Log,
+            Feature::Quantile(_) => Strategy::Quantile,
+        }
+    }
+
+    /// Scores `x` without learning it, as a Q32.32 fraction in [0, 1).
+    pub fn score(&self, x: i64) -> i64 {
+        let v = x.max(0) as f64;
+        let y = match self {
+            Feature::Schema(n) => n.apply(x),
+            Feature::MinMax { min, max } => {
+                if min > max {
+                    0.5
+                } else if max > min {
+                    (v - min) / (max - min)
+                } else {
+                    // One distinct value so far: it is the middle
+                    match v.partial_cmp(min) {
+                        Some(std::cmp::Ordering::Greater) => 1.0,
+                        Some(std::cmp::Ordering::Less) => 0.0,
+                        _ => 0.5,
+                    }
+                }
+            }
+            Feature::ZScore { mean, var, seen } => {
+                if *seen == 0 {
+                    0.5
+                } else {
+                    let z = (x as f64 - mean) / var.sqrt().max(f64::EPSILON);
+                    1.0 / (1.0 + (-LOGISTIC_SLOPE * z).exp())
+                }
+            }
+            Feature::Log { max } => {
+                if *max <= 0.0 {
+                    0.5
+                } else {
+                    v.ln_1p() / max.ln_1p()
+                }
+            }
+            Feature::Quantile(sketch) => sketch.cdf(x.max(0) as u64),
+        };
+        fraction(y)
+    }
+
+    /// Adds `x` to the statistics.
+    pub fn learn(&mut self, x: i64) {
+        let v = x.max(0) as f64;
+        match self {
+            Feature::Schema(_) => {}
+            Feature::MinMax { min, max } => {
+                *min = min.min(v);
+                *max = max.max(v);
+            }
+            Feature::ZScore { mean, var, seen } => {
+                let x = x as f64;
+                if *seen == 0 {
+                    *mean = x;
+                } else {
+                    let delta = x - *mean;
+                    *mean += ZSCORE_ALPHA * delta;
+                    *var = (1.0 - ZSCORE_ALPHA) * (*var + ZSCORE_ALPHA * delta * delta);
+                }
+                *seen = seen.saturating_add(1);
+            }
+            Feature::Log { max } => *max = max.max(v),
+            Feature::Quantile(sketch) => sketch.insert(x.max(0) as u64),
+        }
+    }
+
+    /// Scores `x`, then learns it.
+    pub fn observe(&mut self, x: i64) -> i64 {
+        let y = self.score(x);
+        self.learn(x);
+        y
+    }
+
+    /// Snapshot line body: strategy name and its statistics.
+    fn to_snapshot(&self) -> String {
+        match self {
+            Feature::Schema(_) => "schema".to_string(),
+            Feature::MinMax { min, max } => format!("minmax {} {}", min, max),
+            Feature::ZScore { mean, var, seen } => format!("zscore {} {} {}", mean, var, seen),
+            Feature::Log { max } => format!("log {}", max),
+            Feature::Quantile(s) => {
+                let mut line = format!("quantile {}", s.inserts);
+                for (b, &c) in s.counts.iter().enumerate().filter(|(_, &c)| c > 0) {
+                    line.push_str(&format!(" {}:{}", b, c));
+                }
+                line
+            }
+        }
+    }
+
+    fn from_snapshot(index: usize, fields: &[&str]) -> io::Result<Self> {
+        let bad = || invalid(format!("feature {}: malformed statistics '{}'", index, fields.join(" ")));
+        let num = |i: usize| -> io::Result<f64> { fields.get(i).and_then(|f| f.parse().ok()).ok_or_else(bad) };
+        let strategy = fields.first().and_then(|s| Strategy::parse(s)).ok_or_else(bad)?;
+        Ok(match strategy {
+            Strategy::Schema => Feature::new(Strategy::Schema, index),
+            Strategy::MinMax => Feature::MinMax { min: num(1)?, max: num(2)? },
+            Strategy::ZScore => Feature::ZScore {
+                mean: num(1)?,
+                var: num(2)?,
+                seen: fields.get(3).and_then(|f| f.parse().ok()).ok_or_else(bad)?,
+            },
+            Strategy::Log => Feature::Log { max: num(1)? },
+            Strategy::Quantile => {
+                let mut sketch = QuantileSketch::new();
+                sketch.inserts = fields.get(1).and_then(|f| f.parse().ok()).ok_or_else(bad)?;
+                for pair in &fields[2..] {
+                    let (b, c) = pair
+                        .split_once(':')
+                        .and_then(|(b, c)| Some((b.parse::<usize>().ok()?, c.parse::<u32>().ok()?)))
+                        .filter(|&(b, _)| b < QUANTILE_BUCKETS)
+                        .ok_or_else(bad)?;
+                    sketch.counts[b] = c;
+                }
+                sketch.total = sketch.counts.iter().map(|&c| c as u64).sum();
+                Feature::Quantile(sketch)
+            }
+        })
+    }
+}
+
+/// Per-feature normalizers for vectors of any length
+/// -------------------------------------------------
+/// Features past the configured ones are created on first sight with the
+/// default strategy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Normalizer {
+    default: Strategy,
+    overrides: Vec<(usize, Strategy)>,
+    features: Vec<Feature>,
+}
+
+impl Normalizer {
+    /// Every feature under `default`.
+    pub fn new(default: Strategy) -> Self {
+        Self {
+            default,
+            overrides: Vec::new(),
+            features: Vec::new(),
+        }
+    }
+
+    /// Uses `strategy` for feature `index` instead of the default.
+    pub fn with_strategy(mut self, index: usize, strategy: Strategy) -> Self {
+        self.overrides.retain(|&(i, _)| i != index);
+        self.overrides.push((index, strategy));
+        if let Some(f) = self.features.get_mut(index) {
+            *f = Feature::new(strategy, index);
+        }
+        self
+    }
+
+    /// `default[, slot=strategy ...]`; slots by schema name or index.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut parts = spec.split(',').map(str::trim).filter(|p| !p.is_empty());
+        let mut normalizer = Self::new(Strategy::parse(parts.next()?)?);
+        for part in parts {
+            let (slot, strategy) = part.split_once('=')?;
+            let slot = slot.trim();
+            let index = ATTR_SLOTS
+                .iter()
+                .position(|s| s.name == slot)
+                .or_else(|| slot.parse().ok())?;
+            normalizer = normalizer.with_strategy(index, Strategy::parse(strategy.trim())?);
+        }
+        Some(normalizer)
+    }
+
+    /// The spec this normalizer was built from (`parse` round-trips it).
+    pub fn spec(&self) -> String {
+        let mut spec = self.default.as_str().to_string();
+        for &(index, strategy) in &self.overrides {
+            let name = ATTR_SLOTS.get(index).map(|s| s.name.to_string()).unwrap_or_else(|| index.to_string());
+            spec.push_str(&format!(", {}={}", name, strategy.as_str()));
+        }
+        spec
+    }
+
+    /// `TIGER_NORMALIZE` when set, otherwise the static schema scaling.
+    pub fn from_env() -> io::Result<Self> {
+        match std::env::var(NORMALIZE_ENV) {
+            Ok(value) => Self::parse(&value).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} must be a strategy (schema, minmax, zscore, log, quantile) optionally followed by slot=strategy overrides, got '{}'",
+                        NORMALIZE_ENV, value
+                    ),
+                )
+            }),
+            Err(_) => Ok(Self::new(Strategy::Schema)),
+        }
+    }
+
+    pub fn strategy(&self, index: usize) -> Strategy {
+        self.overrides
+            .iter()
+            .find(|&&(i, _)| i == index)
+            .map_or(self.default, |&(_, s)| s)
+    }
+
+    pub fn feature(&self, index: usize) -> Option<&Feature> {
+        self.features.get(index)
+    }
+
+    fn grow(&mut self, len: usize) {
+        while self.features.len() < len {
+            let index = self.features.len();
+            self.features.push(Feature::new(self.strategy(index), index));
+        }
+    }
+
+    /// Scores every feature, then learns the vector; the output has the
+    /// input's length, each value a Q32.32 fraction in [0, 1).
+    pub fn normalize(&mut self, raw: &[i64]) -> Vec<i64> {
+        self.grow(raw.len());
+        raw.iter().zip(self.features.iter_mut()).map(|(&x, f)| f.observe(x)).collect()
+    }
+
+    /// Scores without learning (slots never seen score ½).
+    pub fn score(&self, raw: &[i64]) -> Vec<i64> {
+        raw.iter()
+            .enumerate()
+            .map(|(i, &x)| match self.features.get(i) {
+                Some(f) => f.score(x),
+                None => Feature::new(self.strategy(i), i).score(x),
+            })
+            .collect()
+    }
+
+    /// One `feature <index> <strategy> <statistics>` line per feature.
+    pub fn to_snapshot(&self) -> String {
+        self.features
+            .iter()
+            .enumerate()
+            .map(|(i, f)| format!("feature {} {}\n", i, f.to_snapshot()))
+            .collect()
+    }
+
+    /// Takes learned statistics from snapshot lines. A feature whose saved
+    /// strategy differs from the configured one starts afresh: the config
+    /// wins over the snapshot.
+    pub fn restore(&mut self, snapshot: &str) -> io::Result<()> {
+        for line in snapshot.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some(rest) = fields.strip_prefix(&["feature"]) else {
+                continue;
+            };
+            let index: usize = rest
+                .first()
+                .and_then(|i| i.parse().ok())
+                .filter(|&i| i < MAX_FEATURES)
+                .ok_or_else(|| invalid(format!("bad feature line '{}'", line)))?;
+            let feature = Feature::from_snapshot(index, &rest[1..])?;
+            self.grow(index + 1);
+            if feature.strategy() == self.features[index].strategy() {
+                self.features[index] = feature;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Self::new(Strategy::Schema)
+    }
+}
@@ -0,0 +1,237 @@
+// src/salt.rs
+
+use getrandom::getrandom;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Environment variable naming the shared master key file (hex).
+pub const MASTER_KEY_ENV: &str = "TIGER_MASTER_KEY_FILE";
+
+/// Base rotation tick. Every rotation period is a multiple of it and all
+/// periods divide 30 s, so nodes on different pressure tiers still meet
+/// on common epochs.
+pub const EPOCH_TICK_SECS: u64 = 5;
+
+/// Period multipliers per pressure tier: 30 s, 15 s, 10 s, 5 s.
+const TIER_TICKS: [u64; 4] = [6, 3, 2, 1];
+
+const HKDF_SALT: &[u8] = b"tiger-delta/salt/v1";
+const HKDF_INFO: &[u8] = b"tiger-delta/epoch";
+
+/// Everything derived for one epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochSalts {
+    pub epoch: u64,
+    /// `config_map[0]` — XDP multiplier (always odd, so the fold stays bijective).
+    pub phi: u64,
+    /// `config_map[1]` — XDP xor mask.
+    pub pi: u64,
+    /// StringState compactification nonce (sine fold).
+    pub nonce: u64,
+    /// 256-bit key of the keyed compactifiers (src/compactifier.rs).
+    pub key: [u8; 32],
+}
+
+/// SaltManager: one source of truth for kernel and userspace salts
+/// ---------------------------------------------------------------
+/// Salts are HKDF-SHA256(master key, epoch), so every node holding the same
+/// key derives the same manifold for the same epoch — no salt exchange.
+/// Epochs sit on a wall-clock grid of `EPOCH_TICK_SECS`; attack pressure
+/// shortens the rotation period. Epochs only move forward: when pressure
+/// falls, the coarser grid may point at an epoch already used, and the
+/// current one stays until the grid passes it. After a rotation the
+/// previous epoch stays valid for `overlap`, so verdicts do not jump at
+/// the boundary.
+pub struct SaltManager {
+    prk: [u8; 32],
+    pressure: f64,
+    overlap: Duration,
+    current: EpochSalts,
+    previous: Option<EpochSalts>,
+    rotated_at: SystemTime,
+}
+
+fn unix_secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn clean_pressure(pressure: f64) -> f64 {
+    if pressure.is_nan() {
+        0.0
+    } else {
+        pressure.clamp(0.0, 1.0)
+    }
+}
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+impl SaltManager {
+    /// Derives the pseudo-random key (HKDF-Extract) from a master key.
+    pub fn new(master_key: &[u8]) -> Self {
+        let prk = hmac(HKDF_SALT, &[master_key]);
+        let now = SystemTime::now();
+        let mut manager = Self {
+            prk,
+            pressure: 0.0,
+            overlap: Duration::from_secs(EPOCH_TICK_SECS),
+            current: EpochSalts { epoch: 0, phi: 1, pi: 0, nonce: 0, key: [0; 32] },
+            previous: None,
+            rotated_at: now,
+        };
+        manager.current = manager.derive(manager.epoch_at(unix_secs(now)));
+        manager
+    }
+
+    /// Node-local key from the hardware RNG (no fleet agreement).
+    pub fn random() -> Self {
+        let mut key = [0u8; 32];
+        if getrandom(&mut key).is_err() {
+            // Останній варіант: час запуску як ключ — слабко, але не нуль
+            let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            key[..16].copy_from_slice(&t.as_nanos().to_le_bytes());
+        }
+        Self::new(&key)
+    }
+
+    /// Reads a hex-encoded master key (whitespace ignored).
+    pub fn from_key_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let hex: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if hex.len() < 32 || !hex.len().is_multiple_of(2) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "master key must be >= 16 bytes of hex"));
+        }
+        let key = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Self::new(&key))
+    }
+
+    /// Shared key from `TIGER_MASTER_KEY_FILE` when set, otherwise random.
+    pub fn from_env() -> io::Result<Self> {
+        match std::env::var(MASTER_KEY_ENV) {
+            Ok(path) => Self::from_key_file(path),
+            Err(_) => Ok(Self::random()),
+        }
+    }
+
+    pub fn with_overlap(mut self, overlap: Duration) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// HKDF-Expand for one epoch: block T(1) gives the words, T(2) the
+    /// compactifier key.
+    pub fn derive(&self, epoch: u64) -> EpochSalts {
+        let epoch_be = epoch.to_be_bytes();
+        let okm = hmac(&self.prk, &[HKDF_INFO, &epoch_be, &[1u8]]);
+        let key = hmac(&self.prk, &[&okm, HKDF_INFO, &epoch_be, &[2u8]]);
+        let word = |i: usize| u64::from_le_bytes(okm[i * 8..i * 8 + 8].try_into().unwrap());
+        EpochSalts {
+            epoch,
+            phi: word(0) | 1,
+            pi: word(1),
+            nonce: word(2),
+            key,
+        }
+    }
+
+    /// Attack pressure in [0, 1]; higher pressure → faster rotation.
+    /// Replaces the current value (one aggregate per control loop).
+    pub fn set_pressure(&mut self, pressure: f64) {
+        self.pressure = clean_pressure(pressure);
+    }
+
+    /// Per-packet pressure: latches the peak until the next rotation, so
+    /// the last packet does not decide the tier for everyone before it.
+    pub fn report_pressure(&mut self, pressure: f64) {
+        self.pressure = self.pressure.max(clean_pressure(pressure));
+    }
+
+    pub fn pressure(&self) -> f64 {
+        self.pressure
+    }
+
+    fn period_ticks(&self) -> u64 {
+        let tier = ((self.pressure * TIER_TICKS.len() as f64) as usize).min(TIER_TICKS.len() - 1);
+        TIER_TICKS[tier]
+    }
+
+    pub fn period(&self) -> Duration {
+        Duration::from_secs(self.period_ticks() * EPOCH_TICK_SECS)
+    }
+
+    /// Epoch number (in ticks) for a wall-clock second at the current pressure.
+    pub fn epoch_at(&self, unix_secs: u64) -> u64 {
+        let tick = unix_secs / EPOCH_TICK_SECS;
+        let k = self.period_ticks();
+        tick - tick % k
+    }
+
+    /// Rotates if the epoch for `now` is past the current one; an epoch at
+    /// or before it (pressure fell, clock stepped back) is never reused.
+    /// A rotation releases the latched pressure. Returns true on rotation.
+    pub fn refresh_at(&mut self, now: SystemTime) -> bool {
+        let epoch = self.epoch_at(unix_secs(now));
+        if epoch <= self.current.epoch {
+            return false;
+        }
+        self.previous = Some(self.current);
+        self.current = self.derive(epoch);
+        self.rotated_at = now;
+        self.pressure = 0.0;
+        true
+    }
+
+    pub fn refresh(&mut self) -> bool {
+        self.refresh_at(SystemTime::now())
+    }
+
+    pub fn current(&self) -> EpochSalts {
+        self.current
+    }
+
+    pub fn in_overlap_at(&self, now: SystemTime) -> bool {
+        self.previous.is_some()
+            && now.duration_since(self.rotated_at).map(|d| d < self.overlap).unwrap_or(true)
+    }
+
+    pub fn in_overlap(&self) -> bool {
+        self.in_overlap_at(SystemTime::now())
+    }
+
+    /// Previous epoch's salts while the overlap window is open.
+    pub fn previous_at(&self, now: SystemTime) -> Option<EpochSalts> {
+        if self.in_overlap_at(now) {
+            self.previous
+        } else {
+            None
+        }
+    }
+
+    pub fn previous(&self) -> Option<EpochSalts> {
+        self.previous_at(SystemTime::now())
+    }
+
+    /// True for the current epoch and, during overlap, the previous one.
+    pub fn accepts_at(&self, epoch: u64, now: SystemTime) -> bool {
+        epoch == self.current.epoch || self.previous_at(now).map(|p| p.epoch == epoch).unwrap_or(false)
+    }
+
+    pub fn accepts(&self, epoch: u64) -> bool {
+        self.accepts_at(epoch, SystemTime::now())
+    }
+}
@@ -0,0 +1,403 @@
+// src/schema.rs
+
+//! Feature schema: the one definition of a TigerΔ feature vector
+//! ------------------------------------------------------------
+//! Two vectors exist, and both are described here and nowhere else:
+//!
+//! * the **kernel vector** — `FEATURE_COUNT` u64 words packed from
+//!   `struct pkt_meta` fields (`KERNEL_FIELDS`: word, shift, width);
+//! * the **attribute vector** — the 10 i64 slots the cognitive cores
+//!   consume (`ATTR_SLOTS`: name, width, normalization to [0, 1]).
+//!
+//! Derived from this module:
+//!
+//! * `src/kernel/tiger_schema.h` — `struct pkt_meta`, its layout asserts
+//!   and `tiger_pack_features()`, generated by `kernel_header()`;
+//!   tests/schema.rs fails when the checked-in header is stale
+//!   (`TIGER_BLESS=1 cargo test --test schema` rewrites it);
+//! * `RawPktMeta` / `RawSample` — the Rust mirrors, checked against the
+//!   same offsets at compile time;
+//! * the emulator's `feature_vector()` (via `pack`) and the payload
+//!   extractor in features.rs (via the `attr::*` slot indices);
+//! * `normalize` / `denormalize` for tools that think in [0, 1]
+//!   vectors (red-team generators).
+
+use std::mem::{offset_of, size_of};
+
+// =================================================================
+// Kernel vector
+// =================================================================
+
+pub const FEATURE_COUNT: usize = 5;
+
+/// `bpf_ktime_get_ns() >> TIME_BUCKET_SHIFT`: ~4ms buckets.
+pub const TIME_BUCKET_SHIFT: u32 = 22;
+
+/// One field packed into the kernel vector.
+#[derive(Clone, Copy, Debug)]
+pub struct KernelField {
+    pub name: &'static str,
+    pub word: usize,
+    pub shift: u32,
+    pub bits: u32,
+}
+
+impl KernelField {
+    pub const fn mask(&self) -> u64 {
+        if self.bits >= 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.bits) - 1
+        }
+    }
+
+    /// Reads the field back out of a packed vector.
+    pub fn extract(&self, v: &[u64; FEATURE_COUNT]) -> u64 {
+        (v[self.word] >> self.shift) & self.mask()
+    }
+}
+
+/// Indices into `KERNEL_FIELDS` (and into the value array of `pack`).
+pub mod kf {
+    pub const SADDR: usize = 0;
+    pub const DADDR: usize = 1;
+    pub const IS_V6: usize = 2;
+    pub const PROTOCOL: usize = 3;
+    pub const LEN: usize = 4;
+    pub const VLAN: usize = 5;
+    pub const QUEUE: usize = 6;
+    pub const TIME_BUCKET: usize = 7;
+    pub const SPORT: usize = 8;
+    pub const DPORT: usize = 9;
+    pub const TCP_FLAGS: usize = 10;
+}
+
+pub const KERNEL_FIELD_COUNT: usize = 11;
+
+const fn field(name: &'static str, word: usize, shift: u32, bits: u32) -> KernelField {
+    KernelField { name, word, shift, bits }
+}
+
+/// Order is the `kf::*` order. Ingress packs source first; the egress
+/// observer packs the same slots with the roles swapped (destination
+/// in SADDR/SPORT, ifindex in QUEUE).
+pub const KERNEL_FIELDS: [KernelField; KERNEL_FIELD_COUNT] = [
+    field("saddr", 0, 32, 32),
+    field("daddr", 0, 0, 32),
+    field("is_v6", 1, 56, 8),
+    field("protocol", 1, 48, 8),
+    field("len", 1, 0, 16),
+    field("vlan", 2, 32, 16),
+    field("queue", 2, 0, 32),
+    field("time_bucket", 3, 0, 64),
+    field("sport", 4, 48, 16),
+    field("dport", 4, 32, 16),
+    field("tcp_flags", 4, 0, 8),
+];
+
+/// No field spills out of its word and no two fields of a word overlap.
+pub const fn kernel_layout_is_valid() -> bool {
+    let mut i = 0;
+    while i < KERNEL_FIELD_COUNT {
+        let a = KERNEL_FIELDS[i];
+        if a.word >= FEATURE_COUNT || a.bits == 0 || a.shift + a.bits > 64 {
+            return false;
+        }
+        let mut j = i + 1;
+        while j < KERNEL_FIELD_COUNT {
+            let b = KERNEL_FIELDS[j];
+            if a.word == b.word && (a.mask() << a.shift) & (b.mask() << b.shift) != 0 {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+const _: () = assert!(kernel_layout_is_valid(), "KERNEL_FIELDS overlap or overflow");
+
+/// Packs field values (in `kf::*` order) into the kernel vector.
+pub fn pack(values: &[u64; KERNEL_FIELD_COUNT]) -> [u64; FEATURE_COUNT] {
+    let mut v = [0u64; FEATURE_COUNT];
+    for (f, &x) in KERNEL_FIELDS.iter().zip(values) {
+        v[f.word] |= (x & f.mask()) << f.shift;
+    }
+    v
+}
+
+// =================================================================
+// struct pkt_meta / struct tiger_sample
+// =================================================================
+
+/// One member of `struct pkt_meta`, in declaration order.
+#[derive(Clone, Copy, Debug)]
+pub struct MetaField {
+    pub name: &'static str,
+    pub size: usize,
+    pub doc: &'static str,
+}
+
+const fn meta(name: &'static str, size: usize, doc: &'static str) -> MetaField {
+    MetaField { name, size, doc }
+}
+
+pub const PKT_META_FIELDS: [MetaField; 9] = [
+    meta("saddr", 4, "IPv4 address, or IPv6 address xor-folded to 32 bit"),
+    meta("daddr", 4, ""),
+    meta("len", 2, "tot_len (v4) / payload_len (v6)"),
+    meta("protocol", 1, "protocol (v4) / nexthdr (v6)"),
+    meta("is_v6", 1, ""),
+    meta("vlan", 2, "innermost VLAN id, 0 when untagged"),
+    meta("sport", 2, ""),
+    meta("dport", 2, ""),
+    meta("tcp_flags", 1, ""),
+];
+
+/// Natural C layout: each member aligned to its own size.
+pub const fn pkt_meta_offset(index: usize) -> usize {
+    let mut off: usize = 0;
+    let mut i = 0;
+    while i < index {
+        off = off.next_multiple_of(PKT_META_FIELDS[i].size) + PKT_META_FIELDS[i].size;
+        i += 1;
+    }
+    off.next_multiple_of(PKT_META_FIELDS[index].size)
+}
+
+pub const PKT_META_ALIGN: usize = 4;
+pub const PKT_META_SIZE: usize =
+    (pkt_meta_offset(PKT_META_FIELDS.len() - 1) + PKT_META_FIELDS[PKT_META_FIELDS.len() - 1].size)
+        .next_multiple_of(PKT_META_ALIGN);
+
+/// Rust mirror of `struct pkt_meta` (raw network-order fields).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RawPktMeta {
+    pub saddr: u32,
+    pub daddr: u32,
+    pub len: u16,
+    pub protocol: u8,
+    pub is_v6: u8,
+    pub vlan: u16,
+    pub sport: u16,
+    pub dport: u16,
+    pub tcp_flags: u8,
+}
+
+const _: () = {
+    assert!(offset_of!(RawPktMeta, saddr) == pkt_meta_offset(0));
+    assert!(offset_of!(RawPktMeta, daddr) == pkt_meta_offset(1));
+    assert!(offset_of!(RawPktMeta, len) == pkt_meta_offset(2));
+    assert!(offset_of!(RawPktMeta, protocol) == pkt_meta_offset(3));
+    assert!(offset_of!(RawPktMeta, is_v6) == pkt_meta_offset(4));
+    assert!(offset_of!(RawPktMeta, vlan) == pkt_meta_offset(5));
+    assert!(offset_of!(RawPktMeta, sport) == pkt_meta_offset(6));
+    assert!(offset_of!(RawPktMeta, dport) == pkt_meta_offset(7));
+    assert!(offset_of!(RawPktMeta, tcp_flags) == pkt_meta_offset(8));
+    assert!(size_of::<RawPktMeta>() == PKT_META_SIZE);
+};
+
+/// Rust mirror of `struct tiger_sample` (the `samples` ring records).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawSample {
+    pub ts_ns: u64,
+    pub fold: u64,
+    pub meta: RawPktMeta,
+    pub rx_queue: u32,
+}
+
+/// Checked by `_Static_assert` next to `struct tiger_sample`.
+pub const TIGER_SAMPLE_SIZE: usize = 40;
+
+const _: () = assert!(size_of::<RawSample>() == TIGER_SAMPLE_SIZE);
+
+// =================================================================
+// Attribute vector
+// =================================================================
+
+pub const ATTR_COUNT: usize = 10;
+
+/// Raw attribute vector, as the cores consume it.
+pub type Attrs = [i64; ATTR_COUNT];
+
+/// Attribute vector scaled to [0, 1] per `ATTR_SLOTS`.
+pub type NormalizedAttrs = [f64; ATTR_COUNT];
+
+/// How a raw slot maps onto [0, 1].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Normalization {
+    /// `x / max`, clamped.
+    Linear { max: f64 },
+    /// `ln(1 + x) / ln(1 + max)`, clamped: for heavy-tailed sums.
+    Log { max: f64 },
+    /// Slot not populated; always 0.
+    Unused,
+}
+
+impl Normalization {
+    pub fn apply(self, x: i64) -> f64 {
+        let x = x.max(0) as f64;
+        match self {
+            Normalization::Linear { max } => (x / max).clamp(0.0, 1.0),
+            Normalization::Log { max } => (x.ln_1p() / max.ln_1p()).clamp(0.0, 1.0),
+            Normalization::Unused => 0.0,
+        }
+    }
+
+    pub fn invert(self, y: f64) -> i64 {
+        let y = y.clamp(0.0, 1.0);
+        match self {
+            Normalization::Linear { max } => (y * max).round() as i64,
+            Normalization::Log { max } => (y * max.ln_1p()).exp_m1().round() as i64,
+            Normalization::Unused => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AttrSlot {
+    pub name: &'static str,
+    /// Bits a raw value can occupy.
+    pub bits: u32,
+    pub normalization: Normalization,
+}
+
+impl AttrSlot {
+    /// Whether the pipeline populates this slot.
+    pub fn is_used(&self) -> bool {
+        self.normalization != Normalization::Unused
+    }
+}
+
+/// Slot indices of the attribute vector.
+pub mod attr {
+    pub const SRC_PORT: usize = 0;
+    pub const LENGTH: usize = 1;
+    pub const FIRST_BYTE: usize = 2;
+    pub const BYTE_WEIGHT: usize = 3;
+    pub const WEIGHT_MOD_111: usize = 4;
+    pub const LENGTH_MOD_7: usize = 5;
+    pub const HEAD_WEIGHT: usize = 6;
+    pub const SRC_IP_TEXT_LEN: usize = 7;
+    pub const RESERVED_8: usize = 8;
+    pub const RESERVED_9: usize = 9;
+}
+
+/// Payload of at most 1024 bytes (the UDP interceptor's limit).
+pub const MAX_PAYLOAD: i64 = 1024;
+
+const fn slot(name: &'static str, bits: u32, normalization: Normalization) -> AttrSlot {
+    AttrSlot { name, bits, normalization }
+}
+
+pub const ATTR_SLOTS: [AttrSlot; ATTR_COUNT] = [
+    slot("src_port", 16, Normalization::Linear { max: 65535.0 }),
+    slot("length", 11, Normalization::Linear { max: MAX_PAYLOAD as f64 }),
+    slot("first_byte", 8, Normalization::Linear { max: 255.0 }),
+    slot("byte_weight", 18, Normalization::Log { max: (MAX_PAYLOAD * 255) as f64 }),
+    slot("weight_mod_111", 7, Normalization::Linear { max: 110.0 }),
+    slot("length_mod_7", 3, Normalization::Linear { max: 6.0 }),
+    slot("head_weight", 11, Normalization::Linear { max: 1275.0 }),
+    slot("src_ip_text_len", 6, Normalization::Linear { max: 39.0 }),
+    slot("reserved_8", 0, Normalization::Unused),
+    slot("reserved_9", 0, Normalization::Unused),
+];
+
+/// Indices of the populated slots, in schema order.
+pub fn used_slots() -> impl Iterator<Item = usize> {
+    ATTR_SLOTS.iter().enumerate().filter(|(_, s)| s.is_used()).map(|(i, _)| i)
+}
+
+pub fn normalize(a: &Attrs) -> NormalizedAttrs {
+    let mut out = [0.0; ATTR_COUNT];
+    for ((o, &x), s) in out.iter_mut().zip(a).zip(&ATTR_SLOTS) {
+        *o = s.normalization.apply(x);
+    }
+    out
+}
+
+pub fn denormalize(n: &NormalizedAttrs) -> Attrs {
+    let mut out = [0; ATTR_COUNT];
+    for ((o, &y), s) in out.iter_mut().zip(n).zip(&ATTR_SLOTS) {
+        *o = s.normalization.invert(y);
+    }
+    out
+}
+
+// =================================================================
+// Kernel header generation
+// =================================================================
+
+fn c_type(size: usize) -> &'static str {
+    match size {
+        1 => "__u8 ",
+        2 => "__u16",
+        4 => "__u32",
+        _ => "__u64",
+    }
+}
+
+/// Contents of src/kernel/tiger_schema.h.
+pub fn kernel_header() -> String {
+    let mut h = String::new();
+    h.push_str("/*\n * TigerΔ: feature schema — GENERATED from src/schema.rs, do not edit.\n");
+    h.push_str(" * Regenerate with `TIGER_BLESS=1 cargo test --test schema`.\n */\n\n");
+    h.push_str("#ifndef TIGER_SCHEMA_H\n#define TIGER_SCHEMA_H\n\n");
+
+    h.push_str("/* Parsed packet metadata. Addresses, lengths and ports keep the raw\n");
+    h.push_str(" * network-order loads; the userspace emulator (src/xdp_emulator.rs)\n");
+    h.push_str(" * reproduces the same values. */\nstruct pkt_meta {\n");
+    for f in &PKT_META_FIELDS {
+        let decl = format!("    {} {};", c_type(f.size), f.name);
+        if f.doc.is_empty() {
+            h.push_str(&format!("{}\n", decl));
+        } else {
+            h.push_str(&format!("{:<23}/* {} */\n", decl, f.doc));
+        }
+    }
+    h.push_str("};\n\n");
+
+    for (i, f) in PKT_META_FIELDS.iter().enumerate() {
+        h.push_str(&format!(
+            "_Static_assert(__builtin_offsetof(struct pkt_meta, {}) == {}, \"pkt_meta.{} moved\");\n",
+            f.name,
+            pkt_meta_offset(i),
+            f.name
+        ));
+    }
+    h.push_str(&format!(
+        "_Static_assert(sizeof(struct pkt_meta) == {}, \"pkt_meta size\");\n\n",
+        PKT_META_SIZE
+    ));
+    h.push_str(&format!("#define TIGER_SAMPLE_SIZE {}\n\n", TIGER_SAMPLE_SIZE));
+
+    h.push_str(&format!("#define FEATURE_COUNT       {}\n", FEATURE_COUNT));
+    h.push_str(&format!("#define TIME_BUCKET_SHIFT   {}\n", TIME_BUCKET_SHIFT));
+    h.push_str(&format!("#define KERNEL_FIELD_COUNT  {}\n\n", KERNEL_FIELD_COUNT));
+    for (i, f) in KERNEL_FIELDS.iter().enumerate() {
+        h.push_str(&format!("#define KF_{:<12} {}\n", f.name.to_uppercase(), i));
+    }
+
+    h.push_str("\n/* Kernel feature vector: f[] in KF_* order, packed into v[] */\n");
+    h.push_str("static __always_inline void tiger_pack_features(__u64 *v, const __u64 *f) {\n");
+    for word in 0..FEATURE_COUNT {
+        let terms: Vec<String> = KERNEL_FIELDS
+            .iter()
+            .filter(|f| f.word == word)
+            .map(|f| {
+                format!(
+                    "((f[KF_{}] & 0x{:X}ULL) << {})",
+                    f.name.to_uppercase(),
+                    f.mask(),
+                    f.shift
+                )
+            })
+            .collect();
+        h.push_str(&format!("    v[{}] = {};\n", word, terms.join("\n         | ")));
+    }
+    h.push_str("}\n\n#endif /* TIGER_SCHEMA_H */\n");
+    h
+}
@@ -1,27 +1,322 @@
 // src/simul.rs
 
+use std::collections::VecDeque;
 use std::f64::consts::PI;
-use crate::lumis::PHI;
+use std::io;
+
+use crate::atomic_core::AtomicCore;
+use crate::lagrange::LagrangeEquilibrium;
+use crate::lumis::{LumisCore, NOMINAL_TICK_HZ, PHI};
+use crate::threshold::ThresholdController;
+
+/// Змінна середовища з режимом двійника (main.rs, типове значення лоадера).
+pub const SIMUL_ENV: &str = "TIGER_SIMUL";
+
+/// Останні спостереження, з яких вибираються майбутні пакети.
+pub const OBSERVATION_WINDOW: usize = 512;
+
+/// Поки спостережень менше, проекції немає.
+pub const MIN_OBSERVATIONS: usize = 32;
+
+/// Межі маси захисту — ті самі, що тримає Brain.
+const MASS_MIN: f64 = 100.0;
+const MASS_MAX: f64 = 10_000.0;
+
+/// Чим двійник вирішує Preempt.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SimulMode {
+    /// Скалярна пісочниця `project_impact` (історична поведінка).
+    #[default]
+    Scalar,
+    /// Монте-Карло: клоновані ядра проходять N випадкових майбутніх.
+    MonteCarlo,
+}
+
+impl SimulMode {
+    pub const ALL: [SimulMode; 2] = [SimulMode::Scalar, SimulMode::MonteCarlo];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "scalar" => Some(SimulMode::Scalar),
+            "montecarlo" | "monte-carlo" | "mc" => Some(SimulMode::MonteCarlo),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SimulMode::Scalar => "scalar",
+            SimulMode::MonteCarlo => "montecarlo",
+        }
+    }
+
+    /// `TIGER_SIMUL` when set, otherwise the scalar sandbox.
+    pub fn from_env() -> io::Result<Self> {
+        match std::env::var(SIMUL_ENV) {
+            Ok(value) => Self::parse(&value).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} must be scalar or montecarlo, got '{}'", SIMUL_ENV, value),
+                )
+            }),
+            Err(_) => Ok(SimulMode::Scalar),
+        }
+    }
+}
+
+/// Один пакет, що пройшов ядра: те, що траєкторії відтворюють.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Observation {
+    pub impact: f64,
+    pub entropy: f64,
+    /// Згорнутий вектор (`StringState::compactify_slice`) як дріб.
+    pub compact: f64,
+    pub arrival_pressure: f64,
+    /// Частота джерела за секунду; 0 — невідома.
+    pub rate_pps: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProjectionConfig {
+    /// Траєкторій на проекцію.
+    pub trajectories: u32,
+    /// Пакетів у кожній траєкторії.
+    pub horizon: u32,
+    /// У режимі MonteCarlo — проекція раз на стільки спостережень.
+    pub period: u32,
+    /// Ймовірність прориву, з якої пакет випереджається.
+    pub preempt_probability: f64,
+    pub seed: u64,
+}
+
+impl Default for ProjectionConfig {
+    fn default() -> Self {
+        Self {
+            trajectories: 64,
+            horizon: 32,
+            period: 16,
+            preempt_probability: 0.5,
+            seed: 0x7163_7ea1,
+        }
+    }
+}
+
+/// Підсумок N траєкторій.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection {
+    pub trajectories: u32,
+    pub horizon: u32,
+    /// Траєкторій, де ядра дійшли до рішення Attack.
+    pub breaches: u32,
+    pub breach_probability: f64,
+    /// Медіана пакетів до прориву серед траєкторій із проривом.
+    pub steps_to_breach: Option<f64>,
+    /// Та сама медіана в секундах за спостереженою частотою.
+    pub seconds_to_breach: Option<f64>,
+}
+
+impl Projection {
+    /// Короткий рядок для журналу.
+    pub fn summary(&self) -> String {
+        match (self.steps_to_breach, self.seconds_to_breach) {
+            (Some(steps), Some(secs)) => {
+                format!("breach p={:.2} in ~{:.0} packets ({:.2}s)", self.breach_probability, steps, secs)
+            }
+            (Some(steps), None) => format!("breach p={:.2} in ~{:.0} packets", self.breach_probability, steps),
+            _ => format!("breach p={:.2}", self.breach_probability),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let opt = |v: Option<f64>| v.map_or_else(|| "null".to_string(), |v| format!("{:.3}", v));
+        format!(
+            "{{\"trajectories\":{},\"horizon\":{},\"breaches\":{},\"breach_probability\":{:.4},\"steps_to_breach\":{},\"seconds_to_breach\":{}}}",
+            self.trajectories,
+            self.horizon,
+            self.breaches,
+            self.breach_probability,
+            opt(self.steps_to_breach),
+            opt(self.seconds_to_breach)
+        )
+    }
+}
 
 /// SIMUL: Віртуальна пісочниця для проекції ентропії.
 /// ------------------------------------------------
 /// Дозволяє тестувати вплив трафіку в "безпечному просторі"
 /// перед тим, як приймати рішення в основному ядрі.
+///
+/// Скалярний режим — накопичувач `project_impact`. Режим Монте-Карло —
+/// цифровий двійник: копії AtomicCore, LagrangeEquilibrium і LumisCore
+/// проходять `trajectories` майбутніх по `horizon` пакетів, кожен пакет
+/// вибирається з вікна спостережень (бутстреп спостереженого розподілу
+/// трафіку). Кроки повторюють етапи 5–8 Brain, порогом слугує поточна база
+/// контролера під модуляцією Lumis-двійника. Частка траєкторій, що
+/// дійшли до Attack, — ймовірність прориву; медіана кроків — час до нього.
 pub struct SimulUnit {
     projection_entropy: f64,
     stability_index: f64,
     learning_rate: f64,
+    mode: SimulMode,
+    config: ProjectionConfig,
+    window: VecDeque<Observation>,
+    rng: u64,
+    since_projection: u32,
+    last_projection: Option<Projection>,
 }
 
 impl SimulUnit {
     pub fn new() -> Self {
+        let config = ProjectionConfig::default();
         SimulUnit {
             projection_entropy: 0.0,
             stability_index: PHI, // Золотий перетин як база стабільності
             learning_rate: 0.001,
+            mode: SimulMode::Scalar,
+            config,
+            window: VecDeque::with_capacity(OBSERVATION_WINDOW),
+            rng: config.seed.max(1),
+            since_projection: 0,
+            last_projection: None,
         }
     }
 
+    pub fn with_mode(mut self, mode: SimulMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Параметри проекції; генератор перезапускається з `config.seed`.
+    pub fn with_projection(mut self, config: ProjectionConfig) -> Self {
+        self.config = config;
+        self.rng = config.seed.max(1);
+        self
+    }
+
+    pub fn mode(&self) -> SimulMode {
+        self.mode
+    }
+
+    pub fn config(&self) -> &ProjectionConfig {
+        &self.config
+    }
+
+    /// Додає пакет, що пройшов ядра, до вікна спостережень.
+    pub fn observe(&mut self, observation: Observation) {
+        if self.window.len() == OBSERVATION_WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back(observation);
+        self.since_projection = self.since_projection.saturating_add(1);
+    }
+
+    pub fn observations(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn last_projection(&self) -> Option<&Projection> {
+        self.last_projection.as_ref()
+    }
+
+    /// Запускає N траєкторій з копій поточного стану ядер. Оригінали не
+    /// змінюються. `None`, поки спостережень менше `MIN_OBSERVATIONS`.
+    pub fn project(
+        &mut self,
+        atomic: &AtomicCore,
+        lagrange: &LagrangeEquilibrium,
+        lumis: &LumisCore,
+        defense_mass: f64,
+        threshold: &ThresholdController,
+    ) -> Option<Projection> {
+        if self.window.len() < MIN_OBSERVATIONS {
+            return None;
+        }
+        let lumis = lumis.twin();
+        let mut steps: Vec<u32> = (0..self.config.trajectories)
+            .filter_map(|_| self.trajectory(atomic.clone(), lagrange.clone(), lumis.clone(), defense_mass, threshold))
+            .collect();
+        steps.sort_unstable();
+
+        let median = match steps.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(steps[n / 2] as f64),
+            n => Some((steps[n / 2 - 1] + steps[n / 2]) as f64 / 2.0),
+        };
+        let rates: Vec<f64> = self.window.iter().map(|o| o.rate_pps).filter(|&r| r > 0.0).collect();
+        let mean_rate = (!rates.is_empty()).then(|| rates.iter().sum::<f64>() / rates.len() as f64);
+        let projection = Projection {
+            trajectories: self.config.trajectories,
+            horizon: self.config.horizon,
+            breaches: steps.len() as u32,
+            breach_probability: steps.len() as f64 / self.config.trajectories.max(1) as f64,
+            steps_to_breach: median,
+            seconds_to_breach: median.zip(mean_rate).map(|(s, r)| s / r),
+        };
+        self.last_projection = Some(projection);
+        Some(projection)
+    }
+
+    /// Режим MonteCarlo: раз на `period` спостережень проектує майбутнє
+    /// і повертає true, якщо ймовірність прориву сягає
+    /// `preempt_probability`. Між проекціями — false.
+    pub fn forecast(
+        &mut self,
+        atomic: &AtomicCore,
+        lagrange: &LagrangeEquilibrium,
+        lumis: &LumisCore,
+        defense_mass: f64,
+        threshold: &ThresholdController,
+    ) -> bool {
+        if self.since_projection < self.config.period.max(1) {
+            return false;
+        }
+        self.since_projection = 0;
+        self.project(atomic, lagrange, lumis, defense_mass, threshold)
+            .is_some_and(|p| p.breach_probability >= self.config.preempt_probability)
+    }
+
+    /// Одна траєкторія; повертає номер пакета (з 1), на якому ядра
+    /// вирішили б Attack, або None, якщо горизонт пройдено.
+    fn trajectory(
+        &mut self,
+        mut atomic: AtomicCore,
+        mut lagrange: LagrangeEquilibrium,
+        mut lumis: LumisCore,
+        mut mass: f64,
+        threshold: &ThresholdController,
+    ) -> Option<u32> {
+        for step in 1..=self.config.horizon {
+            let o = self.sample();
+            let rate = if o.rate_pps > 0.0 { o.rate_pps } else { NOMINAL_TICK_HZ };
+            lumis.advance((1e9 / rate) as u64);
+
+            // Етапи 5–8 Brain на копіях
+            atomic.sharpen_angles(o.impact);
+            atomic.find_the_middle(o.entropy);
+            let threat_p = atomic.threat_probability(o.entropy);
+            let equilibrium = lagrange.stabilize(o.compact, o.impact);
+            if threat_p > threshold.threshold_for(&lumis) || equilibrium.is_none() {
+                return Some(step);
+            }
+
+            let resonance = (1.0 - (equilibrium.unwrap_or(PHI) - PHI).abs() / PHI).clamp(0.0, 1.0);
+            lumis.set_arrival_pressure(o.arrival_pressure);
+            lumis.tick_cycle(o.impact, resonance, &mut mass);
+            mass = mass.clamp(MASS_MIN, MASS_MAX);
+            lagrange.update_mass(mass);
+        }
+        None
+    }
+
+    /// Спостереження з вікна, рівноймовірно (xorshift64).
+    fn sample(&mut self) -> Observation {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let i = ((self.rng >> 32) * self.window.len() as u64) >> 32;
+        self.window[i as usize]
+    }
+
     /// Проектує вхідний удар у пісочницю.
     /// Повертає true, якщо симульована ентропія ламає φ-стабільність.
     pub fn project_impact(&mut self, impact_force: f64) -> bool {
@@ -65,3 +360,9 @@ impl SimulUnit {
         self.stability_index = PHI;
     }
 }
+
+impl Default for SimulUnit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
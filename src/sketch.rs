@@ -0,0 +1,143 @@
+// src/sketch.rs
+
+//! Count-min sketch of per-source packet counts
+//! --------------------------------------------
+//! The XDP program keeps one sketch per CPU (`cms_rows`); the loader sums
+//! the CPUs, diffs consecutive reads into windows and extracts the top
+//! talkers. The emulator and the loader use this module, so hashing and
+//! estimates are the kernel's bit-for-bit.
+//!
+//! Error bounds (Cormode–Muthukrishnan), with N packets in the window,
+//! width w = 2^11 and depth d = 4:
+//!
+//! * an estimate never undercounts: `estimate(x) >= count(x)`;
+//! * `estimate(x) <= count(x) + ε·N` with probability at least `1 - δ`,
+//!   where `ε = e / w ≈ 0.00133` and `δ = e^-d ≈ 0.0183`.
+//!
+//! Per-CPU sketches sum cell-wise into the sketch of the whole stream,
+//! so the bounds hold for the merged view. The row hashes use fixed
+//! public seeds: they only ever inflate counts, and a top talker is
+//! judged by the per-source cores before anything is blocked.
+
+use std::collections::HashSet;
+
+pub const CMS_DEPTH: usize = 4;
+pub const CMS_LOG2_WIDTH: u32 = 11;
+pub const CMS_WIDTH: usize = 1 << CMS_LOG2_WIDTH;
+
+/// `(a, b)` of the multiply-add-shift hash `((a·x + b) mod 2^64) >> (64 - log2 w)`,
+/// one pair per row. Mirrored in src/kernel/tiger_delta_xdp.c.
+pub const CMS_SEEDS: [(u64, u64); CMS_DEPTH] = [
+    (0x9E37_79B9_7F4A_7C15, 0xBF58_476D_1CE4_E5B9),
+    (0x94D0_49BB_1331_11EB, 0x2545_F491_4F6C_DD1D),
+    (0xD6E8_FEB8_6659_FD93, 0xA076_1D64_78BD_642F),
+    (0xE703_7ED1_A0B4_28DB, 0x8EBC_6AF0_9C88_C6E3),
+];
+
+/// A source becomes a top-K candidate when its per-CPU estimate reaches
+/// a power of two at or above this value.
+pub const CMS_CANDIDATE_MIN: u32 = 64;
+
+/// Relative error ε = e / w.
+pub fn epsilon() -> f64 {
+    std::f64::consts::E / CMS_WIDTH as f64
+}
+
+/// Failure probability δ = e^-d.
+pub fn delta() -> f64 {
+    (-(CMS_DEPTH as f64)).exp()
+}
+
+/// Cell of `key` (raw `pkt_meta.saddr`) in `row`.
+pub fn cms_index(row: usize, key: u32) -> usize {
+    let (a, b) = CMS_SEEDS[row];
+    (a.wrapping_mul(key as u64).wrapping_add(b) >> (64 - CMS_LOG2_WIDTH)) as usize
+}
+
+/// Mirror of the kernel candidate test.
+pub fn is_candidate_mark(estimate: u32) -> bool {
+    estimate >= CMS_CANDIDATE_MIN && estimate.is_power_of_two()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CountMinSketch {
+    cells: Vec<u32>,
+}
+
+impl CountMinSketch {
+    pub fn new() -> Self {
+        Self { cells: vec![0; CMS_DEPTH * CMS_WIDTH] }
+    }
+
+    pub fn row(&self, row: usize) -> &[u32] {
+        &self.cells[row * CMS_WIDTH..(row + 1) * CMS_WIDTH]
+    }
+
+    /// Adds one per-CPU row cell-wise (the loader merges CPUs this way).
+    /// Wrapping like the kernel's u32 cells.
+    pub fn add_row(&mut self, row: usize, cells: &[u32]) {
+        for (dst, src) in self.cells[row * CMS_WIDTH..(row + 1) * CMS_WIDTH].iter_mut().zip(cells) {
+            *dst = dst.wrapping_add(*src);
+        }
+    }
+
+    /// Counts one packet of `key`; returns the new estimate
+    /// (what the kernel compares against the candidate marks).
+    pub fn update(&mut self, key: u32) -> u32 {
+        let mut est = u32::MAX;
+        for row in 0..CMS_DEPTH {
+            let cell = &mut self.cells[row * CMS_WIDTH + cms_index(row, key)];
+            *cell = cell.wrapping_add(1);
+            est = est.min(*cell);
+        }
+        est
+    }
+
+    pub fn estimate(&self, key: u32) -> u32 {
+        (0..CMS_DEPTH)
+            .map(|row| self.cells[row * CMS_WIDTH + cms_index(row, key)])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Packets counted (every packet lands once in each row).
+    pub fn total(&self) -> u64 {
+        self.row(0).iter().map(|&c| c as u64).sum()
+    }
+
+    /// Additive error bound ε·N for the current total.
+    pub fn error_bound(&self) -> f64 {
+        epsilon() * self.total() as f64
+    }
+
+    /// Window between two cumulative reads; u32 wrap-around is harmless.
+    pub fn since(&self, earlier: &CountMinSketch) -> CountMinSketch {
+        CountMinSketch {
+            cells: self
+                .cells
+                .iter()
+                .zip(&earlier.cells)
+                .map(|(now, then)| now.wrapping_sub(*then))
+                .collect(),
+        }
+    }
+}
+
+impl Default for CountMinSketch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The `k` candidates with the largest estimates, largest first.
+/// Ties go to the lower key so the order is deterministic.
+pub fn top_k(sketch: &CountMinSketch, candidates: &HashSet<u32>, k: usize) -> Vec<(u32, u32)> {
+    let mut ranked: Vec<(u32, u32)> = candidates
+        .iter()
+        .map(|&key| (key, sketch.estimate(key)))
+        .filter(|&(_, est)| est > 0)
+        .collect();
+    ranked.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked.truncate(k);
+    ranked
+}
@@ -0,0 +1,354 @@
+// src/spectrum.rs
+
+//! Spectral beacon detection on per-source timing series
+//! -----------------------------------------------------
+//! Each source's arrivals and bytes are binned into `SPECTRUM_BINS` bins
+//! of `SPECTRUM_BIN_NS` (256 × 250 ms = 64 s). Every few closed bins the
+//! series is transformed with a radix-2 FFT; the periodogram |X(f)|² gives
+//! the dominant frequency, and its inverse transform (Wiener–Khinchin)
+//! the autocorrelation from which the periodicity score is read:
+//!
+//! * `score` — normalized autocorrelation at the fundamental lag (with
+//!   its neighbours, for pulses jittering across a bin edge), in [0, 1]. A C2 heartbeat (one packet every T seconds, whatever the
+//!   size) scores near 1; Poisson traffic and constant-rate floods score
+//!   near 0 (a flat series has no AC component at all).
+//! * `dominant_hz` — 1 / (fundamental lag): the heartbeat rate, not one
+//!   of the harmonics an impulse train spreads its power over.
+//! * `phase` — position of the pulses within the period on the absolute
+//!   bin grid, so sources can be compared: `synchronized_pulses` groups
+//!   sources beating at the same period and phase (botnet pulses).
+//!
+//! Periods from 2 bins (0.5 s) up to half the window (32 s) are
+//! detectable: at least two cycles must fit into the window. In daemon
+//! mode the series are built from 1-in-N samples, so a low-rate beacon
+//! only shows up at a low sample rate.
+
+use std::f64::consts::TAU;
+
+pub const SPECTRUM_BINS: usize = 256;
+pub const SPECTRUM_BIN_NS: u64 = 250_000_000;
+
+/// Closed bins between two analyses.
+pub const RECOMPUTE_BINS: u64 = 4;
+
+/// Arrivals needed in the window before a source is analyzed.
+pub const MIN_EVENTS: u64 = 6;
+
+/// Score from which a source counts as beaconing.
+pub const BEACON_SCORE: f64 = 0.6;
+
+/// Shortest fundamental lag considered, in bins.
+const MIN_LAG: usize = 2;
+
+/// In-place iterative radix-2 FFT; `re.len()` must be a power of two.
+/// `inverse` computes the unscaled inverse transform.
+pub fn fft(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let n = re.len();
+    assert!(n.is_power_of_two() && im.len() == n, "fft: length must be a power of two");
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * TAU / len as f64;
+        let (w_re, w_im) = (angle.cos(), angle.sin());
+        for start in (0..n).step_by(len) {
+            let (mut c_re, mut c_im) = (1.0, 0.0);
+            for k in 0..len / 2 {
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * c_re - im[b] * c_im;
+                let t_im = re[b] * c_im + im[b] * c_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next = c_re * w_re - c_im * w_im;
+                c_im = c_re * w_im + c_im * w_re;
+                c_re = next;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Periodogram |X(k)|² / n of the mean-removed series, k = 0..=n/2.
+pub fn periodogram(series: &[f64]) -> Vec<f64> {
+    let n = series.len().next_power_of_two();
+    let mean = series.iter().sum::<f64>() / series.len().max(1) as f64;
+    let mut re: Vec<f64> = series.iter().map(|x| x - mean).collect();
+    re.resize(n, 0.0);
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im, false);
+    (0..=n / 2).map(|k| (re[k] * re[k] + im[k] * im[k]) / n as f64).collect()
+}
+
+/// Normalized autocorrelation r(0..n) of the mean-removed series,
+/// through the FFT of the zero-padded series (no circular wrap).
+/// Unbiased: lag L is scaled by n / (n − L); r(0) = 1.
+pub fn autocorrelation(series: &[f64]) -> Vec<f64> {
+    let n = series.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let size = (2 * n).next_power_of_two();
+    let mean = series.iter().sum::<f64>() / n as f64;
+    let mut re: Vec<f64> = series.iter().map(|x| x - mean).collect();
+    re.resize(size, 0.0);
+    let mut im = vec![0.0; size];
+    fft(&mut re, &mut im, false);
+    for k in 0..size {
+        re[k] = re[k] * re[k] + im[k] * im[k];
+        im[k] = 0.0;
+    }
+    fft(&mut re, &mut im, true);
+
+    let r0 = re[0];
+    if r0 <= f64::EPSILON * size as f64 {
+        return vec![0.0; n];
+    }
+    (0..n).map(|l| re[l] / r0 * n as f64 / (n - l) as f64).collect()
+}
+
+/// Spectral summary of one series.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Periodicity {
+    /// Heartbeat frequency (0 when none was found).
+    pub dominant_hz: f64,
+    /// Strongest periodogram line, harmonics included.
+    pub peak_hz: f64,
+    /// Normalized autocorrelation at the fundamental lag, [0, 1].
+    pub score: f64,
+    /// Fundamental lag in bins (0 when none).
+    pub lag: usize,
+    /// Pulse position within the period on the absolute bin grid, [0, 2π).
+    pub phase: f64,
+}
+
+impl Periodicity {
+    pub fn period_s(&self) -> f64 {
+        if self.dominant_hz > 0.0 {
+            1.0 / self.dominant_hz
+        } else {
+            0.0
+        }
+    }
+
+    pub fn is_beacon(&self) -> bool {
+        self.score >= BEACON_SCORE
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"dominant_hz\":{:.4},\"period_s\":{:.2},\"peak_hz\":{:.4},\"score\":{:.3},\"phase\":{:.3}}}",
+            self.dominant_hz,
+            self.period_s(),
+            self.peak_hz,
+            self.score,
+            self.phase
+        )
+    }
+}
+
+/// Analyzes a series whose first element is absolute bin `start_bin`.
+pub fn analyze(series: &[f64], bin_ns: u64, start_bin: u64) -> Periodicity {
+    let n = series.len();
+    if n < 2 * MIN_LAG {
+        return Periodicity::default();
+    }
+    let bin_s = bin_ns as f64 / 1e9;
+    let r = autocorrelation(series);
+
+    // A pulse jittering across a bin edge splits its correlation between
+    // neighbouring lags: the positive parts of L ± 1 are added back
+    let lags = MIN_LAG..=n / 2;
+    let smooth = |l: usize| r[l - 1].max(0.0) + r[l] + r.get(l + 1).copied().unwrap_or(0.0).max(0.0);
+
+    // Fundamental: the shortest lag whose correlation is within 10 % of
+    // the best one, so a harmonic lag (2T, 3T…) never wins over T
+    let best = lags.clone().map(smooth).fold(0.0, f64::max);
+    if best <= 0.0 {
+        return Periodicity::default();
+    }
+    let lag = lags
+        .clone()
+        .find(|&l| smooth(l) >= 0.9 * best && smooth(l) >= smooth(l - 1) && (l + 1 >= n || smooth(l) >= smooth(l + 1)))
+        .unwrap_or(MIN_LAG);
+
+    let power = periodogram(series);
+    let size = (power.len() - 1) * 2;
+    let peak = (1..power.len()).max_by(|&a, &b| power[a].total_cmp(&power[b])).unwrap_or(0);
+
+    // Circular mean of (absolute bin mod lag), weighted by the series
+    let (mut c, mut s) = (0.0, 0.0);
+    for (i, &x) in series.iter().enumerate() {
+        let angle = TAU * ((start_bin + i as u64) % lag as u64) as f64 / lag as f64;
+        c += x * angle.cos();
+        s += x * angle.sin();
+    }
+
+    Periodicity {
+        dominant_hz: 1.0 / (lag as f64 * bin_s),
+        peak_hz: peak as f64 / (size as f64 * bin_s),
+        score: smooth(lag).clamp(0.0, 1.0),
+        lag,
+        phase: s.atan2(c).rem_euclid(TAU),
+    }
+}
+
+/// Per-source ring of arrival and byte counts, analyzed every
+/// `RECOMPUTE_BINS` closed bins.
+#[derive(Clone, Debug)]
+pub struct SpectralSeries {
+    bin_ns: u64,
+    counts: Vec<f64>,
+    bytes: Vec<f64>,
+    head: u64,
+    seen: bool,
+    analyzed_at: u64,
+    arrivals: Periodicity,
+    sizes: Periodicity,
+}
+
+impl SpectralSeries {
+    pub fn new() -> Self {
+        Self::with_bins(SPECTRUM_BIN_NS, SPECTRUM_BINS)
+    }
+
+    pub fn with_bins(bin_ns: u64, bins: usize) -> Self {
+        let bins = bins.max(2 * MIN_LAG).next_power_of_two();
+        Self {
+            bin_ns: bin_ns.max(1),
+            counts: vec![0.0; bins],
+            bytes: vec![0.0; bins],
+            head: 0,
+            seen: false,
+            analyzed_at: 0,
+            arrivals: Periodicity::default(),
+            sizes: Periodicity::default(),
+        }
+    }
+
+    /// Adds `count` arrivals carrying `bytes` at `ts_ns`.
+    pub fn record(&mut self, ts_ns: u64, count: u32, bytes: u64) {
+        let b = ts_ns / self.bin_ns;
+        let n = self.counts.len() as u64;
+        if !self.seen {
+            self.seen = true;
+            self.head = b;
+            self.analyzed_at = b;
+        }
+        if b > self.head {
+            for id in (self.head + 1..=b).take(n as usize) {
+                self.counts[(id % n) as usize] = 0.0;
+                self.bytes[(id % n) as usize] = 0.0;
+            }
+            self.head = b;
+            if b - self.analyzed_at >= RECOMPUTE_BINS {
+                self.analyze();
+                self.analyzed_at = b;
+            }
+        } else if self.head - b >= n {
+            return;
+        }
+        let i = (b % n) as usize;
+        self.counts[i] += count as f64;
+        self.bytes[i] += bytes as f64;
+    }
+
+    /// Closed bins, oldest first (the head bin is still filling).
+    fn closed(&self, ring: &[f64]) -> Vec<f64> {
+        let n = ring.len() as u64;
+        (0..n).map(|k| ring[((self.head + 1 + k) % n) as usize]).take(ring.len() - 1).collect()
+    }
+
+    fn analyze(&mut self) {
+        let counts = self.closed(&self.counts);
+        if (counts.iter().sum::<f64>() as u64) < MIN_EVENTS {
+            self.arrivals = Periodicity::default();
+            self.sizes = Periodicity::default();
+            return;
+        }
+        let start = (self.head + 1).saturating_sub(self.counts.len() as u64);
+        self.arrivals = analyze(&counts, self.bin_ns, start);
+        self.sizes = analyze(&self.closed(&self.bytes), self.bin_ns, start);
+    }
+
+    /// Periodicity of arrival times.
+    pub fn arrivals(&self) -> Periodicity {
+        self.arrivals
+    }
+
+    /// Periodicity of transferred bytes.
+    pub fn sizes(&self) -> Periodicity {
+        self.sizes
+    }
+
+    /// The more periodic of the two.
+    pub fn periodicity(&self) -> Periodicity {
+        if self.sizes.score > self.arrivals.score {
+            self.sizes
+        } else {
+            self.arrivals
+        }
+    }
+}
+
+impl Default for SpectralSeries {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sources beating at one period and phase.
+#[derive(Clone, Debug)]
+pub struct PulseGroup<K> {
+    pub lag: usize,
+    pub dominant_hz: f64,
+    /// Phase coherence of the group (mean resultant length), [0, 1].
+    pub coherence: f64,
+    pub sources: Vec<K>,
+}
+
+/// Groups beaconing sources (`is_beacon`) by fundamental lag and keeps
+/// groups of at least `min_sources` whose phases agree (coherence ≥ 0.9):
+/// independent heartbeats land at random phases, a commanded botnet
+/// pulses together. Largest groups first.
+pub fn synchronized_pulses<K: Copy>(beats: &[(K, Periodicity)], min_sources: usize) -> Vec<PulseGroup<K>> {
+    let mut by_lag: std::collections::BTreeMap<usize, Vec<(K, Periodicity)>> = Default::default();
+    for &(key, p) in beats.iter().filter(|(_, p)| p.is_beacon() && p.lag > 0) {
+        by_lag.entry(p.lag).or_default().push((key, p));
+    }
+
+    let mut groups: Vec<PulseGroup<K>> = by_lag
+        .into_iter()
+        .filter(|(_, members)| members.len() >= min_sources.max(2))
+        .filter_map(|(lag, members)| {
+            let (c, s) = members
+                .iter()
+                .fold((0.0, 0.0), |(c, s), (_, p)| (c + p.phase.cos(), s + p.phase.sin()));
+            let coherence = (c * c + s * s).sqrt() / members.len() as f64;
+            (coherence >= 0.9).then(|| PulseGroup {
+                lag,
+                dominant_hz: members[0].1.dominant_hz,
+                coherence,
+                sources: members.iter().map(|(k, _)| *k).collect(),
+            })
+        })
+        .collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.sources.len()));
+    groups
+}
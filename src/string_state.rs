@@ -1,109 +1,208 @@
 // src/string_state.rs
 
-use getrandom::getrandom;
-use std::time::{Duration, Instant};
+use crate::compactifier::{Compactifier, CompactifierKind};
+use crate::fixed::{self, ONE, PHI, PI};
+use crate::normalize::Normalizer;
+use crate::salt::SaltManager;
+use std::fs;
+use std::io;
+use std::path::Path;
 
-// Fixed-point format: Q32.32 (32-bit integer + 32-bit fractional part)
-const FIXED_SCALE: i64 = 1i64 << 32;
-const PI_FIXED: i64 = 3373259426i64;         // ≈ π × 2³²
-const PHI_FIXED: i64 = 6941587532i64;        // ≈ φ × 2³² (golden ratio)
-const ONE_FIXED: i64 = FIXED_SCALE;         // Represents 1.0
+// Fixed-point format: Q32.32 (32-bit integer + 32-bit fractional part),
+// math from src/fixed.rs
+const FIXED_SCALE: i64 = ONE;
 
-// Nonce refresh interval (every 5 seconds — adjustable)
-const NONCE_EPOCH: Duration = Duration::from_secs(5);
+/// First line of a state snapshot; bumped when the format changes.
+pub const SNAPSHOT_HEADER: &str = "tiger_state 1";
+
+/// Environment variable naming the state snapshot file (main.rs, loader default).
+pub const STATE_FILE_ENV: &str = "TIGER_STATE_FILE";
 
 /// StringState: Core mathematical engine for data compactification
 /// ---------------------------------------------------------------
 /// Performs fixed-point folding of network packet attributes into a compact scalar,
 /// using irrational constants (π, φ) and dynamic nonce for security and diffusion.
+/// The nonce comes from the shared SaltManager, so it rotates in step with
+/// the XDP salts derived from the same master key and epoch.
 /// Designed for O(1) complexity and future eBPF/XDP compatibility.
+/// The fold itself is a pluggable `Compactifier` (src/compactifier.rs);
+/// this sine fold is the default.
+/// Raw features reach the fold through the `Normalizer`
+/// (src/normalize.rs), whose learned statistics are the state that
+/// `snapshot` / `restore` carry across restarts.
 pub struct StringState {
-    nonce: u64,
-    last_update: Instant,
+    salts: SaltManager,
+    compactifier: Box<dyn Compactifier>,
+    normalizer: Normalizer,
 }
 
 impl StringState {
-    /// Creates a new state with fresh nonce
+    /// Creates a new state with a node-local random master key
     pub fn new() -> Self {
-        let mut state = Self {
-            nonce: 0,
-            last_update: Instant::now().checked_sub(Duration::from_secs(100)).unwrap(),
-        };
-        state.refresh_nonce();
-        state
-    }
-
-    /// Ensures nonce is fresh (called before each compactification)
-    pub fn ensure_fresh_nonce(&mut self) {
-        if self.last_update.elapsed() >= NONCE_EPOCH {
-            self.refresh_nonce();
-        }
+        Self::with_salts(SaltManager::random())
     }
 
-    /// Refreshes nonce using hardware RNG (fallback to increment)
-    fn refresh_nonce(&mut self) {
-        let mut buf = [0u8; 8];
-        if getrandom(&mut buf).is_ok() {
-            self.nonce = u64::from_le_bytes(buf);
-        } else {
-            self.nonce = self.nonce.wrapping_add(1);
+    /// Creates a state bound to a (possibly fleet-shared) salt manager
+    pub fn with_salts(salts: SaltManager) -> Self {
+        Self {
+            salts,
+            compactifier: CompactifierKind::Sine.build(),
+            normalizer: Normalizer::default(),
         }
-        self.last_update = Instant::now();
     }
 
-    /// Taylor series approximation of sin(x) in fixed-point
-    /// Accurate enough for chaotic diffusion, very fast
-    fn sin_fixed(mut x: i64) -> i64 {
-        // Reduce to [-π, π]
-        x %= 2 * PI_FIXED;
-        if x > PI_FIXED {
-            x -= 2 * PI_FIXED;
-        }
-        if x < -PI_FIXED {
-            x += 2 * PI_FIXED;
+    /// Replaces the fold; the output stays a Q32.32 fraction in [0, 1)
+    pub fn with_compactifier(mut self, compactifier: Box<dyn Compactifier>) -> Self {
+        self.compactifier = compactifier;
+        self
+    }
+
+    pub fn compactifier(&self) -> &dyn Compactifier {
+        self.compactifier.as_ref()
+    }
+
+    /// Replaces the feature normalization (default: static schema scaling)
+    pub fn with_normalizer(mut self, normalizer: Normalizer) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    pub fn normalizer(&self) -> &Normalizer {
+        &self.normalizer
+    }
+
+    /// Raw features → Q32.32 fractions in [0, 1), the input contract of
+    /// `compactify`; learns the vector for the adaptive strategies
+    pub fn normalize(&mut self, raw: &[i64]) -> Vec<i64> {
+        self.normalizer.normalize(raw)
+    }
+
+    /// Same fractions as `normalize`, without learning the vector: for
+    /// vectors that stand in for a payload rather than carry one
+    pub fn score(&self, raw: &[i64]) -> Vec<i64> {
+        self.normalizer.score(raw)
+    }
+
+    /// Text snapshot of the learned state: header, the configuration it
+    /// was learned under (informational) and the normalizer statistics.
+    /// Salts are not in it — they follow from the master key and the clock
+    pub fn snapshot(&self) -> String {
+        format!(
+            "{}\ncompactifier {}\nnormalize {}\nepoch {}\n{}",
+            SNAPSHOT_HEADER,
+            self.compactifier.kind().as_str(),
+            self.normalizer.spec(),
+            self.salts.current().epoch,
+            self.normalizer.to_snapshot()
+        )
+    }
+
+    /// Takes the learned statistics from a `snapshot`; the configured
+    /// compactifier and strategies stay as they are
+    pub fn restore(&mut self, snapshot: &str) -> io::Result<()> {
+        if snapshot.lines().next() != Some(SNAPSHOT_HEADER) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("not a state snapshot (expected '{}')", SNAPSHOT_HEADER),
+            ));
         }
+        self.normalizer.restore(snapshot)
+    }
+
+    /// Writes the snapshot through a temporary file, so a crash never
+    /// leaves a torn one behind
+    pub fn save_snapshot(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.snapshot())?;
+        fs::rename(&tmp, path)
+    }
 
-        let x2 = (x * x) >> 32;
-        let x3 = (x2 * x) >> 32;
-        let x5 = (x3 * x2) >> 32;
-        let x7 = (x5 * x2) >> 32;
+    /// Restores from `path`; a missing file is a first start, not an error.
+    /// Returns whether a snapshot was found
+    pub fn load_snapshot(&mut self, path: &Path) -> io::Result<bool> {
+        match fs::read_to_string(path) {
+            Ok(text) => self.restore(&text).map(|_| true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
 
-        let term1 = x;
-        let term3 = (x3 * 716861901) >> 32;      // 1/6  approximated
-        let term5 = (x5 * 35791394) >> 32;       // 1/120 approximated
-        let term7 = (x7 * 1429388) >> 32;        // 1/5040 approximated
+    pub fn salts(&self) -> &SaltManager {
+        &self.salts
+    }
 
-        term1 - term3 + term5 - term7
+    /// Gives access to pressure-driven rotation
+    pub fn salts_mut(&mut self) -> &mut SaltManager {
+        &mut self.salts
     }
 
     /// Main compactification function
-    /// Input: 10 normalized attributes in fixed-point format [0, 1.0)
+    /// Input: N attributes (any N; the pipeline's schema vector is `Attrs`),
+    /// normalized to [0, 1.0) fixed-point by `normalize`
     /// Output: compact scalar in [0, 1.0) as i64
-    pub fn compactify(&mut self, attributes: &[i64; 10]) -> i64 {
-        self.ensure_fresh_nonce();
+    pub fn compactify<const N: usize>(&mut self, attributes: &[i64; N]) -> i64 {
+        self.compactify_slice(attributes)
+    }
+
+    /// Same as `compactify` for vectors whose length is only known at run
+    /// time — extractors can add dimensions without touching the core.
+    /// Folds under the current epoch and never rotates it: rotation belongs
+    /// to the loop that also publishes the salts to the kernel
+    pub fn compactify_slice(&mut self, attributes: &[i64]) -> i64 {
+        self.compactifier.compactify(attributes, &self.salts.current())
+    }
+
+    /// Weight and diffusion offset of index `i` under `nonce`
+    /// Weight is a Q32.32 factor in [φ/2, 3φ/2), offset a Q32.32 fraction
+    /// in [0, 1); both are SplitMix64 outputs of (nonce, i), so every index
+    /// of every length has its own pair and they rotate with the nonce
+    pub fn index_key(nonce: u64, i: usize) -> (i64, i64) {
+        let m = splitmix64(nonce ^ (i as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let weight = PHI / 2 + fixed::saturating_mul((m >> 32) as i64, PHI);
+        let offset = (splitmix64(m) >> 32) as i64;
+        (weight, offset)
+    }
 
+    /// The sine fold under `nonce` (`SineFold` in src/compactifier.rs)
+    pub fn sine_fold(attributes: &[i64], nonce: u64) -> i64 {
         let mut sum: i64 = 0;
 
         for (i, &a_i) in attributes.iter().enumerate() {
-            let a_nonce = a_i.wrapping_add(self.nonce as i64);
-            let scaled = (a_nonce * PI_FIXED) >> 32;
-            let sin_val = Self::sin_fixed(scaled);
-            let contrib = (sin_val * PHI_FIXED) >> 32;
-
-            // Additional diffusion using index
-            let idx_offset = ((i as i64) * 123456789i64) << 16;
-            sum = sum.wrapping_add(contrib.wrapping_add(idx_offset));
+            let (weight, offset) = Self::index_key(nonce, i);
+            let a_nonce = a_i.wrapping_add(nonce as i64);
+            // Wraps on purpose: the nonce spreads the angle over the whole
+            // circle, and the sine only sees it modulo 2π anyway
+            let scaled = fixed::wrapping_mul(a_nonce, PI);
+            let sin_val = fixed::sin(scaled);
+            let contrib = fixed::saturating_mul(sin_val, weight);
+
+            // Additional diffusion using the keyed index offset
+            sum = sum.wrapping_add(contrib.wrapping_add(offset));
         }
 
         // Return fractional part (mod 1)
-        sum.rem_euclid(ONE_FIXED)
+        sum.rem_euclid(ONE)
     }
 
     /// Helper: convert fixed-point back to f64 for debugging/logging
     pub fn to_float(value: i64) -> f64 {
-        value as f64 / FIXED_SCALE as f64
+        fixed::to_f64(value)
+    }
+}
+
+impl Default for StringState {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+/// SplitMix64 finalizer: cheap, bijective, full avalanche on one word
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
 // Public constants for use in main.rs or other modules
 pub const FIXED_SCALE_F64: f64 = FIXED_SCALE as f64;
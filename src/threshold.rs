@@ -0,0 +1,248 @@
+// src/threshold.rs
+
+//! Adaptive attack threshold
+//! -------------------------
+//! The attack decision compares the Atomic threat probability with a
+//! threshold that moves instead of the fixed `ATTACK_THRESHOLD`:
+//!
+//! * **alert rate** — an EWMA of the share of decisions that alerted is
+//!   steered toward `target_alert_rate`: too many alerts raise the base
+//!   threshold, too few lower it;
+//! * **false positives** — every `report_false_positive` adds to a
+//!   decaying pressure that raises the base threshold;
+//! * **Lumis** — the effective threshold is the base scaled by
+//!   `LumisCore::dynamic_threshold() / φ`, so a restless core (high
+//!   entropy) breathes it by up to ±0.1/φ ≈ ±6 %.
+//!
+//! One update moves the base by at most `max_step`, and both base and
+//! effective threshold stay within `[min, max]`, so no feedback burst can
+//! switch detection off or turn everything into an attack. Moves of at
+//! least `LOG_RESOLUTION` are recorded in a bounded change log.
+
+use crate::lumis::{LumisCore, PHI};
+use log::info;
+use std::collections::VecDeque;
+
+/// Changes kept in the log.
+pub const LOG_CAPACITY: usize = 256;
+
+/// Smallest move of the effective threshold that is logged.
+pub const LOG_RESOLUTION: f64 = 0.005;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThresholdConfig {
+    /// Starting base threshold.
+    pub initial: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Share of decisions that should alert.
+    pub target_alert_rate: f64,
+    /// Largest move of the base per update.
+    pub max_step: f64,
+    /// Step per unit of alert-rate error.
+    pub rate_gain: f64,
+    /// Step per unit of false-positive pressure.
+    pub fp_gain: f64,
+    /// EWMA weight of one decision (alert rate and false-positive decay).
+    pub alpha: f64,
+}
+
+impl Default for ThresholdConfig {
+    fn default() -> Self {
+        Self {
+            initial: crate::brain::ATTACK_THRESHOLD,
+            min: 0.6,
+            max: 0.98,
+            target_alert_rate: 0.01,
+            max_step: 0.002,
+            rate_gain: 0.05,
+            fp_gain: 0.01,
+            alpha: 1.0 / 256.0,
+        }
+    }
+}
+
+/// What dominated a logged move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeReason {
+    AlertRate,
+    FalsePositive,
+    Lumis,
+}
+
+impl ChangeReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeReason::AlertRate => "alert_rate",
+            ChangeReason::FalsePositive => "false_positive",
+            ChangeReason::Lumis => "lumis",
+        }
+    }
+}
+
+/// One entry of the change log.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThresholdChange {
+    /// Decision count at the change.
+    pub decision: u64,
+    pub from: f64,
+    pub to: f64,
+    pub reason: ChangeReason,
+    pub alert_rate: f64,
+    pub fp_pressure: f64,
+    pub entropy: f64,
+}
+
+impl ThresholdChange {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"decision\":{},\"from\":{:.4},\"to\":{:.4},\"reason\":\"{}\",\"alert_rate\":{:.4},\"fp_pressure\":{:.4},\"entropy\":{:.4}}}",
+            self.decision,
+            self.from,
+            self.to,
+            self.reason.as_str(),
+            self.alert_rate,
+            self.fp_pressure,
+            self.entropy
+        )
+    }
+}
+
+/// Threshold controller of one brain.
+#[derive(Clone, Debug)]
+pub struct ThresholdController {
+    config: ThresholdConfig,
+    base: f64,
+    effective: f64,
+    alert_rate: f64,
+    fp_pressure: f64,
+    decisions: u64,
+    /// Effective threshold at the last logged change.
+    logged: f64,
+    log: VecDeque<ThresholdChange>,
+}
+
+impl ThresholdController {
+    pub fn new(config: ThresholdConfig) -> Self {
+        let initial = config.initial.clamp(config.min, config.max);
+        Self {
+            config,
+            base: initial,
+            effective: initial,
+            // Start on target: no pull before there is evidence
+            alert_rate: config.target_alert_rate,
+            fp_pressure: 0.0,
+            decisions: 0,
+            logged: initial,
+            log: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ThresholdConfig {
+        &self.config
+    }
+
+    /// Threshold the next decision is made with.
+    pub fn threshold(&self) -> f64 {
+        self.effective
+    }
+
+    /// Threshold before the Lumis modulation.
+    pub fn base(&self) -> f64 {
+        self.base
+    }
+
+    pub fn alert_rate(&self) -> f64 {
+        self.alert_rate
+    }
+
+    pub fn fp_pressure(&self) -> f64 {
+        self.fp_pressure
+    }
+
+    /// Oldest first.
+    pub fn changes(&self) -> impl Iterator<Item = &ThresholdChange> {
+        self.log.iter()
+    }
+
+    /// Current base under `lumis`' modulation — the threshold a projected
+    /// Lumis state would decide with (src/simul.rs).
+    pub fn threshold_for(&self, lumis: &LumisCore) -> f64 {
+        (self.base * lumis.dynamic_threshold() / PHI).clamp(self.config.min, self.config.max)
+    }
+
+    /// Feedback: an alert turned out to be benign.
+    pub fn report_false_positive(&mut self) {
+        self.fp_pressure += 1.0;
+    }
+
+    /// Takes one decision's outcome and Lumis' current state; moves the
+    /// base by at most `max_step` and recomputes the effective threshold.
+    pub fn update(&mut self, alerted: bool, lumis: &LumisCore) {
+        let c = self.config;
+        self.decisions += 1;
+        let hit = if alerted { 1.0 } else { 0.0 };
+        self.alert_rate += c.alpha * (hit - self.alert_rate);
+
+        let rate_term = c.rate_gain * (self.alert_rate - c.target_alert_rate);
+        let fp_term = c.fp_gain * self.fp_pressure;
+        self.fp_pressure *= 1.0 - c.alpha;
+        let step = (rate_term + fp_term).clamp(-c.max_step, c.max_step);
+        self.base = (self.base + step).clamp(c.min, c.max);
+
+        self.effective = self.threshold_for(lumis);
+
+        if (self.effective - self.logged).abs() >= LOG_RESOLUTION {
+            let drift = self.effective - self.logged;
+            let base_drift = self.base - self.logged;
+            let reason = if base_drift.abs() < (drift - base_drift).abs() {
+                ChangeReason::Lumis
+            } else if fp_term > rate_term.abs() {
+                ChangeReason::FalsePositive
+            } else {
+                ChangeReason::AlertRate
+            };
+            let change = ThresholdChange {
+                decision: self.decisions,
+                from: self.logged,
+                to: self.effective,
+                reason,
+                alert_rate: self.alert_rate,
+                fp_pressure: self.fp_pressure,
+                entropy: lumis.entropy_level(),
+            };
+            info!(
+                "THRESHOLD: {:.3} → {:.3} ({}, alert rate {:.4})",
+                change.from,
+                change.to,
+                reason.as_str(),
+                change.alert_rate
+            );
+            if self.log.len() == LOG_CAPACITY {
+                self.log.pop_front();
+            }
+            self.log.push_back(change);
+            self.logged = self.effective;
+        }
+    }
+
+    /// Dashboard fragment: current values and the change log.
+    pub fn to_json(&self) -> String {
+        let changes: Vec<String> = self.log.iter().map(ThresholdChange::to_json).collect();
+        format!(
+            "{{\"threshold\":{:.4},\"base\":{:.4},\"alert_rate\":{:.4},\"fp_pressure\":{:.4},\"decisions\":{},\"changes\":[{}]}}",
+            self.effective,
+            self.base,
+            self.alert_rate,
+            self.fp_pressure,
+            self.decisions,
+            changes.join(",")
+        )
+    }
+}
+
+impl Default for ThresholdController {
+    fn default() -> Self {
+        Self::new(ThresholdConfig::default())
+    }
+}
@@ -0,0 +1,330 @@
+// src/timing.rs
+
+//! Arrival timing: multi-resolution time buckets per source
+//! --------------------------------------------------------
+//! The kernel mixes `bpf_ktime_get_ns() >> TIME_BUCKET_SHIFT` (~4 ms) into
+//! the fold; here the same clock (CLOCK_MONOTONIC) and the same finest
+//! bucket are kept per source, next to 1 s and 1 min buckets:
+//!
+//! | resolution | bucket width        | ring      | window |
+//! |------------|---------------------|-----------|--------|
+//! | fine       | 2^22 ns (≈ 4.19 ms) | 240 slots | ≈ 1 s  |
+//! | second     | 1 s                 | 60 slots  | 1 min  |
+//! | minute     | 1 min               | 60 slots  | 1 h    |
+//!
+//! Each ring yields an arrival rate (packets per second over the part of
+//! the window the source has been seen in) and the Fano factor of its
+//! bucket counts (variance / mean: ≈ 1 for Poisson arrivals, > 1 for
+//! bursts, < 1 for paced traffic). Both fold into one arrival pressure
+//! in [0, 1] that the brain adds to AtomicCore impact and Lumis pressure.
+//! Next to the buckets, the inter-arrival intervals feed the HRV-style
+//! jitter metrics of src/jitter.rs, and arrivals and bytes the beacon
+//! detector of src/spectrum.rs.
+
+use crate::jitter::{HrvMetrics, JitterTracker};
+use crate::lumis::PHI_INVERSE;
+use crate::schema::TIME_BUCKET_SHIFT;
+use crate::spectrum::{Periodicity, SpectralSeries};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+pub const RESOLUTIONS: usize = 3;
+
+/// Index of the kernel-compatible ~4 ms resolution.
+pub const FINE: usize = 0;
+pub const SECOND: usize = 1;
+pub const MINUTE: usize = 2;
+
+/// `(bucket width in ns, slots)` per resolution.
+pub const RESOLUTION_TABLE: [(u64, usize); RESOLUTIONS] = [
+    (1 << TIME_BUCKET_SHIFT, 240),
+    (1_000_000_000, 60),
+    (60_000_000_000, 60),
+];
+
+/// Per-source rate at which the rate pressure saturates.
+pub const RATE_SATURATION_PPS: f64 = 10_000.0;
+
+/// Scale of the arrival pressure when added to impact energy.
+pub const ARRIVAL_IMPACT_GAIN: f64 = 0.25;
+
+/// CLOCK_MONOTONIC in ns: the clock behind `bpf_ktime_get_ns()`, so
+/// userspace and kernel timestamps land in the same buckets.
+pub fn monotonic_ns() -> u64 {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: валідний вказівник на timespec; CLOCK_MONOTONIC є завжди
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
+
+/// The kernel's ~4 ms bucket of `ts_ns`.
+pub fn fine_bucket(ts_ns: u64) -> u64 {
+    ts_ns >> TIME_BUCKET_SHIFT
+}
+
+/// Ring of per-bucket packet counts at one resolution.
+#[derive(Clone, Debug)]
+pub struct BucketRing {
+    width_ns: u64,
+    counts: Vec<u32>,
+    /// Bucket id of the newest slot.
+    head: u64,
+    /// Bucket id of the first packet seen.
+    first: u64,
+    seen: bool,
+}
+
+impl BucketRing {
+    pub fn new(width_ns: u64, slots: usize) -> Self {
+        Self {
+            width_ns: width_ns.max(1),
+            counts: vec![0; slots.max(2)],
+            head: 0,
+            first: 0,
+            seen: false,
+        }
+    }
+
+    fn bucket(&self, ts_ns: u64) -> u64 {
+        ts_ns / self.width_ns
+    }
+
+    /// Counts one arrival. Arrivals older than the window are dropped.
+    pub fn record(&mut self, ts_ns: u64) {
+        self.record_n(ts_ns, 1);
+    }
+
+    /// Counts `count` arrivals in the bucket of `ts_ns`.
+    pub fn record_n(&mut self, ts_ns: u64, count: u32) {
+        let b = self.bucket(ts_ns);
+        let n = self.counts.len() as u64;
+        if !self.seen {
+            self.seen = true;
+            self.head = b;
+            self.first = b;
+        }
+        if b > self.head {
+            // Обнуляємо слоти, які пройшли без пакетів
+            for id in (self.head + 1..=b).take(n as usize) {
+                self.counts[(id % n) as usize] = 0;
+            }
+            self.head = b;
+        } else if self.head - b >= n {
+            return;
+        }
+        let slot = &mut self.counts[(b % n) as usize];
+        *slot = slot.saturating_add(count);
+    }
+
+    /// Buckets that carry data: from the first arrival up to the head,
+    /// at most the whole ring.
+    fn span(&self) -> usize {
+        if !self.seen {
+            return 0;
+        }
+        ((self.head - self.first + 1) as usize).min(self.counts.len())
+    }
+
+    fn live(&self) -> impl Iterator<Item = f64> + '_ {
+        let n = self.counts.len() as u64;
+        (0..self.span() as u64).map(move |k| self.counts[((self.head - k) % n) as usize] as f64)
+    }
+
+    /// Packets per second over the live part of the window.
+    pub fn rate_pps(&self) -> f64 {
+        let span = self.span();
+        if span == 0 {
+            return 0.0;
+        }
+        self.live().sum::<f64>() / (span as f64 * self.width_ns as f64 / 1e9)
+    }
+
+    /// Variance / mean of the live bucket counts; 1.0 until there are
+    /// two buckets to compare.
+    pub fn fano(&self) -> f64 {
+        let span = self.span();
+        if span < 2 {
+            return 1.0;
+        }
+        let mean = self.live().sum::<f64>() / span as f64;
+        if mean <= 0.0 {
+            return 1.0;
+        }
+        let var = self.live().map(|c| (c - mean).powi(2)).sum::<f64>() / span as f64;
+        var / mean
+    }
+}
+
+/// Arrival features of one source at one instant.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Arrival {
+    /// Kernel-compatible ~4 ms bucket of the last arrival.
+    pub bucket: u64,
+    pub rate_pps: [f64; RESOLUTIONS],
+    pub fano: [f64; RESOLUTIONS],
+    /// Inter-arrival jitter of the observed packets.
+    pub hrv: HrvMetrics,
+    /// Beaconing of arrivals or bytes (last spectral analysis).
+    pub periodicity: Periodicity,
+}
+
+impl Default for Arrival {
+    /// No timing information: neutral rate and burstiness.
+    fn default() -> Self {
+        Self {
+            bucket: 0,
+            rate_pps: [0.0; RESOLUTIONS],
+            fano: [1.0; RESOLUTIONS],
+            hrv: HrvMetrics::default(),
+            periodicity: Periodicity::default(),
+        }
+    }
+}
+
+impl Arrival {
+    /// Log-scaled 1 s rate, saturating at `RATE_SATURATION_PPS`.
+    pub fn rate_pressure(&self) -> f64 {
+        (self.rate_pps[SECOND].ln_1p() / RATE_SATURATION_PPS.ln_1p()).clamp(0.0, 1.0)
+    }
+
+    /// Burstiness in [0, 1): `(F − 1) / (F + 1)` of the burstiest of the
+    /// two finer resolutions; Poisson or paced arrivals give 0.
+    pub fn burstiness(&self) -> f64 {
+        let f = self.fano[FINE].max(self.fano[SECOND]);
+        ((f - 1.0) / (f + 1.0)).max(0.0)
+    }
+
+    /// Golden-ratio blend of rate and burst pressure, in [0, 1].
+    pub fn pressure(&self) -> f64 {
+        self.rate_pressure() * PHI_INVERSE + self.burstiness() * (1.0 - PHI_INVERSE)
+    }
+}
+
+/// Fine, second and minute buckets of one source, plus its jitter and
+/// spectral series.
+#[derive(Clone, Debug)]
+pub struct TimeBuckets {
+    rings: [BucketRing; RESOLUTIONS],
+    jitter: JitterTracker,
+    spectrum: SpectralSeries,
+    last_ns: u64,
+}
+
+impl TimeBuckets {
+    pub fn new() -> Self {
+        Self {
+            rings: RESOLUTION_TABLE.map(|(width, slots)| BucketRing::new(width, slots)),
+            jitter: JitterTracker::new(),
+            spectrum: SpectralSeries::new(),
+            last_ns: 0,
+        }
+    }
+
+    /// Counts an arrival of unknown size at `ts_ns` and returns the
+    /// updated features.
+    pub fn record(&mut self, ts_ns: u64) -> Arrival {
+        self.record_sampled(ts_ns, 1, 0)
+    }
+
+    /// One observed packet of `bytes` that stands for `weight` arrivals
+    /// (a 1-in-N sample): weighted in the buckets, one interval for the
+    /// jitter.
+    pub fn record_sampled(&mut self, ts_ns: u64, weight: u32, bytes: u64) -> Arrival {
+        self.jitter.observe(ts_ns);
+        self.record_n(ts_ns, weight, bytes.saturating_mul(weight as u64))
+    }
+
+    /// Counts `count` arrivals of `bytes` in total at `ts_ns` without an
+    /// interval: a counter delta covering all packets since the last read.
+    pub fn record_n(&mut self, ts_ns: u64, count: u32, bytes: u64) -> Arrival {
+        for ring in &mut self.rings {
+            ring.record_n(ts_ns, count);
+        }
+        self.spectrum.record(ts_ns, count, bytes);
+        self.last_ns = self.last_ns.max(ts_ns);
+        self.arrival()
+    }
+
+    pub fn arrival(&self) -> Arrival {
+        Arrival {
+            bucket: fine_bucket(self.last_ns),
+            rate_pps: [0, 1, 2].map(|i| self.rings[i].rate_pps()),
+            fano: [0, 1, 2].map(|i| self.rings[i].fano()),
+            hrv: self.jitter.metrics(),
+            periodicity: self.spectrum.periodicity(),
+        }
+    }
+
+    /// Jitter metrics alone (cheaper than a full `arrival()`).
+    pub fn hrv(&self) -> HrvMetrics {
+        self.jitter.metrics()
+    }
+
+    /// Last spectral analysis (cached; no FFT here).
+    pub fn periodicity(&self) -> Periodicity {
+        self.spectrum.periodicity()
+    }
+
+    pub fn spectrum(&self) -> &SpectralSeries {
+        &self.spectrum
+    }
+
+    pub fn ring(&self, resolution: usize) -> &BucketRing {
+        &self.rings[resolution]
+    }
+
+    pub fn last_ns(&self) -> u64 {
+        self.last_ns
+    }
+}
+
+impl Default for TimeBuckets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-source `TimeBuckets` for callers that share one brain across
+/// sources (the UDP interceptor in main.rs). Bounded: when full, sources
+/// idle for `idle_ns` are forgotten, and if none are, a new source is
+/// measured on its own arrival only.
+pub struct SourceClocks<K> {
+    sources: HashMap<K, TimeBuckets>,
+    pub max_sources: usize,
+    pub idle_ns: u64,
+}
+
+impl<K: Hash + Eq + Copy> SourceClocks<K> {
+    pub fn new(max_sources: usize) -> Self {
+        Self {
+            sources: HashMap::new(),
+            max_sources,
+            idle_ns: 300_000_000_000,
+        }
+    }
+
+    /// Counts one packet of `bytes` from `source`.
+    pub fn record(&mut self, source: K, ts_ns: u64, bytes: u64) -> Arrival {
+        if !self.sources.contains_key(&source) && self.sources.len() >= self.max_sources {
+            let idle_ns = self.idle_ns;
+            self.sources.retain(|_, b| ts_ns.saturating_sub(b.last_ns()) < idle_ns);
+            if self.sources.len() >= self.max_sources {
+                return TimeBuckets::new().record_sampled(ts_ns, 1, bytes);
+            }
+        }
+        self.sources.entry(source).or_default().record_sampled(ts_ns, 1, bytes)
+    }
+
+    /// Sources and their last spectral analysis, for pulse grouping.
+    pub fn periodicities(&self) -> Vec<(K, Periodicity)> {
+        self.sources.iter().map(|(&k, b)| (k, b.periodicity())).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+}
@@ -0,0 +1,343 @@
+// src/xdp_emulator.rs
+
+//! XDP Emulator: userspace twin of src/kernel/tiger_delta_xdp.c
+//! ------------------------------------------------------------
+//! Repeats the kernel parsing (Ethernet, 802.1Q/QinQ, IPv4/IPv6, TCP/UDP),
+//! the feature vector and the folding manifold bit-for-bit, so the fold can
+//! be tested and audited without loading eBPF.
+//!
+//! The kernel keeps addresses, lengths and ports as raw network-order loads,
+//! so the emulator reads them with `from_ne_bytes` — exactly what a load
+//! of `__be32`/`__be16` produces on the host running the program.
+//!
+//! The vector layout itself is not restated here: both sides pack it from
+//! the table in src/schema.rs.
+
+use crate::fixed;
+use crate::schema::{self, kf, RawPktMeta, KERNEL_FIELD_COUNT};
+use crate::sketch::{is_candidate_mark, CountMinSketch};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const ETH_HLEN: usize = 14;
+const VLAN_HLEN: usize = 4;
+const VLAN_MAX_DEPTH: usize = 2;
+
+const ETH_P_IP: u16 = 0x0800;
+const ETH_P_IPV6: u16 = 0x86DD;
+const ETH_P_8021Q: u16 = 0x8100;
+const ETH_P_8021AD: u16 = 0x88A8;
+
+pub const IPPROTO_TCP: u8 = 6;
+pub const IPPROTO_UDP: u8 = 17;
+
+pub use crate::schema::FEATURE_COUNT;
+
+/// Fallback salts used by the kernel when `config_map` is unavailable.
+pub const DEFAULT_PHI_SALT: u64 = 0x6A09E667F3BCC909;
+pub const DEFAULT_PI_SALT: u64 = 0x243F6A8885A308D3;
+
+/// Probability encoding shared with `policy_map` / `source_policy`:
+/// a u32 fraction of 2^32, with `DROP_ALWAYS` meaning unconditional drop.
+pub const DROP_ALWAYS: u32 = u32::MAX;
+
+/// Converts a probability in [0, 1] to the kernel encoding.
+pub fn prob_to_fixed(p: f64) -> u32 {
+    if p >= 1.0 {
+        DROP_ALWAYS
+    } else if p <= 0.0 || p.is_nan() {
+        0
+    } else {
+        (p * 4_294_967_296.0) as u32
+    }
+}
+
+/// Mirror of `struct pkt_meta`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PacketMeta {
+    pub saddr: u32,
+    pub daddr: u32,
+    pub len: u16,
+    pub protocol: u8,
+    pub is_v6: bool,
+    pub vlan: u16,
+    pub sport: u16,
+    pub dport: u16,
+    pub tcp_flags: u8,
+}
+
+impl From<RawPktMeta> for PacketMeta {
+    fn from(r: RawPktMeta) -> Self {
+        PacketMeta {
+            saddr: r.saddr,
+            daddr: r.daddr,
+            len: r.len,
+            protocol: r.protocol,
+            is_v6: r.is_v6 != 0,
+            vlan: r.vlan,
+            sport: r.sport,
+            dport: r.dport,
+            tcp_flags: r.tcp_flags,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Drop,
+}
+
+fn be16(frame: &[u8], off: usize) -> Option<u16> {
+    frame.get(off..off + 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn raw16(frame: &[u8], off: usize) -> Option<u16> {
+    frame.get(off..off + 2).map(|b| u16::from_ne_bytes([b[0], b[1]]))
+}
+
+fn raw32(frame: &[u8], off: usize) -> Option<u32> {
+    frame
+        .get(off..off + 4)
+        .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn fold_in6(frame: &[u8], off: usize) -> Option<u32> {
+    let mut acc = 0u32;
+    for word in 0..4 {
+        acc ^= raw32(frame, off + word * 4)?;
+    }
+    Some(acc)
+}
+
+/// Fills ports/flags and returns where the L4 payload starts.
+fn parse_l4(frame: &[u8], off: usize, m: &mut PacketMeta) -> Option<usize> {
+    match m.protocol {
+        IPPROTO_TCP if frame.len() >= off + 20 => {
+            m.sport = raw16(frame, off).unwrap_or(0);
+            m.dport = raw16(frame, off + 2).unwrap_or(0);
+            m.tcp_flags = frame[off + 13];
+            Some(off + ((frame[off + 12] >> 4) as usize * 4).max(20))
+        }
+        IPPROTO_UDP if frame.len() >= off + 8 => {
+            m.sport = raw16(frame, off).unwrap_or(0);
+            m.dport = raw16(frame, off + 2).unwrap_or(0);
+            Some(off + 8)
+        }
+        _ => None,
+    }
+}
+
+/// A parsed frame with what the kernel does not keep: the full source
+/// address and the L4 payload (empty when there is none).
+pub struct ParsedFrame<'a> {
+    pub meta: PacketMeta,
+    pub src: IpAddr,
+    pub payload: &'a [u8],
+}
+
+/// Parses an Ethernet frame the way the XDP program does.
+/// Returns None for frames the kernel passes without folding
+/// (truncated headers, non-IP ethertypes).
+pub fn parse_frame(frame: &[u8]) -> Option<PacketMeta> {
+    parse_frame_payload(frame).map(|p| p.meta)
+}
+
+/// `parse_frame` plus source address and payload, for the AF_XDP inspector.
+pub fn parse_frame_payload(frame: &[u8]) -> Option<ParsedFrame<'_>> {
+    let mut m = PacketMeta::default();
+    let mut cursor = ETH_HLEN;
+    let mut proto = be16(frame, 12)?;
+
+    for _ in 0..VLAN_MAX_DEPTH {
+        if proto != ETH_P_8021Q && proto != ETH_P_8021AD {
+            break;
+        }
+        m.vlan = be16(frame, cursor)? & 0x0FFF;
+        proto = be16(frame, cursor + 2)?;
+        cursor += VLAN_HLEN;
+    }
+
+    let (src, payload_off) = match proto {
+        ETH_P_IP => {
+            if frame.len() < cursor + 20 {
+                return None;
+            }
+            let ihl = (frame[cursor] & 0x0F) as usize;
+            let frag_off = be16(frame, cursor + 6)?;
+
+            m.saddr = raw32(frame, cursor + 12)?;
+            m.daddr = raw32(frame, cursor + 16)?;
+            m.len = raw16(frame, cursor + 2)?;
+            m.protocol = frame[cursor + 9];
+
+            let src: [u8; 4] = frame[cursor + 12..cursor + 16].try_into().ok()?;
+            let payload_off = if ihl >= 5 && frag_off & 0x1FFF == 0 {
+                parse_l4(frame, cursor + ihl * 4, &mut m)
+            } else {
+                None
+            };
+            (IpAddr::V4(Ipv4Addr::from(src)), payload_off)
+        }
+        ETH_P_IPV6 => {
+            if frame.len() < cursor + 40 {
+                return None;
+            }
+            m.saddr = fold_in6(frame, cursor + 8)?;
+            m.daddr = fold_in6(frame, cursor + 24)?;
+            m.len = raw16(frame, cursor + 4)?;
+            m.protocol = frame[cursor + 6];
+            m.is_v6 = true;
+
+            let src: [u8; 16] = frame[cursor + 8..cursor + 24].try_into().ok()?;
+            (IpAddr::V6(Ipv6Addr::from(src)), parse_l4(frame, cursor + 40, &mut m))
+        }
+        _ => return None,
+    };
+
+    let payload = payload_off.and_then(|off| frame.get(off..)).unwrap_or(&[]);
+    Some(ParsedFrame { meta: m, src, payload })
+}
+
+/// Schema field values of one packet, in `kf::*` order — the `f[]`
+/// the kernel hands to `tiger_pack_features()`.
+pub fn kernel_fields(m: &PacketMeta, rx_queue: u32, time_ns: u64) -> [u64; KERNEL_FIELD_COUNT] {
+    let mut f = [0u64; KERNEL_FIELD_COUNT];
+    f[kf::SADDR] = m.saddr as u64;
+    f[kf::DADDR] = m.daddr as u64;
+    f[kf::IS_V6] = m.is_v6 as u64;
+    f[kf::PROTOCOL] = m.protocol as u64;
+    f[kf::LEN] = m.len as u64;
+    f[kf::VLAN] = m.vlan as u64;
+    f[kf::QUEUE] = rx_queue as u64;
+    f[kf::TIME_BUCKET] = time_ns >> schema::TIME_BUCKET_SHIFT;
+    f[kf::SPORT] = m.sport as u64;
+    f[kf::DPORT] = m.dport as u64;
+    f[kf::TCP_FLAGS] = m.tcp_flags as u64;
+    f
+}
+
+/// Builds `v[0..FEATURE_COUNT]` exactly as the kernel does.
+/// `time_ns` plays the role of `bpf_ktime_get_ns()`.
+pub fn feature_vector(m: &PacketMeta, rx_queue: u32, time_ns: u64) -> [u64; FEATURE_COUNT] {
+    schema::pack(&kernel_fields(m, rx_queue, time_ns))
+}
+
+/// The folding manifold: `acc = (acc + rotl(v[i] ^ pi, 13 + i)) * phi`.
+pub fn fold(v: &[u64; FEATURE_COUNT], phi: u64, pi: u64) -> u64 {
+    v.iter().enumerate().fold(0u64, |acc, (i, &x)| {
+        acc.wrapping_add((x ^ pi).rotate_left(13 + i as u32))
+            .wrapping_mul(phi)
+    })
+}
+
+/// One CPU's worth of XDP state: salts, EMA resonance and policy.
+/// `bpf_get_prandom_u32()` is stood in for by a seeded xorshift32,
+/// so drop ratios are reproducible.
+pub struct XdpEmulator {
+    pub phi: u64,
+    pub pi: u64,
+    pub state: u64,
+    pub enforce: bool,
+    pub drop_prob: u32,
+    pub source_policy: HashMap<u32, u32>,
+    /// Mirror of one CPU's `cms_rows`.
+    pub sketch: CountMinSketch,
+    /// Mirror of `cms_candidates` (keys only).
+    pub candidates: HashSet<u32>,
+    prng: u32,
+}
+
+impl XdpEmulator {
+    pub fn new() -> Self {
+        Self {
+            phi: DEFAULT_PHI_SALT,
+            pi: DEFAULT_PI_SALT,
+            state: 0,
+            enforce: false,
+            drop_prob: 0,
+            source_policy: HashMap::new(),
+            sketch: CountMinSketch::new(),
+            candidates: HashSet::new(),
+            prng: 0x9E37_79B9,
+        }
+    }
+
+    pub fn seed_prng(&mut self, seed: u32) {
+        self.prng = seed.max(1);
+    }
+
+    fn prandom_u32(&mut self) -> u32 {
+        let mut x = self.prng;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.prng = x;
+        x
+    }
+
+    /// Mirror of `policy_verdict()`.
+    fn policy_verdict(&mut self, saddr: u32) -> Verdict {
+        if !self.enforce {
+            return Verdict::Pass;
+        }
+        let prob = self.source_policy.get(&saddr).copied().unwrap_or(self.drop_prob);
+        if prob == 0 {
+            return Verdict::Pass;
+        }
+        if prob == DROP_ALWAYS || self.prandom_u32() < prob {
+            Verdict::Drop
+        } else {
+            Verdict::Pass
+        }
+    }
+
+    /// This CPU's `resonance_state` as a Q32.32 fraction of one: the top
+    /// half of the u64 EMA `(*state + acc) >> 1`, the value the loader reads.
+    pub fn resonance_level(&self) -> i64 {
+        (self.state >> 32) as i64
+    }
+
+    /// Drop chance `policy_verdict()` applies to `saddr`, in Q32.32:
+    /// `bpf_get_prandom_u32() < prob` drops with probability prob / 2^32,
+    /// so the u32 encoding already is the fraction; DROP_ALWAYS is one.
+    pub fn drop_level(&self, saddr: u32) -> i64 {
+        if !self.enforce {
+            return 0;
+        }
+        match self.source_policy.get(&saddr).copied().unwrap_or(self.drop_prob) {
+            DROP_ALWAYS => fixed::ONE,
+            prob => prob as i64,
+        }
+    }
+
+    pub fn with_salts(phi: u64, pi: u64) -> Self {
+        Self { phi, pi, ..Self::new() }
+    }
+
+    /// Processes one frame; mirrors the kernel verdict path
+    /// (CIDR lists excluded).
+    pub fn process(&mut self, frame: &[u8], rx_queue: u32, time_ns: u64) -> Verdict {
+        let m = match parse_frame(frame) {
+            Some(m) => m,
+            None => return Verdict::Pass,
+        };
+
+        if is_candidate_mark(self.sketch.update(m.saddr)) {
+            self.candidates.insert(m.saddr);
+        }
+
+        let acc = fold(&feature_vector(&m, rx_queue, time_ns), self.phi, self.pi);
+
+        // Same 64-bit wrap as `(*state + acc) >> 1` in the kernel
+        self.state = self.state.wrapping_add(acc) >> 1;
+
+        self.policy_verdict(m.saddr)
+    }
+}
+
+impl Default for XdpEmulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,122 @@
+// Per-CPU aggregation: mean and median stay exact near u64::MAX, even and
+// single-CPU maps get a defined median, and the windows roll over so the
+// trend follows the recent level.
+
+#[path = "../src/bin/tiger_loader/aggregate.rs"]
+mod aggregate;
+
+use aggregate::{to_level, Aggregator, CpuSnapshot, RollingWindow, Trend};
+use std::time::{Duration, Instant};
+
+/// Raw resonance for a level in [0, 1)
+fn value(level: f64) -> u64 {
+    (level * 18_446_744_073_709_551_616.0) as u64
+}
+
+fn secs(s: u64) -> Duration {
+    Duration::from_secs(s)
+}
+
+#[test]
+fn values_near_the_top_do_not_wrap() {
+    let s = CpuSnapshot::from_values(&[u64::MAX, u64::MAX - 1, u64::MAX, u64::MAX - 5]);
+    assert_eq!(s.cpus, 4);
+    // (4·MAX − 6) / 4 rounds down to MAX − 2
+    assert_eq!(s.mean, u64::MAX - 2);
+    // Middle pair MAX − 1 and MAX: the u128 average rounds down
+    assert_eq!(s.median, u64::MAX - 1);
+    assert_eq!(s.max, u64::MAX);
+    assert!(s.spread < 1e-12);
+    assert!(to_level(s.mean) > 0.999 && to_level(s.mean) <= 1.0);
+
+    // The even median averages the two middle values without overflowing
+    let s = CpuSnapshot::from_values(&[u64::MAX, u64::MAX - 2]);
+    assert_eq!(s.median, u64::MAX - 1);
+    assert_eq!(s.mean, u64::MAX - 1);
+}
+
+#[test]
+fn even_cpu_count_takes_the_middle_pair() {
+    let s = CpuSnapshot::from_values(&[10, 40, 20, 30]);
+    assert_eq!((s.cpus, s.mean, s.median, s.max), (4, 25, 25, 40));
+
+    // One hot CPU moves the mean and the max, not the median
+    let s = CpuSnapshot::from_values(&[value(0.1), value(0.1), value(0.2), value(0.9)]);
+    assert_eq!(s.median, (value(0.1) / 2) + (value(0.2) / 2));
+    assert!((to_level(s.median) - 0.15).abs() < 1e-9);
+    assert!((to_level(s.mean) - 0.325).abs() < 1e-9);
+    assert_eq!(s.max, value(0.9));
+    assert!(s.spread > 0.3);
+}
+
+#[test]
+fn single_cpu_is_its_own_aggregate() {
+    let v = value(0.7);
+    let s = CpuSnapshot::from_values(&[v]);
+    assert_eq!((s.cpus, s.mean, s.median, s.max), (1, v, v, v));
+    assert_eq!(s.spread, 0.0);
+
+    let empty = CpuSnapshot::from_values(&[]);
+    assert_eq!((empty.cpus, empty.mean, empty.median, empty.max), (0, 0, 0, 0));
+}
+
+#[test]
+fn window_drops_samples_older_than_its_span() {
+    let t0 = Instant::now();
+    let mut w = RollingWindow::new(secs(10));
+    for i in 0..=10 {
+        w.push(t0 + secs(i), 1.0);
+    }
+    assert_eq!(w.mean(), 1.0);
+    // Exactly `span` old still counts; one second more rolls it out
+    w.push(t0 + secs(10), 0.0);
+    assert!((w.mean() - 11.0 / 12.0).abs() < 1e-12);
+    w.push(t0 + secs(30), 0.0);
+    assert_eq!(w.mean(), 0.0);
+    assert_eq!(w.max(), 0.0);
+
+    // A linear ramp of 0.01 per minute over a five-minute window
+    let mut w = RollingWindow::new(secs(300));
+    for m in 0..=5 {
+        w.push(t0 + secs(m * 60), 0.01 * m as f64);
+    }
+    assert!((w.slope_per_min() - 0.01).abs() < 1e-12);
+}
+
+#[test]
+fn trend_follows_the_window_roll_over() {
+    let t0 = Instant::now();
+    let mut agg = Aggregator::new(secs(10), secs(300));
+    let mut t = 0;
+    let mut feed = |agg: &mut Aggregator, level: f64, for_secs: u64| {
+        let mut last = None;
+        for _ in 0..for_secs {
+            t += 1;
+            last = Some(agg.update(t0 + secs(t), &[value(level); 4]));
+        }
+        last.unwrap()
+    };
+
+    let calm = feed(&mut agg, 0.1, 400);
+    assert_eq!(calm.trend, Trend::Flat);
+    assert!((calm.decision_level - 0.1).abs() < 1e-9);
+
+    // Escalation acts on the current level at once
+    let rising = feed(&mut agg, 0.8, 1);
+    assert_eq!(rising.trend, Trend::Rising);
+    assert!((rising.decision_level - 0.8).abs() < 1e-9);
+    assert!(rising.short_mean < 0.8);
+
+    // Once the long window has rolled over to the new level, it is flat again
+    let settled = feed(&mut agg, 0.8, 400);
+    assert_eq!(settled.trend, Trend::Flat);
+    assert!((settled.long_mean - 0.8).abs() < 1e-9);
+    assert!((settled.long_max - 0.8).abs() < 1e-9);
+
+    // De-escalation goes through the smoothed short window
+    let falling = feed(&mut agg, 0.1, 2);
+    assert_eq!(falling.trend, Trend::Falling);
+    assert!(falling.decision_level > 0.1 && falling.decision_level < 0.8);
+    assert_eq!(falling.decision_level, falling.short_mean);
+    assert!(falling.to_json().contains("\"trend\":\"Falling\""));
+}
@@ -0,0 +1,69 @@
+// Manifold audit: the statistics must separate a diffusing fold from one
+// that merely stays in [0, 1), and the report must be reproducible.
+
+use tiger_delta_ai_safety::audit::{
+    audit_compactifier, audit_string_state, report_json, AuditConfig, NEAR_RATE_LIMIT,
+};
+use tiger_delta_ai_safety::compactifier::CompactifierKind;
+use tiger_delta_ai_safety::salt::SaltManager;
+use tiger_delta_ai_safety::string_state::StringState;
+
+const KEY: &[u8] = b"audit test key";
+
+fn config() -> AuditConfig {
+    AuditConfig { samples: 300, ..AuditConfig::default() }
+}
+
+#[test]
+fn keyed_hashes_pass_every_check() {
+    for kind in [CompactifierKind::SipHash, CompactifierKind::Blake3] {
+        let report = audit_compactifier(&config(), kind, KEY);
+        for check in &report.checks {
+            assert!(check.pass, "{} failed {}: {}", kind.as_str(), check.name, check.to_json());
+        }
+    }
+}
+
+#[test]
+fn sine_fold_stays_in_range_but_fails_diffusion() {
+    let report = audit_compactifier(&config(), CompactifierKind::Sine, KEY);
+    let avalanche = report.check("avalanche").unwrap();
+    assert!(!avalanche.pass);
+    assert!(avalanche.metric("flip_rate").unwrap() < 0.3);
+    // Neighbouring vectors land next to each other
+    let collisions = report.check("collisions").unwrap();
+    assert!(collisions.metric("near_rate").unwrap() > NEAR_RATE_LIMIT);
+    assert!(!report.pass());
+}
+
+#[test]
+fn poly_hash_is_uniform_and_collision_free() {
+    // Universal, not a PRF: only range-side properties are promised
+    let report = audit_compactifier(&config(), CompactifierKind::Poly, KEY);
+    for name in ["chi_square", "collisions", "epoch_sensitivity", "related_salt"] {
+        assert!(report.check(name).unwrap().pass, "poly failed {}", name);
+    }
+}
+
+#[test]
+fn string_state_is_audited_through_its_compactifier() {
+    let mut state = StringState::with_salts(SaltManager::new(KEY))
+        .with_compactifier(CompactifierKind::Blake3.build());
+    let report = audit_string_state(&config(), &mut state);
+    assert_eq!(report.subject, "string_state/blake3");
+    assert_eq!(report.checks.len(), 3);
+    assert!(report.pass(), "{}", report.to_json());
+}
+
+#[test]
+fn report_is_reproducible_json() {
+    let subjects = |cfg: &AuditConfig| vec![audit_compactifier(cfg, CompactifierKind::SipHash, KEY)];
+    let a = report_json(&config(), &subjects(&config()));
+    let b = report_json(&config(), &subjects(&config()));
+    assert_eq!(a, b);
+    assert!(a.starts_with("{\"seed\":") && a.ends_with("]}"));
+    assert!(a.contains("\"subject\":\"siphash\"") && a.contains("\"name\":\"chi_square\""));
+
+    let other = AuditConfig { seed: 7, ..config() };
+    assert_ne!(report_json(&other, &subjects(&other)), a);
+}
@@ -0,0 +1,149 @@
+// Compactifiers: every kind folds into the same Q32.32 range, is keyed
+// by the epoch salts, and the default stays the historic sine fold.
+
+use tiger_delta_ai_safety::compactifier::{CompactifierKind, PolyFold, POLY_PRIME};
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::salt::SaltManager;
+use tiger_delta_ai_safety::schema::{attr, Attrs};
+use tiger_delta_ai_safety::string_state::StringState;
+
+const ONE: i64 = 1 << 32;
+
+fn vectors(n: usize) -> Vec<Attrs> {
+    (0..n)
+        .map(|i| {
+            let payload = format!("GET /item/{} HTTP/1.1\r\n", i * 7919);
+            payload_attrs(1024 + (i % 50_000) as u16, 12, payload.as_bytes())
+        })
+        .collect()
+}
+
+#[test]
+fn kinds_parse_and_round_trip() {
+    for kind in CompactifierKind::ALL {
+        assert_eq!(CompactifierKind::parse(kind.as_str()), Some(kind));
+        assert_eq!(kind.build().kind(), kind);
+    }
+    assert_eq!(CompactifierKind::parse("BLAKE3"), Some(CompactifierKind::Blake3));
+    assert_eq!(CompactifierKind::parse("md5"), None);
+    assert_eq!(CompactifierKind::default(), CompactifierKind::Sine);
+}
+
+#[test]
+fn default_state_uses_the_sine_fold() {
+    let mut state = StringState::with_salts(SaltManager::new(b"fleet key 0123456789"));
+    let a = vectors(1)[0];
+    let compact = state.compactify(&a);
+    let nonce = state.salts().current().nonce;
+    assert_eq!(state.compactifier().kind(), CompactifierKind::Sine);
+    assert_eq!(compact, StringState::sine_fold(&a, nonce));
+}
+
+#[test]
+fn every_kind_outputs_a_q32_fraction() {
+    let salts = SaltManager::new(b"fleet key 0123456789").derive(42);
+    let mut extremes = vectors(256);
+    extremes.push([i64::MAX; 10]);
+    extremes.push([i64::MIN; 10]);
+    extremes.push([0; 10]);
+    for kind in CompactifierKind::ALL {
+        let c = kind.build();
+        for a in &extremes {
+            let v = c.compactify(a, &salts);
+            assert!((0..ONE).contains(&v), "{} gave {}", kind.as_str(), v);
+        }
+    }
+}
+
+#[test]
+fn keyed_kinds_follow_the_epoch_key() {
+    let manager = SaltManager::new(b"fleet key 0123456789");
+    let (e1, e2) = (manager.derive(6), manager.derive(12));
+    assert_ne!(e1.key, e2.key);
+    // Same master key on another node: same key, same folds
+    let peer = SaltManager::new(b"fleet key 0123456789").derive(6);
+    assert_eq!(peer, e1);
+
+    let inputs = vectors(64);
+    for kind in [CompactifierKind::SipHash, CompactifierKind::Blake3, CompactifierKind::Poly] {
+        let c = kind.build();
+        let changed = inputs
+            .iter()
+            .filter(|&a| c.compactify(a, &e1) != c.compactify(a, &e2))
+            .count();
+        assert!(changed >= 62, "{}: only {} of 64 outputs moved with the key", kind.as_str(), changed);
+        assert!(inputs.iter().all(|a| c.compactify(a, &e1) == c.compactify(a, &peer)));
+    }
+}
+
+#[test]
+fn keyed_kinds_are_roughly_uniform() {
+    let salts = SaltManager::new(b"uniformity").derive(0);
+    let inputs = vectors(4096);
+    for kind in [CompactifierKind::SipHash, CompactifierKind::Blake3, CompactifierKind::Poly] {
+        let c = kind.build();
+        let mut bins = [0usize; 16];
+        for a in &inputs {
+            bins[(c.compactify(a, &salts) >> 28) as usize] += 1;
+        }
+        // 256 expected per bin; ±40 % is far outside chance for 4096 draws
+        for (i, &n) in bins.iter().enumerate() {
+            assert!((154..=358).contains(&n), "{} bin {} holds {}", kind.as_str(), i, n);
+        }
+    }
+}
+
+#[test]
+fn poly_hash_is_injective_across_the_field_modulus() {
+    // Attributes differing by exactly p must not alias (limbs, not a mod p)
+    let salts = SaltManager::new(b"poly").derive(0);
+    let (r, _) = PolyFold::key_pair(&salts);
+    let mut a = [0i64; 10];
+    let mut b = a;
+    a[attr::BYTE_WEIGHT] = 5;
+    b[attr::BYTE_WEIGHT] = 5 + POLY_PRIME as i64;
+    assert_ne!(PolyFold::evaluate(&a, r), PolyFold::evaluate(&b, r));
+    assert!(PolyFold::evaluate(&[i64::MAX; 10], r) < POLY_PRIME);
+}
+
+#[test]
+fn const_generic_and_slice_forms_agree() {
+    let mut state = StringState::with_salts(SaltManager::new(b"lengths"))
+        .with_compactifier(CompactifierKind::SipHash.build());
+    let short = [3i64, 1, 4];
+    let long: [i64; 16] = std::array::from_fn(|i| i as i64 * 1_000_003);
+    assert_eq!(state.compactify(&short), state.compactify_slice(&short[..]));
+    assert_eq!(state.compactify(&long), state.compactify_slice(long.as_slice()));
+    let a = vectors(1)[0];
+    assert_eq!(state.compactify(&a), state.compactify_slice(a.as_slice()));
+}
+
+#[test]
+fn every_kind_folds_any_length_and_counts_it() {
+    let salts = SaltManager::new(b"lengths").derive(3);
+    let words: Vec<i64> = (0..40).map(|i| (i * 7919) as i64).collect();
+    for kind in CompactifierKind::ALL {
+        let c = kind.build();
+        for n in 0..words.len() {
+            let v = c.compactify(&words[..n], &salts);
+            assert!((0..ONE).contains(&v), "{} at length {} gave {}", kind.as_str(), n, v);
+        }
+        // A trailing zero is a new dimension, not padding
+        assert_ne!(c.compactify(&[5], &salts), c.compactify(&[5, 0], &salts), "{}", kind.as_str());
+        assert_ne!(c.compactify(&[0, 5], &salts), c.compactify(&[5], &salts), "{}", kind.as_str());
+    }
+}
+
+#[test]
+fn index_keys_are_bounded_and_follow_the_nonce() {
+    const PHI: f64 = 1.618_033_988_749_895;
+    let keys: Vec<(i64, i64)> = (0..64).map(|i| StringState::index_key(0xDEAD_BEEF, i)).collect();
+    for &(weight, offset) in &keys {
+        let w = StringState::to_float(weight);
+        assert!((PHI / 2.0 - 1e-9..1.5 * PHI).contains(&w), "weight {}", w);
+        assert!((0..ONE).contains(&offset));
+    }
+    let distinct: std::collections::HashSet<_> = keys.iter().collect();
+    assert_eq!(distinct.len(), keys.len());
+    assert_ne!(StringState::index_key(0xDEAD_BEEF, 5), StringState::index_key(0xDEAD_BEF0, 5));
+}
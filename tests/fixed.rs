@@ -0,0 +1,180 @@
+// Q32.32 math: dense sweeps of every function against f64, at the error
+// bounds documented in src/fixed.rs, plus saturation at the edges.
+//
+// Sweeps stay inside |x| ≤ 2^20, where every Q32.32 value is exact in
+// an f64 and the reference itself is trustworthy.
+
+use tiger_delta_ai_safety::atomic_core::{AtomicCore, FixedAtomicCore};
+use tiger_delta_ai_safety::fixed::{self, EXP_MAX, HALF_PI, ONE, PI};
+
+const ULP: f64 = 1.0 / ONE as f64;
+const SWEEP: i64 = 1 << 18;
+
+/// Evenly spaced points over [lo, hi] plus xorshift-random ones.
+fn sweep(lo: f64, hi: f64) -> impl Iterator<Item = i64> {
+    let (a, b) = (fixed::from_f64(lo), fixed::from_f64(hi));
+    let step = ((b - a) / SWEEP).max(1);
+    let mut seed = 0x2545_F491_4F6C_DD1Du64;
+    let random = (0..SWEEP).map(move |_| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        a + (seed % (b - a) as u64) as i64
+    });
+    (0..=SWEEP).map(move |i| a + i * step).chain(random)
+}
+
+/// Largest |f(x) − reference(x)| in ulps over the points.
+fn max_ulps(points: impl Iterator<Item = i64>, f: fn(i64) -> i64, reference: fn(f64) -> f64) -> (f64, i64) {
+    points.fold((0.0, 0), |(worst, at), x| {
+        let err = (fixed::to_f64(f(x)) - reference(fixed::to_f64(x))).abs() / ULP;
+        if err > worst {
+            (err, x)
+        } else {
+            (worst, at)
+        }
+    })
+}
+
+fn assert_within(name: &str, (ulps, at): (f64, i64), limit: f64) {
+    assert!(ulps <= limit, "{}: {:.3} ulp at x = {}", name, ulps, fixed::to_f64(at));
+}
+
+#[test]
+fn constants_round_correctly() {
+    for (c, v) in [
+        (PI, std::f64::consts::PI),
+        (HALF_PI, std::f64::consts::FRAC_PI_2),
+        (fixed::TAU, std::f64::consts::TAU),
+        (fixed::LN_2, std::f64::consts::LN_2),
+        (fixed::E, std::f64::consts::E),
+        (fixed::PHI, 1.618_033_988_749_895),
+    ] {
+        assert!((fixed::to_f64(c) - v).abs() <= 0.5 * ULP, "{} vs {}", c, v);
+    }
+    assert!(fixed::to_f64(EXP_MAX) < 31.0 * std::f64::consts::LN_2);
+}
+
+#[test]
+fn sin_and_cos_within_one_ulp() {
+    assert_within("sin", max_ulps(sweep(-8.0, 8.0), fixed::sin, f64::sin), 1.0);
+    assert_within("cos", max_ulps(sweep(-8.0, 8.0), fixed::cos, f64::cos), 1.0);
+    assert_within("sin wide", max_ulps(sweep(-1048576.0, 1048576.0), fixed::sin, f64::sin), 1.0);
+    assert_within("cos wide", max_ulps(sweep(-1048576.0, 1048576.0), fixed::cos, f64::cos), 1.0);
+    // Exact quadrant values
+    assert_eq!(fixed::sin(0), 0);
+    assert_eq!(fixed::cos(0), ONE);
+    assert_eq!(fixed::sin(HALF_PI), ONE);
+}
+
+#[test]
+fn atan_within_one_ulp() {
+    assert_within("atan", max_ulps(sweep(-4.0, 4.0), fixed::atan, f64::atan), 1.0);
+    assert_within("atan wide", max_ulps(sweep(-1048576.0, 1048576.0), fixed::atan, f64::atan), 1.0);
+    assert_within("atan edges", max_ulps([i64::MAX, i64::MIN + 1, 1, -1].into_iter(), fixed::atan, f64::atan), 1.0);
+}
+
+#[test]
+fn sqrt_is_correctly_rounded() {
+    assert_within("sqrt", max_ulps(sweep(0.0, 16.0), fixed::sqrt, f64::sqrt), 0.5);
+    // Whole i64 range, checked in integers: (2r − 1)² ≤ 4·x·2^32 ≤ (2r + 1)²
+    let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+    for _ in 0..SWEEP {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        let x = (seed >> (1 + seed % 63)) as i64;
+        let r = fixed::sqrt(x) as u128;
+        let n4 = (x as u128) << 34;
+        assert!((2 * r).saturating_sub(1).pow(2) <= n4 && n4 <= (2 * r + 1).pow(2), "sqrt({})", x);
+    }
+    assert_eq!(fixed::sqrt(-ONE), 0);
+    assert_eq!(fixed::sqrt(4 * ONE), 2 * ONE);
+}
+
+#[test]
+fn ln_within_one_ulp() {
+    assert_within("ln", max_ulps(sweep(1e-6, 4.0), fixed::ln, f64::ln), 1.0);
+    assert_within("ln wide", max_ulps(sweep(4.0, 1048576.0), fixed::ln, f64::ln), 1.0);
+    assert_eq!(fixed::ln(ONE), 0);
+    assert_eq!(fixed::ln(0), i64::MIN);
+    assert_eq!(fixed::ln(-5), i64::MIN);
+}
+
+#[test]
+fn exp_within_one_ulp_plus_relative() {
+    let points = sweep(-24.0, fixed::to_f64(EXP_MAX));
+    for x in points {
+        let got = fixed::to_f64(fixed::exp(x));
+        let want = fixed::to_f64(x).exp();
+        let limit = ULP + want * 2f64.powi(-40);
+        assert!((got - want).abs() <= limit, "exp({}) = {} vs {}", fixed::to_f64(x), got, want);
+    }
+    assert_eq!(fixed::exp(0), ONE);
+    assert_eq!(fixed::exp(EXP_MAX + 1), i64::MAX);
+    assert_eq!(fixed::exp(i64::MIN), 0);
+    // ln 5 carries ½ ulp, which exp scales by 5
+    assert!((fixed::exp(fixed::ln(5 * ONE)) - 5 * ONE).abs() <= 3);
+}
+
+#[test]
+fn reduce_angle_lands_in_half_open_range() {
+    for x in sweep(-1048576.0, 1048576.0).take(4096) {
+        let r = fixed::reduce_angle(x);
+        assert!((-PI..PI).contains(&r), "{}", r);
+        assert!((fixed::sin(r) - fixed::sin(x)).abs() <= 2);
+    }
+    for x in [i64::MAX, i64::MIN, PI, -PI] {
+        assert!((-PI..PI).contains(&fixed::reduce_angle(x)), "{}", x);
+    }
+}
+
+#[test]
+fn arithmetic_saturates() {
+    assert_eq!(fixed::saturating_mul(3 * ONE, ONE / 2), 3 * ONE / 2);
+    assert_eq!(fixed::saturating_mul(i64::MAX, 2 * ONE), i64::MAX);
+    assert_eq!(fixed::saturating_mul(i64::MIN, 2 * ONE), i64::MIN);
+    assert_eq!(fixed::saturating_div(ONE, 4 * ONE), ONE / 4);
+    assert_eq!(fixed::saturating_div(ONE, 0), i64::MAX);
+    assert_eq!(fixed::saturating_div(-ONE, 0), i64::MIN);
+    assert_eq!(fixed::saturating_div(0, 0), 0);
+    assert_eq!(fixed::saturating_div(i64::MAX, 1), i64::MAX);
+    assert_eq!(fixed::saturating_add(i64::MAX, ONE), i64::MAX);
+    assert_eq!(fixed::saturating_sub(i64::MIN, ONE), i64::MIN);
+    assert_eq!(fixed::from_f64(f64::INFINITY), i64::MAX);
+    assert_eq!(fixed::from_f64(f64::NAN), 0);
+    assert_eq!(fixed::from_int(-3), -3 * ONE);
+}
+
+#[test]
+fn fixed_atomic_core_tracks_the_f64_core() {
+    let mut core = AtomicCore::new(100);
+    let mut twin = FixedAtomicCore::new(100);
+    let mut seed = 0x1234_5678_9ABC_DEF1u64;
+    // Low entropy makes valence compound by 2 % per step in both cores;
+    // 600 steps stay well inside the Q32.32 range (the twin saturates at 2^31)
+    for step in 0..600 {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        // Mostly calm, with bursts far beyond the resistance
+        let impact = if step % 97 < 5 { (seed % 5000) as f64 } else { (seed % 1000) as f64 / 100.0 };
+        let entropy = (seed >> 40) as f64 / (1u64 << 24) as f64 * 2.0;
+
+        core.sharpen_angles(impact);
+        twin.sharpen_angles(fixed::from_f64(impact));
+        let drift = core.find_the_middle(entropy);
+        let drift_q = twin.find_the_middle(fixed::from_f64(entropy));
+        let p = core.threat_probability(entropy);
+        let p_q = twin.threat_probability(fixed::from_f64(entropy));
+
+        assert_eq!(twin.mutation_phase, core.mutation_phase, "step {}", step);
+        assert_eq!(twin.is_critical, core.is_critical, "step {}", step);
+        assert!((fixed::to_f64(drift_q) - drift).abs() < 1e-6, "drift at step {}", step);
+        assert!((fixed::to_f64(p_q) - p).abs() < 1e-5, "threat at step {}: {} vs {}", step, fixed::to_f64(p_q), p);
+        assert!((fixed::to_f64(twin.electron_cloud) - core.electron_cloud).abs() < 1e-6);
+        // Valence accumulates, so its error is relative
+        let tolerance = 1e-6 * core.valence_energy.max(1.0);
+        assert!((fixed::to_f64(twin.valence_energy) - core.valence_energy).abs() < tolerance, "valence at step {}: {} vs {}", step, fixed::to_f64(twin.valence_energy), core.valence_energy);
+    }
+}
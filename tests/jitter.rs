@@ -0,0 +1,97 @@
+// Inter-arrival jitter: HRV metrics on known interval series and the
+// regular / natural / chaotic classification.
+
+use tiger_delta_ai_safety::brain::Brain;
+use tiger_delta_ai_safety::jitter::{JitterTracker, Rhythm, JITTER_WINDOW, MIN_INTERVALS};
+use tiger_delta_ai_safety::string_state::StringState;
+use tiger_delta_ai_safety::timing::TimeBuckets;
+
+const MS: u64 = 1_000_000;
+
+/// Deterministic xorshift64 in (0, 1).
+fn uniform(seed: &mut u64) -> f64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    (*seed >> 11) as f64 / (1u64 << 53) as f64 + f64::EPSILON
+}
+
+fn feed(intervals: impl IntoIterator<Item = u64>) -> JitterTracker {
+    let mut t = JitterTracker::new();
+    let mut ts = 1_000 * MS;
+    t.observe(ts);
+    for gap in intervals {
+        ts += gap;
+        t.observe(ts);
+    }
+    t
+}
+
+#[test]
+fn metrics_match_hand_computation() {
+    // Intervals 10, 20, 10, 20 ms: mean 15, SDNN 5, diffs ±10 → RMSSD 10
+    let m = feed([10 * MS, 20 * MS, 10 * MS, 20 * MS]).metrics();
+    assert_eq!(m.intervals, 4);
+    assert!((m.mean_ns - 15.0 * MS as f64).abs() < 1e-6);
+    assert!((m.sdnn_ns - 5.0 * MS as f64).abs() < 1e-6);
+    assert!((m.rmssd_ns - 10.0 * MS as f64).abs() < 1e-6);
+    // Every successive difference exceeds 6.25 % of the mean
+    assert_eq!(m.pnn, 1.0);
+    assert_eq!(m.rhythm(), Rhythm::Unknown);
+    assert_eq!(m.score(), 0.0);
+}
+
+#[test]
+fn window_is_bounded_and_reordering_ignored() {
+    let mut t = feed(std::iter::repeat_n(MS, 500));
+    assert_eq!(t.metrics().intervals, JITTER_WINDOW);
+    t.observe(0);
+    assert_eq!(t.metrics().intervals, JITTER_WINDOW);
+}
+
+#[test]
+fn timer_paced_sender_is_regular() {
+    // 1 s beacon with ±0.5 ms of scheduling noise
+    let mut seed = 7;
+    let m = feed((0..100).map(|_| 1_000 * MS + (uniform(&mut seed) * MS as f64) as u64 - MS / 2)).metrics();
+    assert_eq!(m.rhythm(), Rhythm::Regular);
+    assert!(m.score() > 0.95, "{:?}", m);
+    assert!(m.pnn < 0.01);
+}
+
+#[test]
+fn poisson_sender_is_natural() {
+    let mut seed = 42;
+    let m = feed((0..200).map(|_| (-uniform(&mut seed).ln() * 50.0 * MS as f64) as u64)).metrics();
+    assert_eq!(m.rhythm(), Rhythm::Natural, "cv={}", m.cv());
+    assert_eq!(m.score(), 0.0);
+}
+
+#[test]
+fn on_off_flood_is_chaotic() {
+    // Bursts of 30 packets 10 µs apart, then 5 s of silence
+    let series = (0..4).flat_map(|_| std::iter::repeat_n(10_000, 30).chain([5_000 * MS]));
+    let m = feed(series).metrics();
+    assert_eq!(m.rhythm(), Rhythm::Chaotic, "cv={}", m.cv());
+    assert!(m.score() > 0.5);
+}
+
+#[test]
+fn jitter_score_reaches_the_verdict() {
+    let attrs = [40000, 64, 71, 5000, 5000 % 111, 64 % 7, 300, 9, 0, 0];
+    let mut state = StringState::new();
+    let mut brain = Brain::new();
+    let mut verdict = brain.process_at(&attrs, 0, &mut state);
+    for i in 1..=MIN_INTERVALS as u64 + 4 {
+        verdict = brain.process_at(&attrs, i * 250 * MS, &mut state);
+    }
+    assert_eq!(verdict.arrival.hrv.rhythm(), Rhythm::Regular);
+    assert_eq!(verdict.jitter_score, 1.0);
+
+    // Counter deltas carry no intervals
+    let mut buckets = TimeBuckets::new();
+    for i in 0..50 {
+        buckets.record_n(i * MS, 10, 640);
+    }
+    assert_eq!(buckets.hrv().intervals, 0);
+}
@@ -0,0 +1,139 @@
+// Multi-dimensional equilibrium: each zone learns its own center and
+// spread, leaves and returns through its own hysteresis bounds, and the
+// report names exactly the dimensions that moved.
+
+use tiger_delta_ai_safety::brain::Brain;
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::lagrange::{MultiLagrange, ZoneSpec, ZoneTransition, ZONE_WARMUP};
+use tiger_delta_ai_safety::schema::{attr, ATTR_SLOTS};
+use tiger_delta_ai_safety::string_state::StringState;
+
+/// Seeded xorshift noise in [-0.5, 0.5) × `amp`
+fn noise(seed: &mut u64, amp: f64) -> f64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    ((*seed >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * amp
+}
+
+/// Three dimensions around 0.2, 0.5 and 0.8 with small jitter
+fn calm(seed: &mut u64) -> [f64; 3] {
+    [0.2 + noise(seed, 0.02), 0.5 + noise(seed, 0.02), 0.8 + noise(seed, 0.02)]
+}
+
+fn three_zones() -> MultiLagrange {
+    MultiLagrange::new(vec![
+        ZoneSpec::new("a", vec![0]),
+        ZoneSpec::new("b", vec![1]),
+        ZoneSpec::new("c", vec![2]),
+    ])
+}
+
+#[test]
+fn per_feature_zones_follow_the_schema() {
+    let zones = MultiLagrange::per_feature();
+    let names: Vec<&str> = zones.specs().map(|s| s.name.as_str()).collect();
+    assert_eq!(names[0], ATTR_SLOTS[0].name);
+    assert!(!names.contains(&"reserved_8"));
+    assert_eq!(names.len(), 8);
+}
+
+#[test]
+fn report_names_only_the_dimension_that_left() {
+    let mut zones = three_zones();
+    let mut seed = 0x1234_5678u64;
+    for _ in 0..500 {
+        assert!(zones.stabilize(&calm(&mut seed)).is_stable());
+    }
+    let mut v = calm(&mut seed);
+    v[1] = 0.9;
+    let report = zones.stabilize(&v).clone();
+    assert!(!report.is_stable());
+    assert_eq!(report.outside_names(), "b");
+    assert_eq!(report.outside_mask(), 0b010);
+    let b = &report.zones[1];
+    assert_eq!(b.transition, Some(ZoneTransition::Left));
+    assert!(b.sigma > 4.0);
+    assert_eq!(report.worst().unwrap().name, "b");
+    assert!(report.to_json().contains("\"name\":\"b\",\"value\":0.9000"));
+    assert!(report.to_json().contains("\"transition\":\"left\""));
+}
+
+#[test]
+fn hysteresis_holds_between_the_bounds() {
+    let mut zones = MultiLagrange::new(vec![ZoneSpec::new("x", vec![0]).with_bounds(2.0, 6.0)]);
+    let mut seed = 99u64;
+    for _ in 0..500 {
+        zones.stabilize(&[0.5 + noise(&mut seed, 0.1)]);
+    }
+    let spread = {
+        let r = zones.stabilize(&[0.5]);
+        (r.zones[0].value - r.zones[0].center).abs()
+    };
+    assert!(spread < 0.01);
+    // Mean |deviation| of uniform noise of width 0.1 is 0.025
+    let sigma_unit = 0.025;
+    // 4 σ: inside the exit bound, stays
+    assert!(zones.stabilize(&[0.5 + 4.0 * sigma_unit]).is_stable());
+    // 8 σ: leaves
+    assert!(!zones.stabilize(&[0.5 + 8.0 * sigma_unit]).is_stable());
+    // 4 σ again: beyond the enter bound, still out
+    let r = zones.stabilize(&[0.5 + 4.0 * sigma_unit]);
+    assert!(!r.is_stable() && r.zones[0].transition.is_none());
+    // Back near the center: returns
+    let r = zones.stabilize(&[0.5]);
+    assert_eq!(r.zones[0].transition, Some(ZoneTransition::Returned));
+}
+
+#[test]
+fn sustained_deviation_does_not_drag_the_center() {
+    let mut zones = three_zones();
+    let mut seed = 7u64;
+    for _ in 0..500 {
+        zones.stabilize(&calm(&mut seed));
+    }
+    let center = zones.report().zones[2].center;
+    for _ in 0..2000 {
+        let mut v = calm(&mut seed);
+        v[2] = 0.1;
+        assert_eq!(zones.stabilize(&v).outside_names(), "c");
+    }
+    assert_eq!(zones.report().zones[2].center, center);
+}
+
+#[test]
+fn groups_average_their_dimensions_and_warm_up_first() {
+    let mut zones = MultiLagrange::new(vec![ZoneSpec::new("pair", vec![0, 1]), ZoneSpec::new("missing", vec![7])]);
+    let mut seed = 3u64;
+    // Nothing leaves during warm-up, whatever arrives
+    for i in 0..ZONE_WARMUP {
+        let v = if i % 2 == 0 { [0.0, 0.0] } else { [1.0, 1.0] };
+        assert!(zones.stabilize(&v).is_stable());
+    }
+    for _ in 0..1000 {
+        let r = zones.stabilize(&[0.3 + noise(&mut seed, 0.02), 0.5 + noise(&mut seed, 0.02)]);
+        assert!((r.zones[0].value - 0.4).abs() < 0.02);
+        // Dimensions the vector does not have read as zero
+        assert_eq!(r.zones[1].value, 0.0);
+    }
+    // One of the pair moving far enough moves the group out
+    assert_eq!(zones.stabilize(&[0.3, 1.0]).outside_names(), "pair");
+}
+
+#[test]
+fn brain_reports_zone_breaches() {
+    let mut state = StringState::new();
+    let mut brain = Brain::new();
+    for i in 0..300u16 {
+        let v = brain.process(&payload_attrs(40_000 + i % 4, 12, b"GET /index.html HTTP/1.1\r\n"), &mut state);
+        assert_eq!(v.outside_zones, 0, "packet {}", i);
+    }
+    // Same port and address, but a long binary payload
+    let v = brain.process(&payload_attrs(40_001, 12, &[0xFF; 900]), &mut state);
+    let report = brain.zones.report();
+    let names = report.outside_names();
+    assert!(names.contains("length") && names.contains("byte_weight"), "{}", names);
+    assert!(!names.contains("src_port"));
+    assert_eq!(v.outside_zones, report.outside_mask());
+    assert_eq!(report.zones[attr::SRC_PORT].name, "src_port");
+}
@@ -0,0 +1,212 @@
+// Lumis scheduling: on an injected clock advancing one nominal tick per
+// call it reproduces the tick-based core exactly; on real time, rest and
+// decay follow seconds, whatever the packet rate.
+
+use std::time::Duration;
+use tiger_delta_ai_safety::brain::{Action, Brain};
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::lumis::{
+    LumisClock, LumisCore, LumisSchedule, COOLING_PER_TICK, CRITICAL_DECAY_PER_TICK, NOMINAL_TICK_HZ,
+};
+use tiger_delta_ai_safety::string_state::StringState;
+
+const SEC: u64 = 1_000_000_000;
+/// One nominal tick in ns
+const TICK: u64 = SEC / NOMINAL_TICK_HZ as u64;
+
+/// Seeded xorshift (impact, resonance) pairs: bursts, calm and silence
+fn inputs(n: usize, mut seed: u64) -> Vec<(f64, f64)> {
+    (0..n)
+        .map(|i| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let u = (seed >> 11) as f64 / (1u64 << 53) as f64;
+            match (i / 700) % 3 {
+                0 => (u, u * 0.5),
+                1 => (u * 0.4, 0.3),
+                _ => (0.0, 0.0),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn injected_clock_at_the_nominal_rate_matches_ticks() {
+    let mut ticks = LumisCore::new();
+    let mut timed = LumisCore::with_clock(LumisClock::Injected);
+    let (mut mass_a, mut mass_b) = (1000.0, 1000.0);
+    let mut rested = false;
+    for (i, &(impact, resonance)) in inputs(4200, 9).iter().enumerate() {
+        let silent = (i / 700) % 3 == 2;
+        let pressure = if i % 97 < 3 && !silent { 0.6 } else { 0.0 };
+        ticks.set_arrival_pressure(pressure);
+        timed.set_arrival_pressure(pressure);
+        timed.set_now(1_000 * SEC + i as u64 * TICK);
+        ticks.tick_cycle(impact, resonance, &mut mass_a);
+        timed.tick_cycle(impact, resonance, &mut mass_b);
+
+        assert_eq!(ticks.entropy_level(), timed.entropy_level(), "entropy at {}", i);
+        assert_eq!(ticks.is_resting(), timed.is_resting(), "rest at {}", i);
+        assert_eq!(ticks.dynamic_threshold(), timed.dynamic_threshold(), "threshold at {}", i);
+        assert_eq!(mass_a, mass_b);
+        rested |= ticks.is_resting();
+    }
+    assert!(rested, "the silent stretches must reach rest");
+}
+
+#[test]
+fn brains_on_either_clock_agree_at_the_nominal_rate() {
+    let mut state = StringState::new();
+    let mut ticks = Brain::new();
+    let mut timed = Brain::with_lumis_clock(LumisClock::Injected);
+    for i in 0..1500u64 {
+        let attrs = payload_attrs(40_000, 12, format!("GET /{} HTTP/1.1\r\n", i % 13).as_bytes());
+        let ts = 50 * SEC + i * TICK;
+        let a = ticks.process_at(&attrs, ts, &mut state);
+        let b = timed.process_at(&attrs, ts, &mut state);
+        assert_eq!(a.action, b.action, "packet {}", i);
+        // A preempted packet never reaches Lumis: the tick-based core
+        // skips a tick there while the timed one still sees the time pass
+        if a.action == Action::Preempt {
+            assert!(i > 50, "preempted too early to compare");
+            return;
+        }
+        assert_eq!(ticks.lumis.entropy_level(), timed.lumis.entropy_level(), "packet {}", i);
+        assert_eq!(ticks.defense_mass, timed.defense_mass);
+    }
+}
+
+/// Quiet packets every `gap` ns until rest; returns the seconds it took
+fn seconds_to_rest(core: &mut LumisCore, gap: u64, limit: u64) -> Option<f64> {
+    let mut mass = 1000.0;
+    let mut t = 0;
+    while t <= limit {
+        core.set_now(t);
+        core.tick_cycle(0.0, 0.0, &mut mass);
+        if core.is_resting() {
+            return Some(t as f64 / SEC as f64);
+        }
+        t += gap;
+    }
+    None
+}
+
+#[test]
+fn rest_follows_seconds_not_packet_rate() {
+    // The first cycle counts one nominal tick
+    let rest_after = LumisSchedule::default().rest_after.as_secs_f64() - 1.0 / NOMINAL_TICK_HZ;
+    for gap in [SEC / 1000, SEC / 50, SEC / 2] {
+        let mut core = LumisCore::with_clock(LumisClock::Injected);
+        let t = seconds_to_rest(&mut core, gap, 20 * SEC).expect("time-based core never rested");
+        assert!(t >= rest_after && t <= rest_after + gap as f64 / SEC as f64, "gap {} ns: rest after {} s", gap, t);
+    }
+    // The tick-based core at 2 pps needs 500 packets, i.e. 250 s
+    let mut slow = LumisCore::new();
+    assert_eq!(seconds_to_rest(&mut slow, SEC / 2, 20 * SEC), None);
+
+    // rest_after is configurable
+    let schedule = LumisSchedule { rest_after: Duration::from_secs(2), ..LumisSchedule::default() };
+    let mut quick = LumisCore::with_clock(LumisClock::Injected).with_schedule(schedule);
+    let t = seconds_to_rest(&mut quick, SEC / 10, 20 * SEC).unwrap();
+    assert!((2.0..=2.1).contains(&t), "{}", t);
+}
+
+/// Warms a core with moderate pressure, then leaves it idle for `secs`
+/// seconds with the idle timer firing every `period` ns
+fn idle_for(period: u64, secs: u64) -> LumisCore {
+    let mut core = LumisCore::with_clock(LumisClock::Injected);
+    let mut mass = 1000.0;
+    for i in 0..300 {
+        core.set_now(i * TICK);
+        core.tick_cycle(0.4, 0.3, &mut mass);
+    }
+    let start = 299 * TICK;
+    let mut t = start;
+    while t < start + secs * SEC {
+        t += period;
+        core.set_now(t);
+        core.idle();
+    }
+    core
+}
+
+#[test]
+fn idle_timer_decays_per_second_at_any_period() {
+    // Cooling only: 0.99 per nominal tick, whichever the timer period
+    let fine = idle_for(SEC / 100, 1);
+    let coarse = idle_for(SEC / 4, 1);
+    let start = idle_for(SEC, 0).entropy_level();
+    assert!(start > 0.05);
+    let expected = start * COOLING_PER_TICK.powf(NOMINAL_TICK_HZ);
+    for core in [&fine, &coarse] {
+        assert!((core.entropy_level() - expected).abs() < 1e-9, "{} vs {}", core.entropy_level(), expected);
+        assert!(!core.is_resting());
+    }
+
+    // With no packets at all the timer alone brings rest, then zero entropy
+    let rested = idle_for(SEC / 4, 8);
+    assert!(rested.is_resting());
+    assert_eq!(rested.entropy_level(), 0.0);
+}
+
+/// Saturates a core's entropy within a few ns, then spreads `packets`
+/// weightless packets over `span` ns; returns (saturated, final) entropy
+fn critical_decay(packets: u64, span: u64) -> (f64, f64) {
+    let mut core = LumisCore::with_clock(LumisClock::Injected);
+    let mut mass = 1000.0;
+    for i in 0..30 {
+        core.set_now(i);
+        core.tick_cycle(0.5, 0.0, &mut mass);
+    }
+    let start = core.entropy_level();
+    for k in 1..=packets {
+        core.set_now(29 + k * span / packets);
+        core.tick_cycle(0.0, 0.0, &mut mass);
+    }
+    (start, core.entropy_level())
+}
+
+#[test]
+fn critical_decay_follows_time_not_packet_rate() {
+    // A fifth of a nominal tick keeps the entropy above 0.8 throughout
+    let span = TICK / 5;
+    let (start, sparse) = critical_decay(1, span);
+    let (_, dense) = critical_decay(10, span);
+    assert!(start > 0.95, "{}", start);
+    let expected = start * CRITICAL_DECAY_PER_TICK.powf(0.2);
+    assert!(expected > 0.8);
+    for e in [sparse, dense] {
+        assert!((e - expected).abs() < 1e-4, "{} vs {}", e, expected);
+    }
+}
+
+#[test]
+fn idle_timer_fades_arrival_pressure() {
+    let mut core = LumisCore::with_clock(LumisClock::Injected);
+    core.set_now(0);
+    core.idle();
+    core.set_arrival_pressure(0.8);
+    for s in 1..=3 {
+        core.set_now(s * SEC);
+        core.idle();
+    }
+    // Half-life of one second
+    assert!((core.arrival_pressure() - 0.1).abs() < 1e-9, "{}", core.arrival_pressure());
+    assert!(!core.is_resting());
+}
+
+#[test]
+fn monotonic_clock_runs_on_real_time() {
+    let mut core = LumisCore::with_clock(LumisClock::Monotonic);
+    assert_eq!(core.clock(), LumisClock::Monotonic);
+    let mut mass = 1000.0;
+    core.tick_cycle(0.3, 0.2, &mut mass);
+    let first = core.dynamic_threshold();
+    std::thread::sleep(Duration::from_millis(20));
+    core.idle();
+    core.tick_cycle(0.3, 0.2, &mut mass);
+    // The sleep alone is two nominal ticks of phase
+    assert_ne!(core.dynamic_threshold(), first);
+    assert!(!core.is_resting());
+}
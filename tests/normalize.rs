@@ -0,0 +1,232 @@
+// Normalization: every strategy lands in the Q32.32 [0, 1) contract of
+// the cores, adapts the way its statistics say, and survives a snapshot.
+
+use tiger_delta_ai_safety::brain::{Action, Brain};
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::normalize::{Feature, Normalizer, QuantileSketch, Strategy};
+use tiger_delta_ai_safety::salt::SaltManager;
+use tiger_delta_ai_safety::schema::{attr, ATTR_COUNT, ATTR_SLOTS};
+use tiger_delta_ai_safety::string_state::StringState;
+use tiger_delta_ai_safety::timing::Arrival;
+
+const ONE: i64 = 1 << 32;
+
+fn to_f(q: i64) -> f64 {
+    q as f64 / ONE as f64
+}
+
+/// Seeded xorshift draws, heavy-tailed like byte sums
+fn draws(n: usize, mut seed: u64) -> Vec<i64> {
+    (0..n)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            ((seed % 1000) * (seed >> 54)) as i64
+        })
+        .collect()
+}
+
+#[test]
+fn specs_parse_and_round_trip() {
+    for s in Strategy::ALL {
+        assert_eq!(Strategy::parse(s.as_str()), Some(s));
+    }
+    let n = Normalizer::parse("quantile, src_port=minmax, 12=zscore").unwrap();
+    assert_eq!(n.strategy(attr::SRC_PORT), Strategy::MinMax);
+    assert_eq!(n.strategy(attr::LENGTH), Strategy::Quantile);
+    assert_eq!(n.strategy(12), Strategy::ZScore);
+    assert_eq!(Normalizer::parse(&n.spec()), Some(n));
+    assert_eq!(Normalizer::parse("median"), None);
+    assert_eq!(Normalizer::parse("log, no_such_slot=log"), None);
+    assert_eq!(Normalizer::default().strategy(0), Strategy::Schema);
+}
+
+#[test]
+fn every_strategy_outputs_a_q32_fraction() {
+    let mut inputs = draws(2000, 7);
+    inputs.extend([0, -5, i64::MAX, i64::MIN, 1]);
+    for s in Strategy::ALL {
+        for index in [attr::SRC_PORT, attr::BYTE_WEIGHT, attr::RESERVED_8, 40] {
+            let mut f = Feature::new(s, index);
+            for &x in &inputs {
+                let y = f.observe(x);
+                assert!((0..ONE).contains(&y), "{} slot {} gave {} for {}", s.as_str(), index, y, x);
+            }
+        }
+    }
+}
+
+#[test]
+fn schema_strategy_is_the_static_slot_scaling() {
+    let a = payload_attrs(443, 12, b"GET / HTTP/1.1\r\n");
+    let mut n = Normalizer::default();
+    let out = n.normalize(&a);
+    for (i, slot) in ATTR_SLOTS.iter().enumerate() {
+        assert!((to_f(out[i]) - slot.normalization.apply(a[i]).min(1.0 - 1e-9)).abs() < 1e-9, "{}", slot.name);
+    }
+    // Extra dimensions have no schema bound: they fall back to the sketch
+    assert_eq!(Feature::new(Strategy::Schema, ATTR_COUNT + 2).strategy(), Strategy::Quantile);
+}
+
+#[test]
+fn adaptive_strategies_score_before_learning() {
+    let mut minmax = Feature::new(Strategy::MinMax, 0);
+    assert_eq!(to_f(minmax.observe(100)), 0.5);
+    minmax.observe(200);
+    assert!((to_f(minmax.score(150)) - 0.5).abs() < 1e-9);
+    // A new maximum scores at the edge, then widens the range
+    assert!(to_f(minmax.observe(1000)) > 0.999);
+    assert!((to_f(minmax.score(550)) - 0.5).abs() < 1e-9);
+
+    let mut z = Feature::new(Strategy::ZScore, 0);
+    for x in draws(4000, 11).iter().map(|x| 5000 + x % 100) {
+        z.observe(x);
+    }
+    let Feature::ZScore { mean, .. } = z else { panic!() };
+    assert!((to_f(z.score(mean.round() as i64)) - 0.5).abs() < 0.02);
+    assert!(to_f(z.score(100_000)) > 0.99);
+    assert!(to_f(z.score(0)) < 0.01);
+
+    // Log scaling starts from the schema bound of the slot
+    let log = Feature::new(Strategy::Log, attr::LENGTH);
+    assert!((to_f(log.score(1024)) - (1.0 - 1e-9)).abs() < 1e-6);
+}
+
+#[test]
+fn quantile_sketch_tracks_the_empirical_cdf() {
+    let values = draws(8000, 3);
+    let mut sketch = QuantileSketch::new();
+    for &x in &values[..3000] {
+        sketch.insert(x as u64);
+    }
+    let mut sorted: Vec<i64> = values[..3000].to_vec();
+    sorted.sort();
+    for q in [0.1, 0.25, 0.5, 0.75, 0.9] {
+        let x = sorted[(q * sorted.len() as f64) as usize];
+        // 4 buckets per octave: the CDF is within one bucket's mass
+        assert!((sketch.cdf(x as u64) - q).abs() < 0.05, "cdf at q={}: {}", q, sketch.cdf(x as u64));
+        let est = sketch.quantile(q) as f64;
+        assert!((est - x as f64).abs() <= 0.25 * x as f64 + 2.0, "quantile {}: {} vs {}", q, est, x);
+    }
+
+    // Counts decay: after a shift the old distribution fades
+    let mut shifted = QuantileSketch::new();
+    for _ in 0..4000 {
+        shifted.insert(10);
+    }
+    assert!(shifted.cdf(100) > 0.99);
+    for _ in 0..20_000 {
+        shifted.insert(1_000_000);
+    }
+    assert!(shifted.cdf(100) < 0.05, "old mass {}", shifted.cdf(100));
+}
+
+#[test]
+fn snapshot_restores_the_learned_statistics() {
+    let spec = "quantile, src_port=minmax, length=zscore, byte_weight=log, first_byte=schema";
+    let mut state = StringState::with_salts(SaltManager::new(b"snapshot")).with_normalizer(Normalizer::parse(spec).unwrap());
+    let payloads: Vec<_> = (0..500)
+        .map(|i| payload_attrs(1024 + i as u16, 12, format!("GET /{} HTTP/1.1\r\n", i * 31).as_bytes()))
+        .collect();
+    for a in &payloads {
+        let mut v = a.to_vec();
+        v.push(a[attr::LENGTH] * 3);
+        state.normalize(&v);
+    }
+    let snapshot = state.snapshot();
+
+    let mut restored =
+        StringState::with_salts(SaltManager::new(b"snapshot")).with_normalizer(Normalizer::parse(spec).unwrap());
+    restored.restore(&snapshot).unwrap();
+    assert_eq!(restored.normalizer(), state.normalizer());
+    assert_eq!(restored.snapshot(), snapshot);
+
+    // Under another config the mismatching features start afresh
+    let mut other = StringState::new().with_normalizer(Normalizer::parse("quantile").unwrap());
+    other.restore(&snapshot).unwrap();
+    assert_eq!(other.normalizer().feature(attr::HEAD_WEIGHT), state.normalizer().feature(attr::HEAD_WEIGHT));
+    assert_eq!(other.normalizer().feature(attr::SRC_PORT), Some(&Feature::new(Strategy::Quantile, attr::SRC_PORT)));
+
+    assert!(StringState::new().restore("not a snapshot").is_err());
+    assert!(restored.restore("tiger_state 1\nfeature 3 minmax x y\n").is_err());
+}
+
+#[test]
+fn snapshot_file_round_trip() {
+    let path = std::env::temp_dir().join(format!("tiger_state_{}", std::process::id()));
+    let mut state = StringState::new().with_normalizer(Normalizer::parse("zscore").unwrap());
+    assert!(!state.load_snapshot(&path).unwrap());
+    for x in draws(300, 5) {
+        state.normalize(&[x, x / 2]);
+    }
+    state.save_snapshot(&path).unwrap();
+    let mut loaded = StringState::new().with_normalizer(Normalizer::parse("zscore").unwrap());
+    assert!(loaded.load_snapshot(&path).unwrap());
+    assert_eq!(loaded.normalizer(), state.normalizer());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn impact_energy_stays_on_the_payload_scale() {
+    let mut state = StringState::new();
+    let mut brain = Brain::new();
+    let small = payload_attrs(53, 12, b"ping 000");
+    let big = payload_attrs(65000, 39, &[0xFFu8; 1024]);
+    let v_small = brain.process(&small, &mut state);
+    let v_big = Brain::new().process(&big, &mut state);
+    assert!(v_small.impact_energy < v_big.impact_energy);
+    assert!(v_big.impact_energy <= 0.25);
+    // One large packet alone is no reason to preempt
+    assert_ne!(v_big.action, Action::Preempt);
+}
+
+#[test]
+fn synthetic_vectors_are_scored_without_learning() {
+    let mut state = StringState::with_salts(SaltManager::new(b"synthetic"))
+        .with_normalizer(Normalizer::parse("quantile, length=zscore").unwrap());
+    let mut brain = Brain::new();
+    for i in 0..200u16 {
+        brain.process(&payload_attrs(40_000 + i, 12, b"GET /index.html HTTP/1.1\r\n"), &mut state);
+    }
+    let learned = state.snapshot();
+
+    // Header-only vectors far off the payload distribution
+    let mut header = [0i64; ATTR_COUNT];
+    header[attr::LENGTH] = 9000;
+    let expected = state.score(&header);
+    for _ in 0..500 {
+        let v = brain.process_synthetic(&header, Arrival::default(), &mut state);
+        assert_eq!(v.entropy_input, 0.0);
+    }
+    assert_eq!(state.snapshot(), learned);
+    assert_eq!(state.score(&header), expected);
+
+    // The payload path still learns
+    brain.process_with_arrival(&header, Arrival::default(), &mut state);
+    assert_ne!(state.snapshot(), learned);
+}
+
+#[test]
+fn restored_counts_at_the_limit_saturate() {
+    let max = u32::MAX;
+    let snapshot = format!(
+        "tiger_state 1\nfeature 0 quantile {} 9:{} 12:{}\nfeature 1 zscore 5 1 {}\n",
+        max,
+        max,
+        max,
+        u64::MAX
+    );
+    let mut state = StringState::new().with_normalizer(Normalizer::parse("quantile, length=zscore").unwrap());
+    state.restore(&snapshot).unwrap();
+    for x in draws(100, 9) {
+        for &q in &state.normalize(&[x % 64, x]) {
+            assert!((0..ONE).contains(&q));
+        }
+    }
+    // The first insert hit the half-life and halved the saturated buckets
+    let Some(Feature::Quantile(sketch)) = state.normalizer().feature(0) else {
+        panic!("feature 0 is not a quantile sketch");
+    };
+    assert!(sketch.total() < 2 * max as u64);
+}
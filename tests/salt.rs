@@ -0,0 +1,131 @@
+// Salt schedule: HKDF derivation is shared by key, pressure picks the
+// rotation tier, epochs never move backwards, and the previous epoch
+// stays accepted for the overlap window only.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tiger_delta_ai_safety::salt::{SaltManager, EPOCH_TICK_SECS};
+
+const KEY: &[u8] = b"fleet key 0123456789";
+
+fn at(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// A tick ahead of the managers' start, on the common 30 s grid
+fn grid_tick() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let tick = now / EPOCH_TICK_SECS + 1000;
+    tick - tick % 6
+}
+
+#[test]
+fn derivation_depends_on_key_and_epoch_only() {
+    let a = SaltManager::new(KEY);
+    let b = SaltManager::new(KEY);
+    let other = SaltManager::new(b"another key 0123456");
+    assert_eq!(a.derive(42), b.derive(42));
+    assert_ne!(a.derive(42), a.derive(43));
+    assert_ne!(a.derive(42).nonce, other.derive(42).nonce);
+    for epoch in 0..64 {
+        let s = a.derive(epoch);
+        assert_eq!(s.epoch, epoch);
+        assert_eq!(s.phi % 2, 1, "phi must stay odd");
+        assert_ne!(s.key, [0; 32]);
+    }
+}
+
+#[test]
+fn pressure_tiers_shorten_the_period() {
+    let mut m = SaltManager::new(KEY);
+    let periods: Vec<u64> = [0.0, 0.3, 0.6, 0.9, 1.0, 7.0, f64::NAN]
+        .iter()
+        .map(|&p| {
+            m.set_pressure(p);
+            m.period().as_secs()
+        })
+        .collect();
+    assert_eq!(periods, [30, 15, 10, 5, 5, 5, 30]);
+
+    // Each tier lands on its own multiple of the 5 s tick; all of them
+    // meet on the 30 s grid
+    let g = grid_tick();
+    for (p, ticks) in [(0.0, 6), (0.3, 3), (0.6, 2), (1.0, 1)] {
+        m.set_pressure(p);
+        let e = m.epoch_at(g * EPOCH_TICK_SECS + 20);
+        assert_eq!(e % ticks, 0);
+        assert!(e <= g + 4 && e + ticks > g + 4);
+        assert_eq!(m.epoch_at(g * EPOCH_TICK_SECS), g);
+    }
+}
+
+#[test]
+fn epochs_never_move_backwards_when_pressure_falls() {
+    let mut m = SaltManager::new(KEY);
+    // One tick past the 30 s grid: the 5 s tier rotates to g + 1, the
+    // 30 s tier would point back at g
+    let g = grid_tick();
+    let t = (g + 1) * EPOCH_TICK_SECS;
+    m.set_pressure(1.0);
+    assert!(m.refresh_at(at(t)));
+    assert_eq!(m.current().epoch, g + 1);
+
+    m.set_pressure(0.0);
+    assert_eq!(m.epoch_at(t), g);
+    assert!(!m.refresh_at(at(t)), "moved back to a used epoch");
+    assert_eq!(m.current().epoch, g + 1);
+    m.set_pressure(1.0);
+    assert!(!m.refresh_at(at(t)), "flapped between two epochs");
+
+    // The coarse grid takes over once it passes the current epoch
+    m.set_pressure(0.0);
+    assert!(!m.refresh_at(at(t + 20)));
+    assert!(m.refresh_at(at(t + 25)));
+    assert_eq!(m.current().epoch, g + 6);
+
+    // A clock stepping back does not rotate either
+    assert!(!m.refresh_at(at(t - 300)));
+    assert_eq!(m.current().epoch, g + 6);
+}
+
+#[test]
+fn packet_pressure_latches_until_rotation() {
+    let mut m = SaltManager::new(KEY);
+    let t = grid_tick() * EPOCH_TICK_SECS;
+    m.set_pressure(0.0);
+    assert!(m.refresh_at(at(t)));
+    m.report_pressure(0.9);
+    // A calm packet after the hostile one does not lower the tier
+    m.report_pressure(0.0);
+    assert_eq!(m.pressure(), 0.9);
+    assert_eq!(m.period().as_secs(), 5);
+    assert!(m.refresh_at(at(t + 5)));
+    assert_eq!(m.pressure(), 0.0);
+    assert_eq!(m.period().as_secs(), 30);
+}
+
+#[test]
+fn previous_epoch_is_accepted_during_the_overlap_only() {
+    let overlap = Duration::from_secs(EPOCH_TICK_SECS);
+    let mut m = SaltManager::new(KEY).with_overlap(overlap);
+    let first = m.current();
+    assert!(m.previous().is_none());
+    assert!(m.accepts(first.epoch));
+
+    m.set_pressure(1.0);
+    let rotated = SystemTime::now() + Duration::from_secs(EPOCH_TICK_SECS);
+    assert!(m.refresh_at(rotated));
+    let second = m.current();
+    assert!(second.epoch > first.epoch);
+
+    let inside = rotated + overlap / 2;
+    assert_eq!(m.previous_at(inside), Some(first));
+    assert!(m.in_overlap_at(inside));
+    assert!(m.accepts_at(first.epoch, inside) && m.accepts_at(second.epoch, inside));
+    assert!(!m.accepts_at(first.epoch - 1, inside));
+
+    let after = rotated + overlap;
+    assert!(!m.in_overlap_at(after));
+    assert_eq!(m.previous_at(after), None);
+    assert!(!m.accepts_at(first.epoch, after));
+    assert!(m.accepts_at(second.epoch, after));
+}
@@ -0,0 +1,165 @@
+// Feature schema: the generated kernel header must match src/schema.rs,
+// and the emulator / payload extractor must stay inside its layout.
+//
+// A stale header fails `kernel_header_is_current`; regenerate it with
+// `TIGER_BLESS=1 cargo test --test schema` (or `make schema`).
+
+use std::fs;
+use std::mem::{offset_of, size_of, size_of_val};
+use std::path::Path;
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::schema::{
+    self, attr, denormalize, kf, normalize, pack, Normalization, ATTR_COUNT, ATTR_SLOTS,
+    KERNEL_FIELDS, KERNEL_FIELD_COUNT, PKT_META_FIELDS, PKT_META_SIZE, RawPktMeta, TIGER_SAMPLE_SIZE,
+};
+use tiger_delta_ai_safety::xdp_emulator::{feature_vector, kernel_fields, PacketMeta};
+
+const HEADER: &str = "src/kernel/tiger_schema.h";
+
+fn header_path() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(HEADER)
+}
+
+fn sample_meta() -> PacketMeta {
+    PacketMeta {
+        saddr: 0x0100_000A,
+        daddr: 0x0200_000A,
+        len: 0x1C00,
+        protocol: 17,
+        is_v6: true,
+        vlan: 0x0FFF,
+        sport: 0x409C,
+        dport: 0xB822,
+        tcp_flags: 0x12,
+    }
+}
+
+#[test]
+fn kernel_header_is_current() {
+    let generated = schema::kernel_header();
+    let path = header_path();
+    if std::env::var_os("TIGER_BLESS").is_some() {
+        fs::write(&path, &generated).expect("write tiger_schema.h");
+        return;
+    }
+    let on_disk = fs::read_to_string(&path).expect("read tiger_schema.h");
+    assert!(
+        on_disk == generated,
+        "{} is stale: run `TIGER_BLESS=1 cargo test --test schema`",
+        HEADER
+    );
+}
+
+#[test]
+fn raw_meta_sits_at_the_offsets_the_header_asserts() {
+    // `_Static_assert(__builtin_offsetof(struct pkt_meta, <name>) == <off>, ...)`
+    let header = fs::read_to_string(header_path()).unwrap();
+    let c_offsets: Vec<(&str, usize)> = header
+        .lines()
+        .filter_map(|l| {
+            let rest = l.strip_prefix("_Static_assert(__builtin_offsetof(struct pkt_meta, ")?;
+            let (name, rest) = rest.split_once(')')?;
+            let off = rest.trim_start().strip_prefix("==")?.split(',').next()?.trim().parse().ok()?;
+            Some((name, off))
+        })
+        .collect();
+    let c_size: usize = header
+        .lines()
+        .find_map(|l| l.strip_prefix("_Static_assert(sizeof(struct pkt_meta) == "))
+        .and_then(|rest| rest.split(',').next())
+        .and_then(|n| n.trim().parse().ok())
+        .expect("pkt_meta size assert");
+
+    let m = RawPktMeta::default();
+    let rust = [
+        ("saddr", offset_of!(RawPktMeta, saddr), size_of_val(&m.saddr)),
+        ("daddr", offset_of!(RawPktMeta, daddr), size_of_val(&m.daddr)),
+        ("len", offset_of!(RawPktMeta, len), size_of_val(&m.len)),
+        ("protocol", offset_of!(RawPktMeta, protocol), size_of_val(&m.protocol)),
+        ("is_v6", offset_of!(RawPktMeta, is_v6), size_of_val(&m.is_v6)),
+        ("vlan", offset_of!(RawPktMeta, vlan), size_of_val(&m.vlan)),
+        ("sport", offset_of!(RawPktMeta, sport), size_of_val(&m.sport)),
+        ("dport", offset_of!(RawPktMeta, dport), size_of_val(&m.dport)),
+        ("tcp_flags", offset_of!(RawPktMeta, tcp_flags), size_of_val(&m.tcp_flags)),
+    ];
+    assert_eq!(c_offsets.len(), rust.len());
+    for ((field, &(c_name, c_off)), &(name, off, size)) in PKT_META_FIELDS.iter().zip(&c_offsets).zip(&rust) {
+        assert_eq!((c_name, c_off), (name, off), "pkt_meta.{} moved", name);
+        assert_eq!((field.name, field.size), (name, size), "pkt_meta.{} resized", name);
+    }
+    assert_eq!(size_of::<RawPktMeta>(), c_size);
+}
+
+#[test]
+fn layout_is_valid_and_sized() {
+    assert!(schema::kernel_layout_is_valid());
+    assert_eq!(PKT_META_SIZE, 20);
+    assert_eq!(TIGER_SAMPLE_SIZE, 40);
+    assert_eq!(std::mem::size_of::<schema::RawPktMeta>(), PKT_META_SIZE);
+    assert_eq!(std::mem::size_of::<schema::RawSample>(), TIGER_SAMPLE_SIZE);
+}
+
+#[test]
+fn pack_matches_the_historic_hand_packed_vector() {
+    // The layout before the schema existed: folds must not change
+    let m = sample_meta();
+    let (rx_queue, time_ns) = (7u32, 123_456_789_012u64);
+    let legacy = [
+        ((m.saddr as u64) << 32) | m.daddr as u64,
+        ((m.is_v6 as u64) << 56) | ((m.protocol as u64) << 48) | m.len as u64,
+        ((m.vlan as u64) << 32) | rx_queue as u64,
+        time_ns >> 22,
+        ((m.sport as u64) << 48) | ((m.dport as u64) << 32) | m.tcp_flags as u64,
+    ];
+    assert_eq!(feature_vector(&m, rx_queue, time_ns), legacy);
+}
+
+#[test]
+fn every_field_round_trips_through_pack() {
+    let f = kernel_fields(&sample_meta(), 7, 123_456_789_012);
+    let v = pack(&f);
+    for (i, field) in KERNEL_FIELDS.iter().enumerate() {
+        assert_eq!(field.extract(&v), f[i] & field.mask(), "field {}", field.name);
+    }
+    // Values wider than the slot are truncated, never smeared into neighbours
+    let mut wide = [0u64; KERNEL_FIELD_COUNT];
+    wide[kf::TCP_FLAGS] = 0xFFFF;
+    let v = pack(&wide);
+    assert_eq!(KERNEL_FIELDS[kf::DPORT].extract(&v), 0);
+    assert_eq!(KERNEL_FIELDS[kf::TCP_FLAGS].extract(&v), 0xFF);
+}
+
+#[test]
+fn payload_attrs_fit_the_declared_widths() {
+    let payload = vec![0xFFu8; schema::MAX_PAYLOAD as usize];
+    let a = payload_attrs(u16::MAX, 39, &payload);
+    for (i, slot) in ATTR_SLOTS.iter().enumerate() {
+        let limit = if slot.bits == 0 { 0 } else { (1i64 << slot.bits) - 1 };
+        assert!(a[i] <= limit, "{} = {} exceeds {} bits", slot.name, a[i], slot.bits);
+    }
+    assert_eq!(a[attr::BYTE_WEIGHT], 255 * schema::MAX_PAYLOAD);
+}
+
+#[test]
+fn normalization_is_bounded_and_invertible() {
+    let a = payload_attrs(40000, 12, b"GET / HTTP/1.1\r\n");
+    let n = normalize(&a);
+    assert!(n.iter().all(|x| (0.0..=1.0).contains(x)));
+
+    let back = denormalize(&n);
+    for i in 0..ATTR_COUNT {
+        let tolerance = match ATTR_SLOTS[i].normalization {
+            Normalization::Log { .. } => (a[i] / 1000).max(1),
+            _ => 0,
+        };
+        assert!((back[i] - a[i]).abs() <= tolerance, "{}: {} vs {}", ATTR_SLOTS[i].name, back[i], a[i]);
+    }
+
+    // Out-of-range inputs clamp instead of escaping [0, 1]
+    let mut huge = [i64::MAX; ATTR_COUNT];
+    huge[attr::LENGTH] = -5;
+    let n = normalize(&huge);
+    assert_eq!(n[attr::LENGTH], 0.0);
+    assert_eq!(n[attr::SRC_PORT], 1.0);
+    assert_eq!(n[attr::RESERVED_8], 0.0);
+}
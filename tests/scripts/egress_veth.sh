@@ -0,0 +1,64 @@
+#!/bin/bash
+# TigerΔ egress observer test on a veth pair (requires root, bpftool)
+#
+# tgx0 (host, XDP + TC egress) <-> tgx1 (netns tiger_egress, 10.78.0.2)
+# Хост «маякує» на 10.78.0.2 з рівним інтервалом; вихідні пакети мають
+# потрапити в egress_state (per-destination) і в розділ egress звіту.
+
+set -u
+
+LOADER=${LOADER:-target/release/tiger_loader}
+NS=tiger_egress
+PIN_ROOT=$(mktemp -d /sys/fs/bpf/tiger_egress_test.XXXX)
+STATS=$(mktemp /tmp/tiger_egress_stats.XXXX.json)
+LOG=$(mktemp /tmp/tiger_egress_loader.XXXX.log)
+DST_KEY="hex 0a 4e 00 02"   # 10.78.0.2, raw network order як у pkt_meta.daddr
+BEACONS=20
+
+cleanup() {
+    [ -n "${LOADER_PID:-}" ] && kill -INT "$LOADER_PID" 2>/dev/null && wait "$LOADER_PID"
+    ip link del tgx0 2>/dev/null
+    ip netns del $NS 2>/dev/null
+    rm -rf "$PIN_ROOT" "$STATS"
+}
+trap cleanup EXIT
+
+fail() {
+    echo "❌ $1"
+    echo "--- loader log ---"
+    cat "$LOG"
+    exit 1
+}
+
+ip netns add $NS
+ip link add tgx0 type veth peer name tgx1
+ip link set tgx1 netns $NS
+ip addr add 10.78.0.1/24 dev tgx0
+ip link set tgx0 up
+ip -n $NS addr add 10.78.0.2/24 dev tgx1
+ip -n $NS link set tgx1 up
+ip -n $NS link set lo up
+
+TIGER_PIN_ROOT=$PIN_ROOT TIGER_STATS_FILE=$STATS TIGER_POLICY=monitor \
+    "$LOADER" --egress tgx0 >"$LOG" 2>&1 &
+LOADER_PID=$!
+sleep 2
+kill -0 $LOADER_PID 2>/dev/null || fail "loader exited early"
+grep -q "Egress observer attached" "$LOG" || fail "egress observer not attached"
+
+for i in $(seq 1 $BEACONS); do
+    echo -n "BEACON_$i" > /dev/udp/10.78.0.2/4444
+    sleep 0.2
+done
+sleep 1
+
+MAP=$PIN_ROOT/tgx0/egress_state
+bpftool -j map lookup pinned "$MAP" key $DST_KEY >/tmp/tiger_egress_dst.json 2>/dev/null \
+    || fail "no egress state for 10.78.0.2"
+# packets — друге 8-байтове поле struct egress_dst
+PACKETS=$(python3 -c "import json;v=bytes(int(b,16) for b in json.load(open('/tmp/tiger_egress_dst.json'))['value']);print(int.from_bytes(v[8:16],'little'))")
+[ "$PACKETS" -ge "$BEACONS" ] || fail "expected >= $BEACONS packets to 10.78.0.2, got $PACKETS"
+
+grep -q '"egress":{"destinations":[1-9]' "$STATS" || fail "egress section missing from stats report"
+
+echo "✅ Egress path: $PACKETS packets to 10.78.0.2 folded per destination"
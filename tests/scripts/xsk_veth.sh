@@ -0,0 +1,68 @@
+#!/bin/bash
+# TigerΔ AF_XDP inspector test on a veth pair (requires root, bpftool)
+#
+# tgx0 (host, XDP + XSK) <-> tgx1 (netns tiger_xsk, 10.77.0.2)
+# Джерело вручну отримує бюджет в inspect_sources; його UDP-пакети мають
+# піти в XSK-сокет (лічильник redirected), пройти через payload-екстрактор
+# і зняти бюджет (вердикт записано в source_policy або інспекцію завершено).
+
+set -u
+
+LOADER=${LOADER:-target/release/tiger_loader}
+NS=tiger_xsk
+PIN_ROOT=$(mktemp -d /sys/fs/bpf/tiger_xsk_test.XXXX)
+STATS=$(mktemp /tmp/tiger_xsk_stats.XXXX.json)
+LOG=$(mktemp /tmp/tiger_xsk_loader.XXXX.log)
+SRC_KEY="hex 0a 4d 00 02"   # 10.77.0.2, raw network order як у pkt_meta.saddr
+
+cleanup() {
+    [ -n "${LOADER_PID:-}" ] && kill -INT "$LOADER_PID" 2>/dev/null && wait "$LOADER_PID"
+    ip link del tgx0 2>/dev/null
+    ip netns del $NS 2>/dev/null
+    rm -rf "$PIN_ROOT" "$STATS"
+}
+trap cleanup EXIT
+
+fail() {
+    echo "❌ $1"
+    echo "--- loader log ---"
+    cat "$LOG"
+    exit 1
+}
+
+ip netns add $NS
+ip link add tgx0 type veth peer name tgx1
+ip link set tgx1 netns $NS
+ip addr add 10.77.0.1/24 dev tgx0
+ip link set tgx0 up
+ip -n $NS addr add 10.77.0.2/24 dev tgx1
+ip -n $NS link set tgx1 up
+ip -n $NS link set lo up
+
+TIGER_PIN_ROOT=$PIN_ROOT TIGER_STATS_FILE=$STATS TIGER_POLICY=monitor \
+TIGER_SAMPLE_RATE=1 TIGER_XSK_QUEUES=1 \
+    "$LOADER" --daemon tgx0 >"$LOG" 2>&1 &
+LOADER_PID=$!
+sleep 2
+kill -0 $LOADER_PID 2>/dev/null || fail "loader exited early"
+grep -q "AF_XDP inspector" "$LOG" || fail "inspector not started"
+
+MAP=$PIN_ROOT/tgx0/inspect_sources
+bpftool map update pinned "$MAP" key $SRC_KEY value hex 20 00 00 00 || fail "cannot seed $MAP"
+
+for i in $(seq 1 40); do
+    ip netns exec $NS bash -c "echo -n 'TIGER_PROBE_PAYLOAD_$i' > /dev/udp/10.77.0.1/8888"
+done
+sleep 2
+
+REDIRECTED=$(grep -o '"redirected":[0-9]*' "$STATS" | head -1 | cut -d: -f2)
+[ "${REDIRECTED:-0}" -gt 0 ] || fail "no packets redirected to AF_XDP"
+grep -q "inspected=[1-9]" "$LOG" || fail "inspector saw no frames"
+
+if bpftool map lookup pinned "$MAP" key $SRC_KEY 2>/dev/null | grep -q "value"; then
+    BUDGET=$(bpftool map lookup pinned "$MAP" key $SRC_KEY | grep -o 'value: [0-9a-f ]*')
+    # Бюджет вичерпано (або залишок, якщо вердикт ще невизначений)
+    echo "ℹ️ inspect budget left: $BUDGET"
+fi
+
+echo "✅ AF_XDP path: $REDIRECTED packets redirected and judged in userspace"
@@ -0,0 +1,225 @@
+// Monte Carlo twin: projections run on copies of the cores, follow the
+// observed traffic mix, and turn into pre-emption only every `period`
+// packets.
+
+use tiger_delta_ai_safety::atomic_core::AtomicCore;
+use tiger_delta_ai_safety::brain::{Action, Brain};
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::lagrange::LagrangeEquilibrium;
+use tiger_delta_ai_safety::lumis::{LumisClock, LumisCore};
+use tiger_delta_ai_safety::salt::SaltManager;
+use tiger_delta_ai_safety::simul::{Observation, Projection, ProjectionConfig, SimulMode, SimulUnit, MIN_OBSERVATIONS};
+use tiger_delta_ai_safety::string_state::StringState;
+use tiger_delta_ai_safety::threshold::ThresholdController;
+
+/// Seeded xorshift uniforms in [0, 1)
+fn uniforms(mut seed: u64) -> impl Iterator<Item = f64> {
+    std::iter::repeat_with(move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+/// Entropy at the Atomic equilibrium and a fold far below the Lagrange
+/// zone: the cores never decide Attack on it
+fn calm(u: f64) -> Observation {
+    Observation {
+        impact: 0.05,
+        entropy: 0.505 + u * 0.02,
+        compact: 0.1,
+        arrival_pressure: 0.1,
+        rate_pps: 200.0,
+    }
+}
+
+/// Entropy anywhere in the clamp range: threat probability all over [0, 1]
+fn hostile(u: f64) -> Observation {
+    Observation {
+        impact: 0.3,
+        entropy: u * 10.0,
+        compact: 0.1,
+        arrival_pressure: 0.8,
+        rate_pps: 200.0,
+    }
+}
+
+/// A unit that saw `n` observations, `hostile_share` of them hostile
+fn unit_with(n: usize, hostile_share: f64, seed: u64) -> SimulUnit {
+    let mut unit = SimulUnit::new();
+    let mut u = uniforms(seed);
+    for _ in 0..n {
+        let pick = u.next().unwrap();
+        let v = u.next().unwrap();
+        unit.observe(if pick < hostile_share { hostile(v) } else { calm(v) });
+    }
+    unit
+}
+
+struct Cores {
+    atomic: AtomicCore,
+    lagrange: LagrangeEquilibrium,
+    lumis: LumisCore,
+    threshold: ThresholdController,
+}
+
+impl Cores {
+    fn new() -> Self {
+        Self {
+            atomic: AtomicCore::new(100),
+            lagrange: LagrangeEquilibrium::new(1000.0),
+            lumis: LumisCore::with_clock(LumisClock::Injected),
+            threshold: ThresholdController::default(),
+        }
+    }
+
+    fn project(&self, unit: &mut SimulUnit) -> Option<Projection> {
+        unit.project(&self.atomic, &self.lagrange, &self.lumis, 1000.0, &self.threshold)
+    }
+
+    fn debug(&self) -> String {
+        format!("{:?} {:?} {:?} {:?}", self.atomic, self.lagrange, self.lumis, self.threshold)
+    }
+}
+
+#[test]
+fn no_projection_without_enough_history() {
+    let cores = Cores::new();
+    let mut unit = unit_with(MIN_OBSERVATIONS - 1, 0.0, 1);
+    assert_eq!(cores.project(&mut unit), None);
+    unit.observe(calm(0.5));
+    assert!(cores.project(&mut unit).is_some());
+    assert_eq!(unit.observations(), MIN_OBSERVATIONS);
+}
+
+#[test]
+fn projection_leaves_the_cores_untouched() {
+    let mut cores = Cores::new();
+    let mut mass = 1000.0;
+    for (i, u) in uniforms(3).take(200).enumerate() {
+        cores.lumis.set_now(i as u64 * 5_000_000);
+        cores.atomic.sharpen_angles(u * 0.3);
+        cores.atomic.find_the_middle(0.5 + u * 0.05);
+        cores.lagrange.stabilize(u, u);
+        cores.lumis.tick_cycle(u, 0.2, &mut mass);
+    }
+    let before = cores.debug();
+    let mut unit = unit_with(400, 0.5, 4);
+    let projection = cores.project(&mut unit).unwrap();
+    assert!(projection.breaches > 0);
+    assert_eq!(cores.debug(), before);
+    assert_eq!(unit.last_projection(), Some(&projection));
+}
+
+#[test]
+fn breach_probability_follows_the_traffic_mix() {
+    let cores = Cores::new();
+    let project = |share: f64| cores.project(&mut unit_with(512, share, 11)).unwrap();
+
+    let quiet = project(0.0);
+    assert_eq!(quiet.breaches, 0);
+    assert_eq!(quiet.steps_to_breach, None);
+
+    let mixed = project(0.05);
+    let hostile = project(1.0);
+    assert!(mixed.breach_probability > 0.0 && mixed.breach_probability < hostile.breach_probability);
+    assert!(hostile.breach_probability > 0.9, "{}", hostile.breach_probability);
+    // Hostile futures break sooner
+    assert!(hostile.steps_to_breach.unwrap() < mixed.steps_to_breach.unwrap());
+    assert!(mixed.steps_to_breach.unwrap() <= mixed.horizon as f64);
+}
+
+#[test]
+fn time_to_breach_uses_the_observed_rate() {
+    let cores = Cores::new();
+    let p = cores.project(&mut unit_with(512, 1.0, 21)).unwrap();
+    let steps = p.steps_to_breach.unwrap();
+    assert!((p.seconds_to_breach.unwrap() - steps / 200.0).abs() < 1e-12);
+    assert!(p.to_json().contains(&format!("\"breaches\":{}", p.breaches)));
+    assert!(p.summary().starts_with("breach p="));
+
+    // Without a rate there is no time estimate
+    let mut unit = SimulUnit::new();
+    for u in uniforms(22).take(100) {
+        unit.observe(Observation { rate_pps: 0.0, ..hostile(u) });
+    }
+    let p = cores.project(&mut unit).unwrap();
+    assert!(p.steps_to_breach.is_some());
+    assert_eq!(p.seconds_to_breach, None);
+    assert!(p.to_json().ends_with("\"seconds_to_breach\":null}"));
+}
+
+#[test]
+fn projections_are_reproducible_per_seed() {
+    let cores = Cores::new();
+    let config = ProjectionConfig { trajectories: 200, horizon: 16, ..ProjectionConfig::default() };
+    let run = |seed: u64| {
+        let mut unit = unit_with(512, 0.05, 31).with_projection(ProjectionConfig { seed, ..config });
+        cores.project(&mut unit).unwrap()
+    };
+    assert_eq!(run(7), run(7));
+    assert_eq!(run(7).trajectories, 200);
+    assert_eq!(run(7).horizon, 16);
+    // Another seed draws other futures from the same mix: close, not equal
+    let (a, b) = (run(7).breach_probability, run(8).breach_probability);
+    assert!((a - b).abs() < 0.2, "{} vs {}", a, b);
+}
+
+#[test]
+fn forecast_runs_every_period_and_preempts_above_the_probability() {
+    let cores = Cores::new();
+    let config = ProjectionConfig { period: 10, preempt_probability: 0.5, ..ProjectionConfig::default() };
+    let mut unit = unit_with(MIN_OBSERVATIONS, 1.0, 41).with_mode(SimulMode::MonteCarlo).with_projection(config);
+    assert_eq!(unit.mode(), SimulMode::MonteCarlo);
+
+    let forecast =
+        |unit: &mut SimulUnit| unit.forecast(&cores.atomic, &cores.lagrange, &cores.lumis, 1000.0, &cores.threshold);
+    // The history already covers one period
+    assert!(forecast(&mut unit));
+    for i in 1..=30 {
+        unit.observe(hostile(0.9));
+        assert_eq!(forecast(&mut unit), i % 10 == 0, "observation {}", i);
+    }
+
+    // A calm window never crosses the line
+    let mut calm_unit = unit_with(100, 0.0, 42).with_mode(SimulMode::MonteCarlo).with_projection(config);
+    assert!(!forecast(&mut calm_unit));
+    assert_eq!(calm_unit.last_projection().unwrap().breaches, 0);
+}
+
+#[test]
+fn modes_parse_and_print() {
+    for mode in SimulMode::ALL {
+        assert_eq!(SimulMode::parse(mode.as_str()), Some(mode));
+    }
+    assert_eq!(SimulMode::parse("MC"), Some(SimulMode::MonteCarlo));
+    assert_eq!(SimulMode::parse("dice"), None);
+    assert_eq!(SimulMode::default(), SimulMode::Scalar);
+}
+
+#[test]
+fn brain_projects_from_its_own_history() {
+    let mut state = StringState::with_salts(SaltManager::new(b"simul test key 0123"));
+    let mut brain = Brain::new().with_simul_mode(SimulMode::MonteCarlo);
+    let period = brain.simul.config().period as usize;
+    let mut preempted = 0;
+    for i in 0..400u64 {
+        let attrs = payload_attrs(40_000, 12, format!("GET /{} HTTP/1.1\r\n", i % 13).as_bytes());
+        let v = brain.process_at(&attrs, i * 10_000_000, &mut state);
+        if v.action == Action::Preempt {
+            preempted += 1;
+        }
+        if brain.simul.observations() < MIN_OBSERVATIONS {
+            assert!(brain.simul.last_projection().is_none());
+        }
+    }
+    // Projections only run once per period of observed packets
+    assert!(brain.simul.last_projection().is_some());
+    assert!(preempted <= 400 / period, "{} preempts", preempted);
+
+    let before = format!("{:?} {:?} {:?}", brain.atomic, brain.lagrange, brain.lumis);
+    let projection = brain.project().unwrap();
+    assert_eq!(format!("{:?} {:?} {:?}", brain.atomic, brain.lagrange, brain.lumis), before);
+    assert_eq!(brain.simul.last_projection(), Some(&projection));
+}
@@ -0,0 +1,131 @@
+// Count-min sketch: the emulator's per-CPU sketch (same hashing as the
+// kernel) checked against exact counts and the documented error bounds.
+
+use tiger_delta_ai_safety::sketch::{self, top_k, CountMinSketch, CMS_DEPTH};
+use tiger_delta_ai_safety::xdp_emulator::{parse_frame, XdpEmulator, IPPROTO_UDP};
+
+fn udp_from(src: [u8; 4]) -> Vec<u8> {
+    let mut f = vec![0u8; 12];
+    f.extend_from_slice(&0x0800u16.to_be_bytes());
+    f.extend_from_slice(&[0x45, 0, 0, 28, 0, 0, 0, 0, 64, IPPROTO_UDP, 0, 0]);
+    f.extend_from_slice(&src);
+    f.extend_from_slice(&[10, 0, 0, 2]);
+    f.extend_from_slice(&[0x9C, 0x40, 0x22, 0xB8, 0, 8, 0, 0]);
+    f
+}
+
+/// Zipf-like stream: source i sends max(1, n / (i + 1)) packets.
+/// Returns (saddr key, frame, exact count) per source.
+fn zipf_sources(n: usize) -> Vec<(u32, Vec<u8>, u32)> {
+    (0..n)
+        .map(|i| {
+            let frame = udp_from([10, 1, (i >> 8) as u8, i as u8]);
+            let key = parse_frame(&frame).unwrap().saddr;
+            (key, frame, (n / (i + 1)).max(1) as u32)
+        })
+        .collect()
+}
+
+/// Interleaves the sources round-robin, as concurrent senders would.
+fn feed(emu: &mut XdpEmulator, sources: &[(u32, Vec<u8>, u32)]) {
+    let max = sources.iter().map(|s| s.2).max().unwrap_or(0);
+    for round in 0..max {
+        for (_, frame, count) in sources {
+            if round < *count {
+                emu.process(frame, 0, 0);
+            }
+        }
+    }
+}
+
+#[test]
+fn never_undercounts_and_stays_within_bound() {
+    let sources = zipf_sources(2000);
+    let mut emu = XdpEmulator::new();
+    feed(&mut emu, &sources);
+
+    let total: u64 = sources.iter().map(|s| s.2 as u64).sum();
+    assert_eq!(emu.sketch.total(), total);
+
+    let bound = emu.sketch.error_bound();
+    let mut over = 0;
+    for (key, _, count) in &sources {
+        let est = emu.sketch.estimate(*key);
+        assert!(est >= *count, "undercount: est={} count={}", est, count);
+        if (est - count) as f64 > bound {
+            over += 1;
+        }
+    }
+    // P[err > εN] <= δ per key; allow 2x slack over the expectation
+    let allowed = (2.0 * sketch::delta() * sources.len() as f64).ceil() as usize;
+    assert!(over <= allowed, "{} keys above εN={:.1}, allowed {}", over, bound, allowed);
+}
+
+#[test]
+fn top_k_recovers_heavy_hitters() {
+    let sources = zipf_sources(2000);
+    let mut emu = XdpEmulator::new();
+    feed(&mut emu, &sources);
+
+    let top = top_k(&emu.sketch, &emu.candidates, 10);
+    let expected: Vec<u32> = sources.iter().take(10).map(|s| s.0).collect();
+    let got: Vec<u32> = top.iter().map(|t| t.0).collect();
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn candidates_marked_at_powers_of_two() {
+    let light = udp_from([192, 0, 2, 1]);
+    let heavy = udp_from([192, 0, 2, 2]);
+    let mut emu = XdpEmulator::new();
+    for _ in 0..63 {
+        emu.process(&light, 0, 0);
+    }
+    for _ in 0..64 {
+        emu.process(&heavy, 0, 0);
+    }
+    assert!(!emu.candidates.contains(&parse_frame(&light).unwrap().saddr));
+    assert!(emu.candidates.contains(&parse_frame(&heavy).unwrap().saddr));
+    assert!(sketch::is_candidate_mark(128) && !sketch::is_candidate_mark(96));
+}
+
+#[test]
+fn per_cpu_sketches_merge_into_the_stream_sketch() {
+    let sources = zipf_sources(300);
+    let (mut cpu0, mut cpu1, mut single) = (XdpEmulator::new(), XdpEmulator::new(), XdpEmulator::new());
+    for (i, (_, frame, count)) in sources.iter().enumerate() {
+        // RSS: a source stays on one CPU
+        let cpu = if i % 2 == 0 { &mut cpu0 } else { &mut cpu1 };
+        for _ in 0..*count {
+            cpu.process(frame, 0, 0);
+            single.process(frame, 0, 0);
+        }
+    }
+
+    let mut merged = CountMinSketch::new();
+    for row in 0..CMS_DEPTH {
+        merged.add_row(row, cpu0.sketch.row(row));
+        merged.add_row(row, cpu1.sketch.row(row));
+    }
+    assert_eq!(merged, single.sketch);
+}
+
+#[test]
+fn windows_are_differences_of_cumulative_reads() {
+    let a = udp_from([198, 51, 100, 1]);
+    let key = parse_frame(&a).unwrap().saddr;
+    let mut emu = XdpEmulator::new();
+    for _ in 0..500 {
+        emu.process(&a, 0, 0);
+    }
+    let earlier = emu.sketch.clone();
+    for _ in 0..120 {
+        emu.process(&a, 0, 0);
+    }
+
+    let window = emu.sketch.since(&earlier);
+    assert_eq!(window.estimate(key), 120);
+    assert_eq!(window.total(), 120);
+
+    assert_eq!(top_k(&window, &emu.candidates, 1), vec![(key, 120)]);
+}
@@ -0,0 +1,152 @@
+// Spectral beacon detection: FFT correctness, heartbeat vs. noise vs.
+// flood, byte-series periodicity and synchronized pulse grouping.
+
+use std::f64::consts::TAU;
+use tiger_delta_ai_safety::brain::Brain;
+use tiger_delta_ai_safety::spectrum::{
+    analyze, fft, synchronized_pulses, Periodicity, SpectralSeries, SPECTRUM_BIN_NS,
+};
+use tiger_delta_ai_safety::string_state::StringState;
+use tiger_delta_ai_safety::timing::TimeBuckets;
+
+const MS: u64 = 1_000_000;
+const SEC: u64 = 1_000 * MS;
+
+fn uniform(seed: &mut u64) -> f64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    (*seed >> 11) as f64 / (1u64 << 53) as f64 + f64::EPSILON
+}
+
+/// One packet every `period` (± `jitter`), from `offset`, for `secs`.
+fn heartbeat(series: &mut SpectralSeries, period: u64, jitter: u64, offset: u64, secs: u64, seed: &mut u64) {
+    let mut t = offset;
+    while t < offset + secs * SEC {
+        let wobble = (uniform(seed) * 2.0 * jitter as f64) as u64;
+        series.record(t + wobble - jitter.min(t + wobble), 1, 200);
+        t += period;
+    }
+}
+
+#[test]
+fn fft_matches_naive_dft() {
+    let mut seed = 3;
+    let x: Vec<f64> = (0..64).map(|_| uniform(&mut seed) - 0.5).collect();
+    let (mut re, mut im) = (x.clone(), vec![0.0; 64]);
+    fft(&mut re, &mut im, false);
+    for k in 0..64 {
+        let (mut dr, mut di) = (0.0, 0.0);
+        for (n, &v) in x.iter().enumerate() {
+            let a = -TAU * (k * n) as f64 / 64.0;
+            dr += v * a.cos();
+            di += v * a.sin();
+        }
+        assert!((re[k] - dr).abs() < 1e-9 && (im[k] - di).abs() < 1e-9, "bin {}", k);
+    }
+    // Round trip through the unscaled inverse
+    fft(&mut re, &mut im, true);
+    for (a, b) in re.iter().zip(&x) {
+        assert!((a / 64.0 - b).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn c2_heartbeat_is_a_beacon_at_its_own_frequency() {
+    let mut seed = 11;
+    let mut s = SpectralSeries::new();
+    heartbeat(&mut s, 5 * SEC, 40 * MS, 100 * SEC, 90, &mut seed);
+    let p = s.arrivals();
+    assert!(p.is_beacon(), "{:?}", p);
+    // Fundamental, not a harmonic
+    assert!((p.dominant_hz - 0.2).abs() < 0.01, "{:?}", p);
+    assert!((p.period_s() - 5.0).abs() < 0.3);
+}
+
+#[test]
+fn poisson_and_floods_are_not_beacons() {
+    let mut seed = 5;
+    let mut poisson = SpectralSeries::new();
+    let mut t = 100 * SEC;
+    while t < 190 * SEC {
+        poisson.record(t, 1, 100);
+        t += (-uniform(&mut seed).ln() * 300.0 * MS as f64) as u64;
+    }
+    assert!(!poisson.periodicity().is_beacon(), "{:?}", poisson.periodicity());
+
+    // Constant rate: a flat series has nothing periodic in it
+    let mut flood = SpectralSeries::new();
+    for i in 0..90_000u64 {
+        flood.record(100 * SEC + i * MS, 1, 64);
+    }
+    assert_eq!(flood.arrivals().score, 0.0);
+}
+
+#[test]
+fn periodic_bulk_transfers_show_in_the_byte_series() {
+    // Steady 20 pps of 60 B, plus a 1400 B packet every 8 s instead of one of them
+    let mut s = SpectralSeries::new();
+    for i in 0..1_800u64 {
+        let t = 100 * SEC + i * 50 * MS;
+        let bytes = if i % 160 == 0 { 1400 } else { 60 };
+        s.record(t, 1, bytes);
+    }
+    assert!(!s.arrivals().is_beacon());
+    let p = s.sizes();
+    assert!(p.is_beacon(), "{:?}", p);
+    assert!((p.period_s() - 8.0).abs() < 0.5);
+    assert_eq!(s.periodicity(), p);
+}
+
+#[test]
+fn synchronized_botnet_pulses_are_grouped() {
+    let mut seed = 23;
+    let mut beats = Vec::new();
+    // Five bots commanded to pulse together every 4 s
+    for bot in 0..5u32 {
+        let mut s = SpectralSeries::new();
+        heartbeat(&mut s, 4 * SEC, 20 * MS, 100 * SEC + 500 * MS, 80, &mut seed);
+        beats.push((bot, s.arrivals()));
+    }
+    // Five independent 4 s heartbeats at spread-out phases
+    for host in 0..5u32 {
+        let mut s = SpectralSeries::new();
+        heartbeat(&mut s, 4 * SEC, 20 * MS, 100 * SEC + host as u64 * 800 * MS, 80, &mut seed);
+        beats.push((100 + host, s.arrivals()));
+    }
+
+    let groups = synchronized_pulses(&beats[..5], 3);
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].sources.len(), 5);
+    assert!((groups[0].dominant_hz - 0.25).abs() < 0.02);
+
+    assert!(synchronized_pulses(&beats[5..], 3).is_empty());
+    // Too few members
+    assert!(synchronized_pulses(&beats[..2], 3).is_empty());
+}
+
+#[test]
+fn analyze_reports_phase_on_the_absolute_grid() {
+    // Pulses at absolute bins ≡ 3 (mod 8)
+    let start = 1_000u64;
+    let series: Vec<f64> = (0..128u64).map(|i| if (start + i) % 8 == 3 { 1.0 } else { 0.0 }).collect();
+    let p: Periodicity = analyze(&series, SPECTRUM_BIN_NS, start);
+    assert_eq!(p.lag, 8);
+    assert!((p.phase - TAU * 3.0 / 8.0).abs() < 1e-9);
+}
+
+#[test]
+fn periodicity_reaches_the_verdict() {
+    let attrs = [40000, 64, 71, 5000, 5000 % 111, 64 % 7, 300, 9, 0, 0];
+    let mut state = StringState::new();
+    let mut brain = Brain::new();
+    let mut verdict = brain.process_at(&attrs, 0, &mut state);
+    for i in 1..=40u64 {
+        verdict = brain.process_at(&attrs, i * 2 * SEC, &mut state);
+    }
+    assert!(verdict.periodicity > 0.6, "{}", verdict.periodicity);
+    assert!((verdict.dominant_hz - 0.5).abs() < 0.02);
+
+    let mut buckets = TimeBuckets::new();
+    assert_eq!(buckets.record(0).periodicity, Periodicity::default());
+}
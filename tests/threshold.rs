@@ -0,0 +1,143 @@
+// Adaptive threshold: steers the alert rate to its target, answers
+// false-positive feedback, breathes with Lumis, and never steps or
+// strays outside its bounds.
+
+use tiger_delta_ai_safety::brain::{Brain, ATTACK_THRESHOLD};
+use tiger_delta_ai_safety::features::payload_attrs;
+use tiger_delta_ai_safety::lumis::{LumisCore, PHI};
+use tiger_delta_ai_safety::string_state::StringState;
+use tiger_delta_ai_safety::threshold::{ChangeReason, ThresholdConfig, ThresholdController, LOG_CAPACITY};
+
+/// Seeded xorshift uniforms in [0, 1)
+fn uniforms(mut seed: u64) -> impl Iterator<Item = f64> {
+    std::iter::repeat_with(move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed >> 11) as f64 / (1u64 << 53) as f64
+    })
+}
+
+/// Lumis with some entropy, so its threshold oscillates
+fn restless_lumis() -> LumisCore {
+    let mut lumis = LumisCore::new();
+    let mut mass = 1000.0;
+    for _ in 0..400 {
+        lumis.tick_cycle(0.45, 0.3, &mut mass);
+    }
+    assert!(lumis.entropy_level() > 0.1);
+    lumis
+}
+
+#[test]
+fn alert_rate_converges_to_the_target() {
+    let lumis = LumisCore::new();
+    let mut c = ThresholdController::default();
+    let target = c.config().target_alert_rate;
+    let mut alerts = 0;
+    let mut last = Vec::new();
+    // Threat probabilities uniform on [0, 0.95): 1 % alert at ≈ 0.9405
+    for (i, u) in uniforms(17).take(200_000).enumerate() {
+        let p = u * 0.95;
+        let alerted = p > c.threshold();
+        if i >= 100_000 {
+            alerts += alerted as usize;
+            last.push(c.threshold());
+        }
+        c.update(alerted, &lumis);
+    }
+    let rate = alerts as f64 / 100_000.0;
+    assert!((rate - target).abs() < 0.004, "alert rate {} vs target {}", rate, target);
+    let mean = last.iter().sum::<f64>() / last.len() as f64;
+    assert!((mean - 0.9405).abs() < 0.01, "settled at {}", mean);
+}
+
+#[test]
+fn steps_and_values_stay_bounded() {
+    let lumis = restless_lumis();
+    let config = ThresholdConfig::default();
+    let mut c = ThresholdController::new(config);
+    let mut base = c.base();
+    for (i, u) in uniforms(5).take(20_000).enumerate() {
+        // Alert storms, silence and bursts of false-positive reports
+        if i < 10_000 && i % 3000 < 40 {
+            c.report_false_positive();
+        }
+        c.update(u < if i < 10_000 { 0.9 } else { 0.0 }, &lumis);
+        assert!((c.base() - base).abs() <= config.max_step + 1e-12, "step at {}", i);
+        assert!((config.min..=config.max).contains(&c.base()));
+        assert!((config.min..=config.max).contains(&c.threshold()));
+        base = c.base();
+    }
+    // Silence pulls the base down to the floor, never past it
+    assert_eq!(c.base(), config.min);
+}
+
+#[test]
+fn false_positives_raise_the_threshold() {
+    let lumis = LumisCore::new();
+    let mut calm = ThresholdController::default();
+    let mut corrected = ThresholdController::default();
+    for i in 0..2000 {
+        let alerted = i % 100 == 0;
+        if alerted && i < 1000 {
+            corrected.report_false_positive();
+        }
+        calm.update(alerted, &lumis);
+        corrected.update(alerted, &lumis);
+    }
+    assert!(corrected.threshold() > calm.threshold() + 0.01, "{} vs {}", corrected.threshold(), calm.threshold());
+    assert!(corrected.changes().any(|c| c.reason == ChangeReason::FalsePositive));
+    // The pressure decays once the reports stop
+    assert!(corrected.fp_pressure() < 0.1);
+}
+
+#[test]
+fn lumis_breathes_the_effective_threshold() {
+    let lumis = restless_lumis();
+    let quiet = LumisCore::new();
+    let mut c = ThresholdController::default();
+    c.update(false, &quiet);
+    assert!((c.threshold() - c.base()).abs() < 1e-12);
+
+    c.update(false, &lumis);
+    let factor = lumis.dynamic_threshold() / PHI;
+    assert!((c.threshold() - (c.base() * factor).clamp(0.6, 0.98)).abs() < 1e-12);
+    assert!((c.threshold() - c.base()).abs() <= c.base() * 0.1 / PHI);
+}
+
+#[test]
+fn change_log_is_bounded_and_serializable() {
+    let lumis = LumisCore::new();
+    let mut c = ThresholdController::new(ThresholdConfig { max_step: 0.01, ..ThresholdConfig::default() });
+    for i in 0..40_000 {
+        // Alternate storms and silence to keep the threshold moving
+        c.update((i / 3000) % 2 == 0, &lumis);
+    }
+    let changes: Vec<_> = c.changes().collect();
+    assert_eq!(changes.len(), LOG_CAPACITY);
+    for pair in changes.windows(2) {
+        assert_eq!(pair[0].to, pair[1].from);
+        assert!(pair[0].decision < pair[1].decision);
+    }
+    let json = c.to_json();
+    assert!(json.starts_with("{\"threshold\":") && json.contains("\"reason\":\"alert_rate\""));
+}
+
+#[test]
+fn brain_decides_with_the_controller_threshold() {
+    let mut state = StringState::new();
+    let mut brain = Brain::new();
+    let attrs = payload_attrs(443, 12, b"GET / HTTP/1.1\r\n");
+    let first = brain.process(&attrs, &mut state);
+    assert_eq!(first.threshold, ATTACK_THRESHOLD);
+
+    for _ in 0..20 {
+        brain.report_false_positive();
+    }
+    let mut last = first;
+    for _ in 0..50 {
+        last = brain.process(&attrs, &mut state);
+    }
+    assert!(last.threshold > ATTACK_THRESHOLD, "{}", last.threshold);
+}
@@ -0,0 +1,120 @@
+// Arrival timing: bucket alignment with the kernel fold, rate and
+// burstiness estimates, and their effect on the brain.
+
+use tiger_delta_ai_safety::brain::Brain;
+use tiger_delta_ai_safety::string_state::StringState;
+use tiger_delta_ai_safety::timing::{
+    fine_bucket, monotonic_ns, Arrival, SourceClocks, TimeBuckets, FINE, MINUTE, SECOND,
+};
+use tiger_delta_ai_safety::xdp_emulator::{feature_vector, PacketMeta};
+
+const MS: u64 = 1_000_000;
+const SEC: u64 = 1_000 * MS;
+
+#[test]
+fn fine_bucket_is_the_kernel_time_word() {
+    let ts = 987_654_321_012_345;
+    let v = feature_vector(&PacketMeta::default(), 0, ts);
+    assert_eq!(fine_bucket(ts), v[3]);
+
+    let mut t = TimeBuckets::new();
+    assert_eq!(t.record(ts).bucket, v[3]);
+}
+
+#[test]
+fn monotonic_clock_advances() {
+    let a = monotonic_ns();
+    let b = monotonic_ns();
+    assert!(b >= a && a > 0);
+}
+
+#[test]
+fn steady_rate_is_measured_at_every_resolution() {
+    // 200 pps for 2 minutes, evenly paced
+    let mut t = TimeBuckets::new();
+    let mut a = Arrival::default();
+    for i in 0..200 * 120u64 {
+        a = t.record(100 * SEC + i * 5 * MS);
+    }
+    assert!((a.rate_pps[FINE] - 200.0).abs() < 10.0, "{:?}", a.rate_pps);
+    assert!((a.rate_pps[SECOND] - 200.0).abs() < 10.0, "{:?}", a.rate_pps);
+    // Only 2 of the 60 minute buckets are live, the last one partial
+    assert!(a.rate_pps[MINUTE] > 100.0 && a.rate_pps[MINUTE] <= 200.0);
+    // Paced arrivals are not bursty
+    assert!(a.fano[SECOND] < 1.0);
+    assert_eq!(a.burstiness(), 0.0);
+}
+
+#[test]
+fn bursts_raise_burstiness_and_pressure() {
+    // Same 100 packets per second, once paced, once in a single 4 ms burst
+    let mut paced = TimeBuckets::new();
+    let mut bursty = TimeBuckets::new();
+    let (mut p, mut b) = (Arrival::default(), Arrival::default());
+    for s in 0..10u64 {
+        for i in 0..100u64 {
+            p = paced.record(s * SEC + i * 10 * MS);
+            b = bursty.record(s * SEC + i * 10_000);
+        }
+    }
+    assert!(b.fano[FINE] > 10.0, "{:?}", b.fano);
+    assert!(b.burstiness() > 0.8);
+    assert!(b.pressure() > p.pressure());
+    assert!((b.rate_pps[SECOND] - p.rate_pps[SECOND]).abs() < 1e-9);
+}
+
+#[test]
+fn windows_forget_old_buckets() {
+    let mut t = TimeBuckets::new();
+    for i in 0..1000u64 {
+        t.record(i * MS);
+    }
+    // 10 minutes of silence: the second ring is empty again
+    let a = t.record(600 * SEC);
+    assert!(a.rate_pps[SECOND] < 1.0);
+    // Late arrivals outside the window are ignored
+    let before = t.ring(FINE).rate_pps();
+    t.record(SEC);
+    assert_eq!(t.ring(FINE).rate_pps(), before);
+}
+
+#[test]
+fn source_clocks_keep_sources_apart_and_bounded() {
+    let mut clocks = SourceClocks::new(2);
+    for i in 0..500u64 {
+        clocks.record(1u32, i * MS, 64);
+    }
+    let quiet = clocks.record(2u32, 500 * MS, 64);
+    assert!(quiet.rate_pps[SECOND] <= 1.0);
+    assert_eq!(clocks.len(), 2);
+
+    // Full: a third source is measured but not kept while the others are live
+    clocks.record(3u32, SEC, 64);
+    assert_eq!(clocks.len(), 2);
+    // Once the others idle out it takes a slot
+    clocks.record(3u32, 1_000 * SEC, 64);
+    assert_eq!(clocks.len(), 1);
+}
+
+#[test]
+fn arrival_pressure_reaches_the_brain() {
+    let attrs = [40000, 64, 71, 5000, 5000 % 111, 64 % 7, 300, 9, 0, 0];
+    let mut state = StringState::new();
+
+    let mut idle = Brain::new();
+    let calm = idle.process_at(&attrs, 0, &mut state);
+
+    let mut flooded = Brain::new();
+    let mut last = calm;
+    // 10k pps for two seconds
+    for i in 0..20_000u64 {
+        last = flooded.process_at(&attrs, i * 100_000, &mut state);
+    }
+    assert!(last.arrival.rate_pps[SECOND] > 5_000.0);
+    assert!(last.impact_energy > calm.impact_energy);
+    assert!(flooded.lumis.arrival_pressure() > idle.lumis.arrival_pressure());
+
+    // Untimed processing stays neutral
+    let untimed = Brain::new().process(&attrs, &mut state);
+    assert_eq!(untimed.arrival, Arrival::default());
+}
@@ -0,0 +1,231 @@
+// XDP emulator: parsing and feature-vector checks for the kernel fold
+// (IPv4/IPv6, 802.1Q/QinQ, TCP/UDP ports and flags).
+
+use tiger_delta_ai_safety::fixed;
+use tiger_delta_ai_safety::xdp_emulator::{
+    feature_vector, fold, parse_frame, prob_to_fixed, Verdict, XdpEmulator,
+    DEFAULT_PHI_SALT, DEFAULT_PI_SALT, DROP_ALWAYS, IPPROTO_TCP, IPPROTO_UDP,
+};
+
+fn eth(ethertype: u16) -> Vec<u8> {
+    let mut f = vec![0u8; 12];
+    f.extend_from_slice(&ethertype.to_be_bytes());
+    f
+}
+
+fn vlan(f: &mut Vec<u8>, id: u16, inner: u16) {
+    f.extend_from_slice(&id.to_be_bytes());
+    f.extend_from_slice(&inner.to_be_bytes());
+}
+
+fn ipv4(f: &mut Vec<u8>, proto: u8, src: [u8; 4], dst: [u8; 4], l4_len: usize) {
+    let tot_len = (20 + l4_len) as u16;
+    f.extend_from_slice(&[0x45, 0]);
+    f.extend_from_slice(&tot_len.to_be_bytes());
+    f.extend_from_slice(&[0, 0, 0, 0, 64, proto, 0, 0]);
+    f.extend_from_slice(&src);
+    f.extend_from_slice(&dst);
+}
+
+fn ipv6(f: &mut Vec<u8>, next: u8, src: [u8; 16], dst: [u8; 16], l4_len: usize) {
+    f.extend_from_slice(&[0x60, 0, 0, 0]);
+    f.extend_from_slice(&(l4_len as u16).to_be_bytes());
+    f.extend_from_slice(&[next, 64]);
+    f.extend_from_slice(&src);
+    f.extend_from_slice(&dst);
+}
+
+fn tcp(f: &mut Vec<u8>, sport: u16, dport: u16, flags: u8) {
+    f.extend_from_slice(&sport.to_be_bytes());
+    f.extend_from_slice(&dport.to_be_bytes());
+    f.extend_from_slice(&[0; 8]);
+    f.extend_from_slice(&[0x50, flags, 0xFF, 0xFF, 0, 0, 0, 0]);
+}
+
+fn udp(f: &mut Vec<u8>, sport: u16, dport: u16) {
+    f.extend_from_slice(&sport.to_be_bytes());
+    f.extend_from_slice(&dport.to_be_bytes());
+    f.extend_from_slice(&[0, 8, 0, 0]);
+}
+
+fn tcp_v4(sport: u16, dport: u16, flags: u8) -> Vec<u8> {
+    let mut f = eth(0x0800);
+    ipv4(&mut f, IPPROTO_TCP, [10, 0, 0, 1], [10, 0, 0, 2], 20);
+    tcp(&mut f, sport, dport, flags);
+    f
+}
+
+#[test]
+fn ipv4_tcp_ports_and_flags() {
+    let m = parse_frame(&tcp_v4(40000, 443, 0x02)).expect("parsed");
+
+    assert!(!m.is_v6);
+    assert_eq!(m.protocol, IPPROTO_TCP);
+    assert_eq!(u16::from_be(m.sport), 40000);
+    assert_eq!(u16::from_be(m.dport), 443);
+    assert_eq!(m.tcp_flags, 0x02);
+    assert_eq!(u32::from_be(m.saddr), u32::from_be_bytes([10, 0, 0, 1]));
+    assert_eq!(u16::from_be(m.len), 40);
+}
+
+#[test]
+fn ipv6_udp_is_folded() {
+    let mut f = eth(0x86DD);
+    let mut src = [0u8; 16];
+    src[0] = 0x20;
+    src[1] = 0x01;
+    src[15] = 1;
+    ipv6(&mut f, IPPROTO_UDP, src, [0xFE; 16], 8);
+    udp(&mut f, 5353, 53);
+
+    let m = parse_frame(&f).expect("IPv6 must no longer be skipped");
+    assert!(m.is_v6);
+    assert_eq!(m.protocol, IPPROTO_UDP);
+    assert_eq!(u16::from_be(m.dport), 53);
+    assert_eq!(m.tcp_flags, 0);
+    assert_ne!(m.saddr, 0);
+}
+
+#[test]
+fn vlan_and_qinq_tags() {
+    let mut single = eth(0x8100);
+    vlan(&mut single, 0x2064, 0x0800); // PCP bits set, id 100
+    ipv4(&mut single, IPPROTO_UDP, [192, 0, 2, 1], [192, 0, 2, 2], 8);
+    udp(&mut single, 1000, 2000);
+    let m = parse_frame(&single).expect("802.1Q");
+    assert_eq!(m.vlan, 100);
+    assert_eq!(u16::from_be(m.sport), 1000);
+
+    let mut qinq = eth(0x88A8);
+    vlan(&mut qinq, 10, 0x8100);
+    vlan(&mut qinq, 200, 0x0800);
+    ipv4(&mut qinq, IPPROTO_UDP, [192, 0, 2, 1], [192, 0, 2, 2], 8);
+    udp(&mut qinq, 1000, 2000);
+    let m = parse_frame(&qinq).expect("QinQ");
+    assert_eq!(m.vlan, 200, "innermost id wins");
+}
+
+#[test]
+fn truncated_and_non_ip_pass_unfolded() {
+    let mut f = tcp_v4(1, 2, 0x10);
+    f.truncate(14 + 10);
+    assert!(parse_frame(&f).is_none());
+
+    let arp = eth(0x0806);
+    assert!(parse_frame(&arp).is_none());
+
+    let mut emu = XdpEmulator::new();
+    emu.enforce = true;
+    emu.drop_prob = DROP_ALWAYS;
+    emu.state = u64::MAX;
+    assert_eq!(emu.process(&arp, 0, 0), Verdict::Pass);
+    assert_eq!(emu.state, u64::MAX, "non-IP must not touch resonance state");
+}
+
+#[test]
+fn truncated_l4_keeps_l3_features() {
+    let mut f = tcp_v4(1, 2, 0x10);
+    f.truncate(14 + 20 + 10);
+    let m = parse_frame(&f).expect("L3 still parsed");
+    assert_eq!(m.sport, 0);
+    assert_eq!(m.tcp_flags, 0);
+}
+
+#[test]
+fn ports_flags_and_vlan_change_the_fold() {
+    let base = parse_frame(&tcp_v4(40000, 443, 0x02)).unwrap();
+    let fold_of = |m| fold(&feature_vector(&m, 0, 0), DEFAULT_PHI_SALT, DEFAULT_PI_SALT);
+
+    let other_port = parse_frame(&tcp_v4(40000, 22, 0x02)).unwrap();
+    let other_flags = parse_frame(&tcp_v4(40000, 443, 0x12)).unwrap();
+    let mut tagged = base;
+    tagged.vlan = 7;
+
+    assert_ne!(fold_of(base), fold_of(other_port));
+    assert_ne!(fold_of(base), fold_of(other_flags));
+    assert_ne!(fold_of(base), fold_of(tagged));
+}
+
+#[test]
+fn time_bucket_is_4ms() {
+    let m = parse_frame(&tcp_v4(1, 2, 0)).unwrap();
+    let bucket = 1u64 << 22;
+    assert_eq!(feature_vector(&m, 0, 0), feature_vector(&m, 0, bucket - 1));
+    assert_ne!(feature_vector(&m, 0, 0), feature_vector(&m, 0, bucket));
+}
+
+fn drop_ratio(emu: &mut XdpEmulator, frame: &[u8], n: usize) -> f64 {
+    let drops = (0..n)
+        .filter(|&i| emu.process(frame, 0, i as u64) == Verdict::Drop)
+        .count();
+    drops as f64 / n as f64
+}
+
+#[test]
+fn graduated_drop_tracks_probability() {
+    let frame = tcp_v4(40000, 443, 0x02);
+    let mut emu = XdpEmulator::new();
+    emu.enforce = true;
+    emu.seed_prng(7);
+
+    for p in [0.0, 0.1, 0.5, 0.9] {
+        emu.drop_prob = prob_to_fixed(p);
+        let ratio = drop_ratio(&mut emu, &frame, 20_000);
+        assert!((ratio - p).abs() < 0.02, "p={} ratio={}", p, ratio);
+    }
+
+    emu.drop_prob = prob_to_fixed(1.0);
+    assert_eq!(drop_ratio(&mut emu, &frame, 1_000), 1.0);
+}
+
+#[test]
+fn monitor_mode_never_drops() {
+    let frame = tcp_v4(40000, 443, 0x02);
+    let mut emu = XdpEmulator::new();
+    emu.drop_prob = DROP_ALWAYS;
+    assert_eq!(drop_ratio(&mut emu, &frame, 1_000), 0.0);
+}
+
+#[test]
+fn per_source_fraction_overrides_global() {
+    let frame = tcp_v4(40000, 443, 0x02);
+    let saddr = parse_frame(&frame).unwrap().saddr;
+    let mut emu = XdpEmulator::new();
+    emu.enforce = true;
+    emu.drop_prob = DROP_ALWAYS;
+    emu.source_policy.insert(saddr, 0);
+    assert_eq!(drop_ratio(&mut emu, &frame, 1_000), 0.0);
+
+    emu.drop_prob = 0;
+    emu.source_policy.insert(saddr, DROP_ALWAYS);
+    assert_eq!(drop_ratio(&mut emu, &frame, 1_000), 1.0);
+}
+
+#[test]
+fn fixed_point_views_match_the_kernel_encodings() {
+    let frame = tcp_v4(40000, 443, 0x02);
+    let saddr = parse_frame(&frame).unwrap().saddr;
+    let mut emu = XdpEmulator::new();
+    emu.enforce = true;
+    emu.seed_prng(11);
+
+    // The EMA state is a fraction of 2^64; its top half is Q32.32
+    emu.state = 0xC000_0000_0000_0000;
+    assert_eq!(emu.resonance_level(), 3 * fixed::ONE / 4);
+    for i in 0..100 {
+        emu.process(&frame, 0, i);
+        assert!((0..fixed::ONE).contains(&emu.resonance_level()));
+    }
+
+    for p in [0.0, 0.25, 0.6] {
+        emu.drop_prob = prob_to_fixed(p);
+        let level = emu.drop_level(saddr);
+        assert!((fixed::to_f64(level) - p).abs() < 1e-9);
+        let ratio = drop_ratio(&mut emu, &frame, 20_000);
+        assert!((ratio - fixed::to_f64(level)).abs() < 0.02, "p={} ratio={}", p, ratio);
+    }
+    emu.source_policy.insert(saddr, DROP_ALWAYS);
+    assert_eq!(emu.drop_level(saddr), fixed::ONE);
+    emu.enforce = false;
+    assert_eq!(emu.drop_level(saddr), 0);
+}